@@ -0,0 +1,123 @@
+use crate::common::{AocError, AocResult, IntoAocResult, ParamInfo, SolverContext};
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+/// Parameters recognized by every animated day.
+pub const PARAMS: [ParamInfo; 3] = [
+    ParamInfo {
+        name: "animate",
+        values: "terminal|files",
+        description: "Plays the animation in the terminal, or writes each frame to output/<day>.<part>.frameNNNN.txt.",
+        default: "no animation",
+    },
+    ParamInfo {
+        name: "fps",
+        values: "n",
+        description: "Frames per second in the terminal, or 0 for no delay.",
+        default: "10",
+    },
+    ParamInfo {
+        name: "max-frames",
+        values: "n",
+        description: "Stops animating after n frames.",
+        default: "no limit",
+    },
+];
+
+/// Where animation frames go.
+#[derive(Clone, Copy)]
+enum AnimationTarget {
+    /// Redraws each frame in the terminal.
+    Terminal,
+    /// Writes each frame to its own file in the output directory.
+    Files,
+}
+
+impl FromStr for AnimationTarget {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "terminal" => Ok(Self::Terminal),
+            "files" => Ok(Self::Files),
+            _ => Err(AocError::new("animate must be terminal or files")),
+        }
+    }
+}
+
+/// Plays the frames of an animation, as configured by the `animate`, `fps`,
+/// and `max-frames` parameters.
+///
+/// Frames are only rendered if an animation was requested, so solvers can
+/// produce frames unconditionally.
+pub struct Animator<'a> {
+    context: &'a SolverContext,
+    name: String,
+    target: Option<AnimationTarget>,
+    delay: Duration,
+    max_frames: usize,
+    frames: usize,
+}
+
+impl<'a> Animator<'a> {
+    /// Creates an animator for frames named after `name`, such as `25.A`.
+    pub fn new(context: &'a SolverContext, name: &str) -> AocResult<Self> {
+        let target = match context.param("animate") {
+            None => None,
+            Some(target) => Some(AnimationTarget::from_str(target)?),
+        };
+        let delay = match context.param("fps") {
+            None => Duration::from_millis(100),
+            Some(fps) => match fps
+                .parse::<u32>()
+                .into_aoc_result_msg("fps must be a non-negative integer")?
+            {
+                0 => Duration::ZERO,
+                fps => Duration::from_secs(1) / fps,
+            },
+        };
+        let max_frames = match context.param("max-frames") {
+            None => usize::MAX,
+            Some(max_frames) => max_frames
+                .parse::<usize>()
+                .into_aoc_result_msg("max-frames must be a non-negative integer")?,
+        };
+        Ok(Animator {
+            context,
+            name: name.to_owned(),
+            target,
+            delay,
+            max_frames,
+            frames: 0,
+        })
+    }
+
+    /// Shows the next frame, calling `render` to produce it only if needed.
+    pub fn frame<F>(&mut self, render: F) -> AocResult<()>
+    where
+        F: FnOnce() -> String,
+    {
+        let target = match self.target {
+            Some(target) if self.frames < self.max_frames => target,
+            _ => return Ok(()),
+        };
+        let frame = render();
+        match target {
+            AnimationTarget::Terminal => {
+                // Clear the screen and move the cursor back to the top left.
+                let mut stdout = io::stdout().lock();
+                write!(stdout, "\x1b[2J\x1b[H{}", frame).into_aoc_result()?;
+                stdout.flush().into_aoc_result()?;
+                thread::sleep(self.delay);
+            }
+            AnimationTarget::Files => {
+                let name = format!("{}.frame{:04}.txt", self.name, self.frames);
+                self.context.output().write(&name, frame.as_bytes())?;
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use crate::common::{AocResult, InputHash, OutputSink};
+
+/// Directory inside the output directory that snapshots are kept in.
+const CACHE_DIRECTORY: &str = "cache";
+
+fn file_name(name: &str, key: InputHash) -> String {
+    format!("{}/{}.{}.txt", CACHE_DIRECTORY, name, key)
+}
+
+/// Reads the snapshot saved under the given name and key, if there is one.
+///
+/// The key is normally the hash of whatever the snapshot was computed from,
+/// so a snapshot is only found again for the same work.
+pub fn load(output: &OutputSink, name: &str, key: InputHash) -> AocResult<Option<String>> {
+    output.read(&file_name(name, key))
+}
+
+/// Saves a snapshot under the given name and key, replacing any earlier one.
+pub fn store(output: &OutputSink, name: &str, key: InputHash, contents: &str) -> AocResult<()> {
+    output.write(&file_name(name, key), contents.as_bytes())
+}
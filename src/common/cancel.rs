@@ -0,0 +1,34 @@
+use crate::common::{AocError, AocResult};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+thread_local! {
+    /// Flag that is set once the solver running on this thread should stop.
+    static TOKEN: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Makes `check` fail on this thread once the token is set.
+pub fn set_token(token: Arc<AtomicBool>) {
+    TOKEN.with(|current| *current.borrow_mut() = Some(token));
+}
+
+/// Fails if the solver running on this thread was cancelled, such as when it
+/// ran past the time limit.
+///
+/// Long-running loops call this as they go, so that an abandoned solver stops
+/// instead of running on in the background. Solvers that were not started
+/// with a token are never cancelled.
+pub fn check() -> AocResult<()> {
+    let cancelled = TOKEN.with(|token| {
+        token
+            .borrow()
+            .as_ref()
+            .is_some_and(|token| token.load(Ordering::Relaxed))
+    });
+    if cancelled {
+        Err(AocError::new("solver was cancelled"))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use crate::common::math::OverflowPolicy;
+use crate::common::{AocError, AocResult, OutputSink, ParseOptions, ProgressSink, Warnings};
+use std::any::Any;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Input parsed by one part of a day, kept for the other part when both are
+/// solved together.
+#[derive(Default)]
+pub struct ParseCache {
+    parsed: Mutex<Option<Arc<dyn Any + Send + Sync>>>,
+}
+
+impl ParseCache {
+    /// Forgets the parsed input, so that the next solver parses it again.
+    pub fn clear(&self) {
+        *self.lock() = None;
+    }
+
+    /// The parsed input, if it has been parsed into a `T`.
+    fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.lock().clone()?.downcast().ok()
+    }
+
+    fn set<T: Send + Sync + 'static>(&self, parsed: Arc<T>) {
+        *self.lock() = Some(parsed);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Arc<dyn Any + Send + Sync>>> {
+        self.parsed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Options for a single solver run, shared with every solver.
+pub struct SolverContext {
+    strategy: Option<String>,
+    params: HashMap<String, String>,
+    threads: usize,
+    output: OutputSink,
+    progress: ProgressSink,
+    parse_cache: Arc<ParseCache>,
+    overflow: OverflowPolicy,
+    parse_options: ParseOptions,
+    warnings: Arc<Warnings>,
+    /// Time spent parsing the input, if the solver reported it.
+    parse_time: Mutex<Option<Duration>>,
+}
+
+impl SolverContext {
+    pub fn new(
+        strategy: Option<String>,
+        params: HashMap<String, String>,
+        threads: usize,
+        output: OutputSink,
+        overflow: OverflowPolicy,
+        strict: bool,
+        warnings: Arc<Warnings>,
+    ) -> Self {
+        SolverContext {
+            strategy,
+            params,
+            threads,
+            output,
+            progress: ProgressSink::default(),
+            parse_cache: Arc::default(),
+            overflow,
+            parse_options: ParseOptions::new(strict, Arc::clone(&warnings)),
+            warnings,
+            parse_time: Mutex::new(None),
+        }
+    }
+
+    /// Reports the progress of long-running solvers to the given sink.
+    pub fn with_progress(self, progress: ProgressSink) -> Self {
+        SolverContext { progress, ..self }
+    }
+
+    /// Keeps parsed input in the given cache, which may be shared with the
+    /// context of the other part.
+    pub fn with_parse_cache(self, parse_cache: Arc<ParseCache>) -> Self {
+        SolverContext {
+            parse_cache,
+            ..self
+        }
+    }
+
+    /// The name of the strategy requested on the command line, if any.
+    pub fn strategy(&self) -> Option<&str> {
+        self.strategy.as_deref()
+    }
+
+    /// Parses the requested strategy into the solver's own strategy type,
+    /// falling back to `default` when no strategy was requested.
+    pub fn strategy_or<T>(&self, default: T) -> AocResult<T>
+    where
+        T: FromStr<Err = AocError>,
+    {
+        match self.strategy() {
+            None => Ok(default),
+            Some(strategy) => T::from_str(strategy),
+        }
+    }
+
+    /// The raw value of a solver parameter given on the command line, if any.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|value| value.as_str())
+    }
+
+    /// Fails if the parameter is set, since what it asks for needs a feature
+    /// this build does not have.
+    #[cfg(not(feature = "visualize"))]
+    pub fn reject_param(&self, name: &str, feature: &str) -> AocResult<()> {
+        match self.param(name) {
+            None => Ok(()),
+            Some(_) => Err(AocError::new(format!(
+                "{} requires the {} feature, which this build does not have",
+                name, feature
+            ))),
+        }
+    }
+
+    /// The number of threads solvers may use for parallel work.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Where solvers write artifacts other than the answer itself.
+    pub fn output(&self) -> &OutputSink {
+        &self.output
+    }
+
+    /// Where long-running solvers report how far they have gotten.
+    pub fn progress(&self) -> &ProgressSink {
+        &self.progress
+    }
+
+    /// How arithmetic that overflows should be handled.
+    pub fn overflow(&self) -> OverflowPolicy {
+        self.overflow
+    }
+
+    /// Options for parsing the input.
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.parse_options
+    }
+
+    /// Where solvers and parsers report non-fatal issues.
+    pub fn warnings(&self) -> &Warnings {
+        &self.warnings
+    }
+
+    /// Parses the input, timing it apart from the rest of the solver.
+    pub fn parse<T, F>(&self, parse: F) -> AocResult<T>
+    where
+        F: FnOnce() -> AocResult<T>,
+    {
+        let now = Instant::now();
+        let result = parse();
+        let elapsed = now.elapsed();
+        let mut parse_time = self.lock_parse_time();
+        *parse_time = Some(parse_time.unwrap_or_default() + elapsed);
+        result
+    }
+
+    /// Gets the input parsed by an earlier solver sharing this context's
+    /// parse cache, or parses it with `parse` and keeps it for the next one.
+    ///
+    /// The cache is not locked while parsing, so a solver that is slow to
+    /// parse, or abandoned after a timeout, does not hold up the other part.
+    /// If both parts parse at once, the input parsed last is kept.
+    pub fn parse_shared<T, F>(&self, parse: F) -> AocResult<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce() -> AocResult<T>,
+    {
+        if let Some(parsed) = self.parse_cache.get() {
+            return Ok(parsed);
+        }
+        let parsed = Arc::new(parse()?);
+        self.parse_cache.set(Arc::clone(&parsed));
+        Ok(parsed)
+    }
+
+    /// The cache that `parse_shared` keeps parsed input in.
+    pub fn parse_cache(&self) -> &ParseCache {
+        &self.parse_cache
+    }
+
+    /// Takes the time spent in `parse` since the last call, or `None` if the
+    /// solver never called it.
+    pub fn take_parse_time(&self) -> Option<Duration> {
+        self.lock_parse_time().take()
+    }
+
+    fn lock_parse_time(&self) -> MutexGuard<'_, Option<Duration>> {
+        // Parsing that panicked cannot leave the time in a broken state.
+        self.parse_time
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for SolverContext {
+    fn default() -> Self {
+        SolverContext::new(
+            None,
+            HashMap::new(),
+            1,
+            OutputSink::default(),
+            OverflowPolicy::BigInt,
+            false,
+            Arc::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_shared_parses_once_until_cleared() {
+        let context = SolverContext::default();
+        assert_eq!(*context.parse_shared(|| Ok(1)).unwrap(), 1);
+        assert_eq!(*context.parse_shared(|| Ok(2)).unwrap(), 1);
+        context.parse_cache().clear();
+        assert_eq!(*context.parse_shared(|| Ok(3)).unwrap(), 3);
+    }
+
+    #[test]
+    fn parse_shared_does_not_keep_failed_parses() {
+        let context = SolverContext::default();
+        assert!(context
+            .parse_shared::<i32, _>(|| Err(AocError::new("bad input")))
+            .is_err());
+        assert_eq!(*context.parse_shared(|| Ok(1)).unwrap(), 1);
+    }
+
+    #[test]
+    fn parse_shared_does_not_lock_the_cache_while_parsing() {
+        // A parse that uses the cache itself would deadlock if the cache
+        // stayed locked.
+        let context = SolverContext::default();
+        let outer = context.parse_shared(|| {
+            let inner = context.parse_shared(|| Ok(1))?;
+            Ok(*inner + 1)
+        });
+        assert_eq!(*outer.unwrap(), 2);
+        assert_eq!(*context.parse_shared(|| Ok(0)).unwrap(), 2);
+    }
+}
@@ -1,23 +1,123 @@
+use std::error::Error;
 use std::fmt::{Display, Formatter, Result as DisplayResult};
 
+#[derive(Debug)]
 pub struct AocError {
     message: String,
+    /// Line of the input the error happened on, counting from 1.
+    line: Option<usize>,
+    /// Column of the line the error happened at, counting from 1.
+    column: Option<usize>,
+    /// The input around the error, shown below the message.
+    explanation: Option<String>,
+    /// Whether the error is that the input could not be found.
+    missing_input: bool,
 }
 
 impl AocError {
     pub fn new<S: Into<String>>(message: S) -> AocError {
         AocError {
             message: message.into(),
+            line: None,
+            column: None,
+            explanation: None,
+            missing_input: false,
         }
     }
+
+    /// Records the input line the error happened on, unless one is already
+    /// recorded by a parser closer to the error.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    /// Records the column of the line the error happened at.
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column.get_or_insert(column);
+        self
+    }
+
+    /// Marks the error as the input not being found, which the program
+    /// reports with its own exit code.
+    pub fn missing_input(mut self) -> Self {
+        self.missing_input = true;
+        self
+    }
+
+    pub fn is_missing_input(&self) -> bool {
+        self.missing_input
+    }
+
+    /// Attaches the lines of the input around the error, with a caret under
+    /// the failing column if it is known.
+    pub fn explained(mut self, input: &str) -> Self {
+        let line = match self.line {
+            None => {
+                self.explanation = Some(String::from(
+                    "  (no input location was recorded for this error)",
+                ));
+                return self;
+            }
+            Some(line) => line,
+        };
+        let first = line.saturating_sub(2).max(1);
+        let width = (line + 1).to_string().len();
+        let mut explanation = Vec::new();
+        for (number, text) in input.lines().enumerate().map(|(i, text)| (i + 1, text)) {
+            if number < first {
+                continue;
+            }
+            if number > line + 1 {
+                break;
+            }
+            let marker = if number == line { '>' } else { ' ' };
+            explanation.push(format!(
+                "{} {:>width$} | {}",
+                marker,
+                number,
+                text,
+                width = width
+            ));
+            if let (true, Some(column)) = (number == line, self.column) {
+                explanation.push(format!(
+                    "  {:>width$} | {:>column$}",
+                    "",
+                    "^",
+                    width = width,
+                    column = column
+                ));
+            }
+        }
+        self.explanation = Some(explanation.join("\n"));
+        self
+    }
+
+    /// The error message, without the `Error:` prefix added by `Display`.
+    pub fn message(&self) -> String {
+        let mut message = match (self.line, self.column) {
+            (None, None) => String::new(),
+            (Some(line), None) => format!("line {}: ", line),
+            (None, Some(column)) => format!("column {}: ", column),
+            (Some(line), Some(column)) => format!("line {}, column {}: ", line, column),
+        };
+        message += &self.message;
+        if let Some(explanation) = &self.explanation {
+            message.push('\n');
+            message += explanation;
+        }
+        message
+    }
 }
 
 impl Display for AocError {
     fn fmt(&self, f: &mut Formatter) -> DisplayResult {
-        write!(f, "Error: {}", self.message)
+        write!(f, "Error: {}", self.message())
     }
 }
 
+impl Error for AocError {}
+
 pub type AocResult<T> = Result<T, AocError>;
 
 pub trait IntoAocResult<T> {
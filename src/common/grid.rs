@@ -0,0 +1,181 @@
+use crate::common::{AocError, AocResult};
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+/// A pair of buffers for stepping a state that is rebuilt from its previous
+/// value, such as a grid where every cell depends on its old neighbors.
+///
+/// Each step reads the front buffer and writes the back buffer, then swaps
+/// them, so the allocations of both buffers are reused across steps.
+#[derive(Clone)]
+pub struct DoubleBuffer<T> {
+    front: T,
+    back: T,
+}
+
+impl<T> DoubleBuffer<T> {
+    /// Creates a double buffer reading from `front`, with `back` as scratch
+    /// space for the next step.
+    pub fn new(front: T, back: T) -> Self {
+        DoubleBuffer { front, back }
+    }
+
+    /// The current state.
+    pub fn read(&self) -> &T {
+        &self.front
+    }
+
+    /// The current state along with the scratch space for the next state,
+    /// for writing the next state based on the current one.
+    pub fn write(&mut self) -> (&T, &mut T) {
+        (&self.front, &mut self.back)
+    }
+
+    /// Makes the written state current.
+    pub fn swap(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Takes the current state, dropping the scratch space.
+    pub fn into_inner(self) -> T {
+        self.front
+    }
+}
+
+/// Offsets to the cells around a cell.
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid stored as a flat vector, row by row.
+#[derive(Clone)]
+pub struct Grid2D<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid2D<T> {
+    /// Builds a grid from its rows, which must all have the same width.
+    pub fn from_rows<I>(rows: I) -> AocResult<Self>
+    where
+        I: IntoIterator<Item = Vec<T>>,
+    {
+        let mut cells = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        for row in rows {
+            if height == 0 {
+                width = row.len();
+            } else if row.len() != width {
+                return Err(AocError::new(format!(
+                    "row has width {}, expected {}",
+                    row.len(),
+                    width
+                ))
+                .at_line(height + 1));
+            }
+            cells.extend(row);
+            height += 1;
+        }
+        Ok(Grid2D {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// Number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Whether the grid has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Maps a flat index back to its `(x, y)` point.
+    pub fn point(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// Iterator over the rows of the grid.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // Chunks of zero length are not allowed, but a grid of zero width has
+        // no cells anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Iterator over the indices of the cells around the given index,
+    /// including diagonals.
+    pub fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> {
+        let (x, y) = self.point(index);
+        let (width, height) = (self.width, self.height);
+        DIRECTIONS.iter().filter_map(move |&(dx, dy)| {
+            // Stepping off the top or left edge wraps around to a huge value,
+            // so one comparison checks both edges.
+            let x = x.wrapping_add(dx as usize);
+            let y = y.wrapping_add(dy as usize);
+            (x < width && y < height).then(|| y * width + x)
+        })
+    }
+
+    /// Precomputes the neighbor indices of every cell, for grids that look up
+    /// neighbors more often than they change shape.
+    pub fn neighbor_lists(&self) -> NeighborLists {
+        let mut starts = Vec::with_capacity(self.len() + 1);
+        let mut indices = Vec::with_capacity(self.len() * DIRECTIONS.len());
+        starts.push(0);
+        for index in 0..self.len() {
+            indices.extend(self.neighbors(index));
+            starts.push(indices.len());
+        }
+        NeighborLists { starts, indices }
+    }
+}
+
+/// The neighbor indices of every cell of a grid, created by
+/// `Grid2D::neighbor_lists`.
+///
+/// The lists are stored back to back in one vector, so looking one up does
+/// not chase a pointer per cell.
+#[derive(Clone)]
+pub struct NeighborLists {
+    /// Where the list of each cell starts in `indices`, with one extra entry
+    /// for where the last list ends.
+    starts: Vec<usize>,
+    indices: Vec<usize>,
+}
+
+impl NeighborLists {
+    /// The indices of the cells next to the given index.
+    pub fn get(&self, index: usize) -> &[usize] {
+        &self.indices[self.starts[index]..self.starts[index + 1]]
+    }
+}
+
+impl<T> Index<usize> for Grid2D<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.cells[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid2D<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.cells[index]
+    }
+}
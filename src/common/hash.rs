@@ -0,0 +1,24 @@
+use std::fmt::{Display, Formatter, Result as DisplayResult};
+
+/// A hash identifying the contents of an input file (64-bit FNV-1a).
+///
+/// Unlike the standard library hashers, the result is stable across runs
+/// and compiler versions, so it can be stored alongside results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InputHash(u64);
+
+impl InputHash {
+    pub fn new(input: &str) -> Self {
+        const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+        InputHash(input.bytes().fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        }))
+    }
+}
+
+impl Display for InputHash {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        write!(f, "{:016x}", self.0)
+    }
+}
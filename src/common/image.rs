@@ -0,0 +1,126 @@
+use crate::common::{AocResult, OutputSink};
+
+/// Builds a binary Netpbm image from its magic number and pixel data.
+fn encode(
+    magic: &str,
+    width: usize,
+    height: usize,
+    max_value: Option<u8>,
+    data: Vec<u8>,
+) -> Vec<u8> {
+    let mut image = match max_value {
+        None => format!("{}\n{} {}\n", magic, width, height),
+        Some(max_value) => format!("{}\n{} {}\n{}\n", magic, width, height, max_value),
+    }
+    .into_bytes();
+    image.extend(data);
+    image
+}
+
+/// Writes a black and white PBM image, where `pixel(x, y)` is true for black pixels.
+pub fn write_pbm<F>(
+    output: &OutputSink,
+    name: &str,
+    width: usize,
+    height: usize,
+    pixel: F,
+) -> AocResult<()>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    // Each row is packed into bytes, most significant bit first.
+    let row_bytes = width.div_ceil(8);
+    let mut data = vec![0u8; row_bytes * height];
+    for y in 0..height {
+        for x in 0..width {
+            if pixel(x, y) {
+                data[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+    output.write(name, &encode("P4", width, height, None, data))
+}
+
+/// Writes a grayscale PGM image, where `pixel(x, y)` is the brightness of each pixel.
+pub fn write_pgm<F>(
+    output: &OutputSink,
+    name: &str,
+    width: usize,
+    height: usize,
+    pixel: F,
+) -> AocResult<()>
+where
+    F: Fn(usize, usize) -> u8,
+{
+    let data = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| pixel(x, y))
+        .collect();
+    output.write(name, &encode("P5", width, height, Some(255), data))
+}
+
+/// Writes a color PPM image, where `pixel(x, y)` is the RGB color of each pixel.
+pub fn write_ppm<F>(
+    output: &OutputSink,
+    name: &str,
+    width: usize,
+    height: usize,
+    pixel: F,
+) -> AocResult<()>
+where
+    F: Fn(usize, usize) -> [u8; 3],
+{
+    let data = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| pixel(x, y))
+        .collect();
+    output.write(name, &encode("P6", width, height, Some(255), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Writes an image with `write` into a fresh directory, returning its bytes.
+    fn written<F>(name: &str, write: F) -> Vec<u8>
+    where
+        F: FnOnce(&OutputSink, &str) -> AocResult<()>,
+    {
+        let directory: PathBuf =
+            std::env::temp_dir().join(format!("aoc-image-{}-{}", std::process::id(), name));
+        let output = OutputSink::new(&directory);
+        write(&output, name).unwrap();
+        let bytes = fs::read(directory.join(name)).unwrap();
+        fs::remove_dir_all(directory).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn pbm_packs_rows_into_bytes() {
+        let bytes = written("tiny.pbm", |output, name| {
+            write_pbm(output, name, 2, 2, |x, y| x == y)
+        });
+        assert_eq!(bytes, b"P4\n2 2\n\x80\x40");
+    }
+
+    #[test]
+    fn pgm_writes_one_byte_per_pixel() {
+        let bytes = written("tiny.pgm", |output, name| {
+            write_pgm(output, name, 2, 2, |x, y| (10 * (2 * y + x)) as u8)
+        });
+        assert_eq!(bytes, b"P5\n2 2\n255\n\x00\x0a\x14\x1e");
+    }
+
+    #[test]
+    fn ppm_writes_three_bytes_per_pixel() {
+        let bytes = written("tiny.ppm", |output, name| {
+            write_ppm(output, name, 2, 2, |x, y| [x as u8, y as u8, 255])
+        });
+        assert_eq!(
+            bytes,
+            b"P6\n2 2\n255\n\x00\x00\xff\x01\x00\xff\x00\x01\xff\x01\x01\xff"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+/// A strategy a day can be solved with.
+pub struct StrategyInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// A parameter a day recognizes.
+pub struct ParamInfo {
+    pub name: &'static str,
+    /// The values the parameter accepts.
+    pub values: &'static str,
+    pub description: &'static str,
+    /// The behavior when the parameter is not given.
+    pub default: &'static str,
+}
+
+/// The strategies and parameters a day recognizes, for introspection.
+pub struct DayInfo {
+    /// Strategies for `--strategy`, with the default first.
+    pub strategies: &'static [StrategyInfo],
+    /// Parameters for `--param`.
+    pub params: &'static [ParamInfo],
+    /// Whether the day also recognizes the parameters in `animate::PARAMS`.
+    pub animated: bool,
+}
+
+impl DayInfo {
+    /// Info for a day without strategies or parameters.
+    pub const EMPTY: DayInfo = DayInfo {
+        strategies: &[],
+        params: &[],
+        animated: false,
+    };
+}
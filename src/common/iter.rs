@@ -0,0 +1,118 @@
+use std::str::CharIndices;
+
+/// Iterator over overlapping windows of a string, created by `str_windows`.
+pub struct StrWindows<'a> {
+    src: &'a str,
+    starts: CharIndices<'a>,
+    ends: CharIndices<'a>,
+}
+
+impl<'a> Iterator for StrWindows<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The end iterator runs `size - 1` characters ahead of the start
+        // iterator, so each window ends after the character it yields.
+        let (last, ch) = self.ends.next()?;
+        let (from, _) = self.starts.next()?;
+        Some(&self.src[from..last + ch.len_utf8()])
+    }
+}
+
+/// Creates an iterator over slices of the string in overlapping windows of
+/// `size` characters, like `slice::windows` but respecting UTF-8 boundaries.
+///
+/// Yields nothing if `size` is zero or longer than the string.
+pub fn str_windows(src: &str, size: usize) -> StrWindows<'_> {
+    let mut ends = if size == 0 { "" } else { src }.char_indices();
+    for _ in 1..size {
+        ends.next();
+    }
+    StrWindows {
+        src,
+        starts: src.char_indices(),
+        ends,
+    }
+}
+
+/// Creates an iterator over windows of `size` elements of the slice that
+/// start every `stride` elements, like `slice::windows` with a step.
+///
+/// Yields nothing if `size` or `stride` is zero or `size` is longer than the
+/// slice.
+pub fn strided_windows<T>(
+    slice: &[T],
+    size: usize,
+    stride: usize,
+) -> impl Iterator<Item = &[T]> + '_ {
+    let count = if size == 0 || stride == 0 || size > slice.len() {
+        0
+    } else {
+        (slice.len() - size) / stride + 1
+    };
+    (0..count).map(move |i| &slice[i * stride..i * stride + size])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn windows(src: &str, size: usize) -> Vec<&str> {
+        str_windows(src, size).collect()
+    }
+
+    #[test]
+    fn str_windows_of_ascii() {
+        assert_eq!(windows("NNCB", 2), ["NN", "NC", "CB"]);
+        assert_eq!(windows("abc", 1), ["a", "b", "c"]);
+        assert_eq!(windows("abc", 3), ["abc"]);
+    }
+
+    #[test]
+    fn str_windows_of_multi_byte_characters() {
+        assert_eq!(windows("café", 2), ["ca", "af", "fé"]);
+        assert_eq!(windows("éàü", 2), ["éà", "àü"]);
+        assert_eq!(windows("a🦀b🎄", 2), ["a🦀", "🦀b", "b🎄"]);
+        assert_eq!(windows("🦀🎄🦀", 3), ["🦀🎄🦀"]);
+    }
+
+    #[test]
+    fn str_windows_longer_than_the_string() {
+        assert!(windows("abc", 4).is_empty());
+        assert!(windows("é🦀", 3).is_empty());
+        assert!(windows("", 1).is_empty());
+    }
+
+    #[test]
+    fn str_windows_of_size_zero() {
+        assert!(windows("abc", 0).is_empty());
+        assert!(windows("", 0).is_empty());
+    }
+
+    #[test]
+    fn strided_windows_with_stride_one_match_windows() {
+        let slice = [1, 2, 3, 4, 5];
+        assert!(strided_windows(&slice, 2, 1).eq(slice.windows(2)));
+        assert!(strided_windows(&slice, 5, 1).eq(slice.windows(5)));
+    }
+
+    #[test]
+    fn strided_windows_skip_by_stride() {
+        let slice = [1, 2, 3, 4, 5, 6, 7];
+        let windows = strided_windows(&slice, 3, 2).collect::<Vec<_>>();
+        assert_eq!(windows, [&[1, 2, 3][..], &[3, 4, 5], &[5, 6, 7]]);
+        let windows = strided_windows(&slice, 2, 3).collect::<Vec<_>>();
+        assert_eq!(windows, [&[1, 2][..], &[4, 5]]);
+        // A stride past the window leaves gaps between windows.
+        let windows = strided_windows(&slice, 1, 4).collect::<Vec<_>>();
+        assert_eq!(windows, [&[1][..], &[5]]);
+    }
+
+    #[test]
+    fn strided_windows_yield_nothing_for_bad_sizes() {
+        let slice = [1, 2, 3];
+        assert_eq!(strided_windows(&slice, 0, 1).count(), 0);
+        assert_eq!(strided_windows(&slice, 1, 0).count(), 0);
+        assert_eq!(strided_windows(&slice, 4, 1).count(), 0);
+    }
+}
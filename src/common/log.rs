@@ -0,0 +1,92 @@
+use std::fmt::Arguments;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Verbosity at which debug messages are logged, set with `-v`.
+pub const DEBUG: u8 = 1;
+
+/// Verbosity at which trace messages are logged, set with `-vv`.
+pub const TRACE: u8 = 2;
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Messages kept instead of written to standard error, while capturing.
+static CAPTURED: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// Sets the verbosity that messages are logged at, where 0 logs nothing.
+pub fn set_verbosity(verbosity: u8) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+}
+
+/// Whether messages at the given verbosity are logged.
+pub fn enabled(verbosity: u8) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= verbosity
+}
+
+/// Name of the module a message comes from, such as `day19`.
+pub fn source(module_path: &'static str) -> &'static str {
+    module_path.rsplit("::").next().unwrap_or(module_path)
+}
+
+/// Starts keeping logged messages instead of writing them to standard error,
+/// such as while the terminal dashboard owns the screen.
+#[cfg(feature = "tui")]
+pub fn start_capture() {
+    *CAPTURED.lock().unwrap_or_else(PoisonError::into_inner) = Some(Vec::new());
+}
+
+/// Stops capturing, returning the messages logged since the capture started.
+#[cfg(feature = "tui")]
+pub fn take_capture() -> Vec<String> {
+    CAPTURED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+        .unwrap_or_default()
+}
+
+/// Writes a logged message to standard error, or keeps it if messages are
+/// being captured.
+pub fn write(message: Arguments) {
+    match CAPTURED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_mut()
+    {
+        Some(messages) => messages.push(message.to_string()),
+        None => eprintln!("{}", message),
+    }
+}
+
+/// Logs a message to standard error at the given verbosity.
+///
+/// The message is only formatted if it will be logged, so a disabled message
+/// costs a single load.
+macro_rules! log {
+    ($verbosity:expr, $label:literal, $($arg:tt)+) => {
+        if $crate::common::log::enabled($verbosity) {
+            $crate::common::log::write(format_args!(
+                "[{} {}] {}",
+                $label,
+                $crate::common::log::source(module_path!()),
+                format_args!($($arg)+)
+            ));
+        }
+    };
+}
+
+/// Logs a message with `-v`.
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::common::log::log!($crate::common::log::DEBUG, "debug", $($arg)+)
+    };
+}
+
+/// Logs a message with `-vv`, for output too detailed for `-v`.
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        $crate::common::log::log!($crate::common::log::TRACE, "trace", $($arg)+)
+    };
+}
+
+pub(crate) use {debug, log, trace};
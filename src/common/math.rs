@@ -0,0 +1,205 @@
+use crate::common::{AocError, AocResult};
+use num_traits::ops::saturating::{SaturatingAdd, SaturatingMul};
+use num_traits::{CheckedAdd, CheckedMul, PrimInt, Unsigned, WrappingAdd, WrappingMul, Zero};
+use std::str::FromStr;
+
+/// How solvers handle arithmetic that overflows.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Fails with an error.
+    Check,
+    /// Clamps the result to the bounds of the type.
+    Saturate,
+    /// Wraps the result around the bounds of the type.
+    Wrap,
+    /// Switches to big integers in solvers that support them, and otherwise
+    /// fails like `Check`.
+    BigInt,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "check" => Ok(Self::Check),
+            "saturate" => Ok(Self::Saturate),
+            "wrap" => Ok(Self::Wrap),
+            "bigint" => Ok(Self::BigInt),
+            _ => Err(AocError::new(
+                "overflow must be check, saturate, wrap, or bigint",
+            )),
+        }
+    }
+}
+
+fn overflow_error() -> AocError {
+    AocError::new("arithmetic overflow (see --overflow)")
+}
+
+impl OverflowPolicy {
+    pub fn add<T>(&self, a: T, b: T) -> AocResult<T>
+    where
+        T: CheckedAdd + SaturatingAdd + WrappingAdd,
+    {
+        match self {
+            Self::Check | Self::BigInt => a.checked_add(&b).ok_or_else(overflow_error),
+            Self::Saturate => Ok(a.saturating_add(&b)),
+            Self::Wrap => Ok(a.wrapping_add(&b)),
+        }
+    }
+
+    pub fn mul<T>(&self, a: T, b: T) -> AocResult<T>
+    where
+        T: CheckedMul + SaturatingMul + WrappingMul,
+    {
+        match self {
+            Self::Check | Self::BigInt => a.checked_mul(&b).ok_or_else(overflow_error),
+            Self::Saturate => Ok(a.saturating_mul(&b)),
+            Self::Wrap => Ok(a.wrapping_mul(&b)),
+        }
+    }
+
+    pub fn sum<T, I>(&self, values: I) -> AocResult<T>
+    where
+        T: CheckedAdd + SaturatingAdd + WrappingAdd + Zero,
+        I: IntoIterator<Item = T>,
+    {
+        values
+            .into_iter()
+            .try_fold(T::zero(), |sum, value| self.add(sum, value))
+    }
+}
+
+/// Computes `(a + b) mod m` without overflowing.
+pub fn modadd<T: PrimInt + Unsigned>(a: T, b: T, m: T) -> T {
+    let (a, b) = (a % m, b % m);
+    // a + b >= m exactly when a >= m - b, which avoids computing a + b.
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// Computes `(a - b) mod m` without underflowing.
+pub fn modsub<T: PrimInt + Unsigned>(a: T, b: T, m: T) -> T {
+    let (a, b) = (a % m, b % m);
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+/// Computes `(a * b) mod m` without overflowing, by doubling and adding.
+pub fn modmul<T: PrimInt + Unsigned>(a: T, b: T, m: T) -> T {
+    let mut a = a % m;
+    let mut b = b % m;
+    let mut result = T::zero();
+    while b != T::zero() {
+        if b & T::one() == T::one() {
+            result = modadd(result, a, m);
+        }
+        a = modadd(a, a, m);
+        b = b >> 1;
+    }
+    result
+}
+
+/// Computes `base^exp mod m` by repeated squaring.
+pub fn modpow<T: PrimInt + Unsigned>(base: T, exp: T, m: T) -> T {
+    let mut base = base % m;
+    let mut exp = exp;
+    let mut result = T::one() % m;
+    while exp != T::zero() {
+        if exp & T::one() == T::one() {
+            result = modmul(result, base, m);
+        }
+        base = modmul(base, base, m);
+        exp = exp >> 1;
+    }
+    result
+}
+
+/// Finds `x` such that `a * x mod m == 1`, which only exists when `a` and `m`
+/// are coprime.
+pub fn modinv<T: PrimInt + Unsigned>(a: T, m: T) -> Option<T> {
+    // Extended Euclidean algorithm, keeping the coefficients of `a` reduced
+    // modulo `m` so they never go negative.
+    let (mut old_r, mut r) = (a % m, m);
+    let (mut old_s, mut s) = (T::one() % m, T::zero());
+    while r != T::zero() {
+        let q = old_r / r;
+        let next_r = old_r - q * r;
+        old_r = r;
+        r = next_r;
+        let next_s = modsub(old_s, modmul(q, s, m), m);
+        old_s = s;
+        s = next_s;
+    }
+    if old_r == T::one() {
+        Some(old_s)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modmul_known_values() {
+        assert_eq!(modmul(3u64, 4, 5), 2);
+        assert_eq!(modmul(0u64, 12345, 7), 0);
+        assert_eq!(modmul(123456789u64, 987654321, 1_000_000_007), 259106859);
+    }
+
+    #[test]
+    fn modmul_near_u64_limit() {
+        // (-1) * (-1) = 1 and (-1) * (-2) = 2 modulo u64::MAX.
+        assert_eq!(modmul(u64::MAX - 1, u64::MAX - 1, u64::MAX), 1);
+        assert_eq!(modmul(u64::MAX - 1, u64::MAX - 2, u64::MAX), 2);
+        assert_eq!(modmul(u64::MAX, u64::MAX, u64::MAX - 1), 1);
+    }
+
+    #[test]
+    fn modpow_known_values() {
+        assert_eq!(modpow(2u64, 10, 1000), 24);
+        assert_eq!(modpow(3u64, 0, 7), 1);
+        assert_eq!(modpow(5u64, 3, 1), 0);
+        assert_eq!(modpow(7u64, 1_000_000_005, 1_000_000_007), 142857144);
+    }
+
+    #[test]
+    fn modpow_near_u64_limit() {
+        // 2^64 = (2^64 - 1) + 1.
+        assert_eq!(modpow(2u64, 64, u64::MAX), 1);
+        assert_eq!(modpow(u64::MAX - 1, 3, u64::MAX), u64::MAX - 1);
+    }
+
+    #[test]
+    fn modinv_known_values() {
+        assert_eq!(modinv(3u64, 11), Some(4));
+        assert_eq!(modinv(10u64, 17), Some(12));
+        assert_eq!(modinv(1u64, 2), Some(1));
+    }
+
+    #[test]
+    fn modinv_without_inverse() {
+        assert_eq!(modinv(4u64, 8), None);
+        assert_eq!(modinv(0u64, 7), None);
+        assert_eq!(modinv(6u64, 9), None);
+    }
+
+    #[test]
+    fn modinv_near_u64_limit() {
+        // u64::MAX is odd, so 2 * 2^63 = 2^64 = u64::MAX + 1.
+        assert_eq!(modinv(2u64, u64::MAX), Some(1 << 63));
+        let m = u64::MAX - 58; // 2^64 - 59 is prime.
+        let a = u64::MAX - 100;
+        let inverse = modinv(a, m).unwrap();
+        assert_eq!(modmul(a, inverse, m), 1);
+    }
+}
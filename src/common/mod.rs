@@ -1,5 +1,37 @@
+pub mod animate;
+pub mod cache;
+pub mod cancel;
+mod context;
 mod error;
+pub mod grid;
+mod hash;
+#[cfg(feature = "visualize")]
+pub mod image;
+mod info;
+pub mod iter;
+pub mod log;
+pub mod math;
+mod output;
+pub mod parallel;
+mod parse;
+pub mod profile;
+mod progress;
+mod rng;
+pub mod search;
 mod solver;
+#[cfg(feature = "visualize")]
+pub mod svg;
+#[cfg(test)]
+pub mod testing;
+mod warnings;
 
+pub use context::{ParseCache, SolverContext};
 pub use error::{AocError, AocResult, IntoAocResult};
-pub use solver::{iAoc, SolverFn};
+pub use hash::InputHash;
+pub use info::{DayInfo, ParamInfo, StrategyInfo};
+pub use output::OutputSink;
+pub use parse::{parse_lines, ParseOptions};
+pub use progress::ProgressSink;
+pub use rng::Rng;
+pub use solver::{iAoc, ParserFn, SolutionValue, Solver, SolverFn};
+pub use warnings::{Warning, Warnings};
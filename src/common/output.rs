@@ -0,0 +1,42 @@
+use crate::common::{AocResult, IntoAocResult};
+use std::fs;
+use std::path::PathBuf;
+
+/// Destination for artifacts produced by solvers, such as rendered images.
+pub struct OutputSink {
+    directory: PathBuf,
+}
+
+impl OutputSink {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        OutputSink {
+            directory: directory.into(),
+        }
+    }
+
+    /// Writes an artifact with the given file name, replacing any existing one.
+    ///
+    /// The name may include subdirectories, which are created as needed.
+    pub fn write(&self, name: &str, contents: &[u8]) -> AocResult<()> {
+        let path = self.directory.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).into_aoc_result()?;
+        }
+        fs::write(path, contents).into_aoc_result()
+    }
+
+    /// Reads an artifact written by an earlier run, if it exists.
+    pub fn read(&self, name: &str) -> AocResult<Option<String>> {
+        let path = self.directory.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read_to_string(path).map(Some).into_aoc_result()
+    }
+}
+
+impl Default for OutputSink {
+    fn default() -> Self {
+        OutputSink::new("output")
+    }
+}
@@ -0,0 +1,59 @@
+use std::thread;
+
+/// Splits `items` into at most `threads` contiguous chunks of similar size.
+fn chunk_len(len: usize, threads: usize) -> usize {
+    let threads = threads.max(1);
+    len.div_ceil(threads).max(1)
+}
+
+/// Maps contiguous chunks of `items` with `f`, one chunk per thread, and
+/// returns the results in chunk order.
+///
+/// With a single thread, or without the `parallel` feature, `f` runs on the
+/// current thread over the whole slice.
+pub fn map_chunks<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&[T]) -> R + Sync,
+{
+    if threads <= 1 || items.len() <= 1 || !cfg!(feature = "parallel") {
+        return vec![f(items)];
+    }
+
+    let chunk_len = chunk_len(items.len(), threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || f(chunk)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+/// Runs `f` over contiguous chunks of `items`, one chunk per thread.
+///
+/// `f` receives the index of the first item in its chunk alongside the chunk
+/// itself. With a single thread, or without the `parallel` feature, `f` runs
+/// on the current thread over the whole slice.
+pub fn for_each_chunk_mut<T, F>(items: &mut [T], threads: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync,
+{
+    if threads <= 1 || items.len() <= 1 || !cfg!(feature = "parallel") {
+        return f(0, items);
+    }
+
+    let chunk_len = chunk_len(items.len(), threads);
+    let f = &f;
+    thread::scope(|scope| {
+        for (i, chunk) in items.chunks_mut(chunk_len).enumerate() {
+            scope.spawn(move || f(i * chunk_len, chunk));
+        }
+    });
+}
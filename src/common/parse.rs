@@ -0,0 +1,68 @@
+use crate::common::{AocError, AocResult, Warnings};
+use std::sync::Arc;
+
+/// Parses each line of the input, recording the line number in any error.
+pub fn parse_lines<T, F>(input: &str, mut parse: F) -> AocResult<Vec<T>>
+where
+    F: FnMut(&str) -> AocResult<T>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse(line).map_err(|err| err.at_line(i + 1)))
+        .collect()
+}
+
+/// Options shared by the input parsers.
+///
+/// Parsers are lenient by default: input they can make sense of, such as
+/// trailing tokens or a definition given twice, is accepted with a warning.
+/// In strict mode, the same input is rejected.
+#[derive(Clone, Default)]
+pub struct ParseOptions {
+    strict: bool,
+    warnings: Arc<Warnings>,
+}
+
+impl ParseOptions {
+    /// Creates parse options that report tolerated input to `warnings`.
+    pub fn new(strict: bool, warnings: Arc<Warnings>) -> Self {
+        ParseOptions { strict, warnings }
+    }
+
+    /// Reports input that lenient parsing tolerates, failing in strict mode.
+    pub fn tolerate<S: Into<String>>(&self, message: S) -> AocResult<()> {
+        let message = message.into();
+        if self.strict {
+            Err(AocError::new(message))
+        } else {
+            self.warnings.push(message);
+            Ok(())
+        }
+    }
+
+    /// Reports input that is valid but suspicious, in strict mode too.
+    pub fn warn<S: Into<String>>(&self, message: S) {
+        self.warnings.push(message);
+    }
+
+    /// Reports input that lenient parsing tolerates if `condition` does not hold.
+    pub fn check<S: Into<String>>(&self, condition: bool, message: S) -> AocResult<()> {
+        if condition {
+            Ok(())
+        } else {
+            self.tolerate(message)
+        }
+    }
+
+    /// Reports any tokens left over after parsing `what`.
+    pub fn expect_end<I, T>(&self, mut rest: I, what: &str) -> AocResult<()>
+    where
+        I: Iterator<Item = T>,
+    {
+        self.check(
+            rest.next().is_none(),
+            format!("unexpected trailing input after {}", what),
+        )
+    }
+}
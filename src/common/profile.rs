@@ -0,0 +1,216 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Borrow;
+use std::collections::hash_map::{Entry, RandomState};
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::ops::{Deref, Index};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static HASH_INSERTS: AtomicU64 = AtomicU64::new(0);
+static HASH_LOOKUPS: AtomicU64 = AtomicU64::new(0);
+
+fn count(counter: &AtomicU64) {
+    if ENABLED.load(Ordering::Relaxed) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Turns on counting. Counters stay at zero until this is called.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Resets all counters to zero.
+pub fn reset() {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    HASH_INSERTS.store(0, Ordering::Relaxed);
+    HASH_LOOKUPS.store(0, Ordering::Relaxed);
+}
+
+/// Operation counts collected since the last reset.
+#[derive(Clone, Copy, Default)]
+pub struct Counters {
+    pub allocations: u64,
+    pub hash_inserts: u64,
+    pub hash_lookups: u64,
+}
+
+pub fn counters() -> Counters {
+    Counters {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        hash_inserts: HASH_INSERTS.load(Ordering::Relaxed),
+        hash_lookups: HASH_LOOKUPS.load(Ordering::Relaxed),
+    }
+}
+
+/// Global allocator that counts allocations and reallocations while
+/// counting is enabled.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(&ALLOCATIONS);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(&ALLOCATIONS);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(&ALLOCATIONS);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Hash map that counts inserts and lookups.
+///
+/// Read-only access to the underlying map is available through `Deref`, but
+/// only the methods defined here are counted.
+#[derive(Clone, Debug)]
+pub struct CountingMap<K, V, S = RandomState>(HashMap<K, V, S>);
+
+impl<K, V> CountingMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        CountingMap(HashMap::new())
+    }
+}
+
+impl<K, V, S> CountingMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        count(&HASH_INSERTS);
+        self.0.insert(key, value)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        count(&HASH_LOOKUPS);
+        self.0.get(key)
+    }
+
+    /// Counted as a lookup; inserting through the entry is not counted again.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        count(&HASH_LOOKUPS);
+        self.0.entry(key)
+    }
+}
+
+impl<K, V, S> Default for CountingMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        CountingMap(HashMap::default())
+    }
+}
+
+impl<K, V, S> Deref for CountingMap<K, V, S> {
+    type Target = HashMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for CountingMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        count(&HASH_LOOKUPS);
+        &self.0[key]
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for CountingMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = HashMap::default();
+        for (key, value) in iter {
+            count(&HASH_INSERTS);
+            map.insert(key, value);
+        }
+        CountingMap(map)
+    }
+}
+
+/// Hash set that counts inserts and lookups.
+///
+/// Read-only access to the underlying set is available through `Deref`, but
+/// only the methods defined here are counted.
+#[derive(Clone, Debug)]
+pub struct CountingSet<T, S = RandomState>(HashSet<T, S>);
+
+impl<T, S> CountingSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    pub fn insert(&mut self, value: T) -> bool {
+        count(&HASH_INSERTS);
+        self.0.insert(value)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        count(&HASH_LOOKUPS);
+        self.0.contains(value)
+    }
+}
+
+impl<T, S> Default for CountingSet<T, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        CountingSet(HashSet::default())
+    }
+}
+
+impl<T, S> Deref for CountingSet<T, S> {
+    type Target = HashSet<T, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, S> FromIterator<T> for CountingSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = HashSet::default();
+        for value in iter {
+            count(&HASH_INSERTS);
+            set.insert(value);
+        }
+        CountingSet(set)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::common::log;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Destination for reports of how far a long-running solver has gotten,
+/// which are written at most once per interval so that solvers can report
+/// from their inner loops.
+pub struct ProgressSink {
+    /// Time between reports, or `None` if progress is not reported.
+    interval: Option<Duration>,
+    /// When progress was last reported, or when the sink was created.
+    last: Mutex<Instant>,
+}
+
+impl ProgressSink {
+    pub fn new(interval: Option<Duration>) -> Self {
+        ProgressSink {
+            interval,
+            last: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Writes the message given by `describe` to standard error if the
+    /// interval has passed since the last report. The message is only built
+    /// if it will be written.
+    pub fn report<F>(&self, describe: F)
+    where
+        F: FnOnce() -> String,
+    {
+        let interval = match self.interval {
+            None => return,
+            Some(interval) => interval,
+        };
+        let mut last = self.last.lock().unwrap_or_else(PoisonError::into_inner);
+        if last.elapsed() < interval {
+            return;
+        }
+        *last = Instant::now();
+        log::write(format_args!("[progress] {}", describe()));
+    }
+}
+
+impl Default for ProgressSink {
+    fn default() -> Self {
+        ProgressSink::new(None)
+    }
+}
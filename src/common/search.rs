@@ -0,0 +1,116 @@
+use crate::common::{cancel, AocError, AocResult, IntoAocResult, Rng};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+/// Verifies that a search heuristic is admissible, meaning it never
+/// overestimates the remaining cost to the goal.
+///
+/// States are sampled by random walks of up to `max_walk` steps from `start`.
+/// For each sampled state, the heuristic is compared against the true
+/// remaining cost, which should be computed without the heuristic (for
+/// instance, with Dijkstra's algorithm). States that cannot reach the goal
+/// are skipped.
+///
+/// Returns the number of states that were checked.
+pub fn check_admissible<S, N, H, C>(
+    start: &S,
+    samples: usize,
+    max_walk: usize,
+    rng: &mut Rng,
+    next_states: N,
+    heuristic: H,
+    remaining_cost: C,
+) -> AocResult<usize>
+where
+    S: Clone,
+    N: Fn(&S) -> Vec<S>,
+    H: Fn(&S) -> usize,
+    C: Fn(&S) -> Option<usize>,
+{
+    let mut checked = 0;
+    for _ in 0..samples {
+        let mut state = start.clone();
+        for _ in 0..rng.below(max_walk + 1) {
+            let next = next_states(&state);
+            if next.is_empty() {
+                break;
+            }
+            state = next[rng.below(next.len())].clone();
+        }
+
+        if let Some(cost) = remaining_cost(&state) {
+            let estimate = heuristic(&state);
+            if estimate > cost {
+                return Err(AocError::new(format!(
+                    "heuristic estimated {} but the remaining cost is {}",
+                    estimate, cost
+                )));
+            }
+            checked += 1;
+        }
+    }
+    Ok(checked)
+}
+
+/// Costs found by `lowest_costs`.
+pub struct LowestCosts<S> {
+    /// Lowest cost of reaching each state found so far. These are final for
+    /// every state if the search did not stop at a goal.
+    pub costs: HashMap<S, usize>,
+    /// The goal state the search stopped at and its cost, if any.
+    pub goal: Option<(S, usize)>,
+}
+
+/// Finds the lowest cost of reaching states from the nearest of `sources`
+/// with Dijkstra's algorithm, where every source costs nothing to reach.
+///
+/// `next_states` gives the states one step away from a state, along with the
+/// cost of taking that step. The search stops at the first state for which
+/// `is_goal` returns true, and otherwise visits every reachable state.
+pub fn lowest_costs<S, N, I, G>(
+    sources: impl IntoIterator<Item = S>,
+    next_states: N,
+    is_goal: G,
+) -> AocResult<LowestCosts<S>>
+where
+    S: Clone + Eq + Hash + Ord,
+    N: Fn(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    G: Fn(&S) -> bool,
+{
+    let mut costs = HashMap::new();
+    let mut open_set = BinaryHeap::new();
+    for source in sources {
+        costs.insert(source.clone(), 0);
+        open_set.push(Reverse((0, source)));
+    }
+
+    while let Some(Reverse((cost, state))) = open_set.pop() {
+        cancel::check()?;
+
+        // We have found a better path to this state already.
+        if cost > costs[&state] {
+            continue;
+        }
+        if is_goal(&state) {
+            return Ok(LowestCosts {
+                costs,
+                goal: Some((state, cost)),
+            });
+        }
+
+        for (next, step_cost) in next_states(&state) {
+            let next_cost = cost
+                .checked_add(step_cost)
+                .into_aoc_result_msg("total cost overflowed")?;
+            let best = costs.entry(next.clone()).or_insert(usize::MAX);
+            if next_cost < *best {
+                *best = next_cost;
+                open_set.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+
+    Ok(LowestCosts { costs, goal: None })
+}
@@ -1,6 +1,47 @@
-use crate::common::AocResult;
+use crate::common::{AocResult, SolverContext};
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 
 #[allow(non_camel_case_types)]
 pub type iAoc = u64;
 
-pub type SolverFn = fn(&str) -> AocResult<iAoc>;
+/// The answer produced by a solver.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SolutionValue {
+    Number(iAoc),
+    /// An answer that does not fit in `iAoc`, such as a big integer.
+    Text(String),
+}
+
+impl From<iAoc> for SolutionValue {
+    fn from(value: iAoc) -> Self {
+        SolutionValue::Number(value)
+    }
+}
+
+impl Display for SolutionValue {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        match self {
+            Self::Number(value) => write!(f, "{}", value),
+            Self::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+pub type SolverFn = fn(&str, &SolverContext) -> AocResult<SolutionValue>;
+
+/// Parses the input of a day without solving it.
+pub type ParserFn = fn(&str, &SolverContext) -> AocResult<()>;
+
+/// The solvers of a day, whose parts share one parsed representation of the
+/// input, so that solving both parts together only parses it once.
+///
+/// The time spent in `parse` is reported as parsing time, so it should only
+/// read the input. Work that the parts start from belongs in the parts.
+pub trait Solver {
+    type Parsed: Send + Sync + 'static;
+
+    /// Parses the input, which is also all that `--parse-only` does.
+    fn parse(input: &str, context: &SolverContext) -> AocResult<Self::Parsed>;
+    fn part_a(parsed: &Self::Parsed, context: &SolverContext) -> AocResult<SolutionValue>;
+    fn part_b(parsed: &Self::Parsed, context: &SolverContext) -> AocResult<SolutionValue>;
+}
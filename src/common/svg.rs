@@ -0,0 +1,135 @@
+use crate::common::{AocResult, OutputSink};
+
+/// Escapes text for use in SVG markup.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A minimal SVG document, drawn in pixel coordinates with the origin at the
+/// top left.
+pub struct Document {
+    width: f64,
+    height: f64,
+    elements: Vec<String>,
+}
+
+impl Document {
+    pub fn new(width: f64, height: f64) -> Self {
+        Document {
+            width,
+            height,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Adds a filled rectangle.
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: &str) {
+        self.elements.push(format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x,
+            y,
+            width,
+            height,
+            escape(fill)
+        ));
+    }
+
+    /// Adds a filled circle.
+    pub fn circle(&mut self, x: f64, y: f64, radius: f64, fill: &str) {
+        self.elements.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            x,
+            y,
+            radius,
+            escape(fill)
+        ));
+    }
+
+    /// Adds a line through all of the given points.
+    pub fn polyline(&mut self, points: &[(f64, f64)], stroke: &str) {
+        let points = points
+            .iter()
+            .map(|(x, y)| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.elements.push(format!(
+            r#"<polyline points="{}" fill="none" stroke="{}"/>"#,
+            points,
+            escape(stroke)
+        ));
+    }
+
+    /// Adds text, with its baseline starting at the given point.
+    pub fn text(&mut self, x: f64, y: f64, size: f64, text: &str) {
+        self.elements.push(format!(
+            r#"<text x="{}" y="{}" font-family="monospace" font-size="{}">{}</text>"#,
+            x,
+            y,
+            size,
+            escape(text)
+        ));
+    }
+
+    /// Renders the document as SVG markup.
+    pub fn render(&self) -> String {
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            self.width, self.height, self.width, self.height
+        );
+        svg += "\n";
+        for element in &self.elements {
+            svg += "  ";
+            svg += element;
+            svg += "\n";
+        }
+        svg += "</svg>\n";
+        svg
+    }
+
+    /// Writes the document with the given file name.
+    pub fn write(&self, output: &OutputSink, name: &str) -> AocResult<()> {
+        output.write(name, self.render().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_every_element() {
+        let mut document = Document::new(100.0, 50.0);
+        document.rect(0.0, 0.0, 100.0, 50.0, "white");
+        document.circle(10.5, 20.0, 3.0, "red");
+        document.polyline(&[(0.0, 0.0), (5.0, 10.0), (20.0, 2.5)], "#333");
+        document.text(4.0, 45.0, 12.0, r#"a < b && "c""#);
+        assert_eq!(
+            document.render(),
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50" viewBox="0 0 100 50">"#,
+                "\n",
+                r#"  <rect x="0" y="0" width="100" height="50" fill="white"/>"#,
+                "\n",
+                r#"  <circle cx="10.5" cy="20" r="3" fill="red"/>"#,
+                "\n",
+                r##"  <polyline points="0,0 5,10 20,2.5" fill="none" stroke="#333"/>"##,
+                "\n",
+                r#"  <text x="4" y="45" font-family="monospace" font-size="12">a &lt; b &amp;&amp; &quot;c&quot;</text>"#,
+                "\n",
+                "</svg>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn escapes_attributes() {
+        let mut document = Document::new(1.0, 1.0);
+        document.circle(0.0, 0.0, 1.0, r#"url("<&>")"#);
+        assert!(document
+            .render()
+            .contains(r#"fill="url(&quot;&lt;&amp;&gt;&quot;)""#));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::common::math::OverflowPolicy;
+use crate::common::{OutputSink, SolverContext};
+use std::sync::Arc;
+
+/// Builds the context an example is solved with, using the given strategy
+/// and params and the default options otherwise.
+pub fn context(strategy: Option<&str>, params: &[(&str, &str)]) -> SolverContext {
+    SolverContext::new(
+        strategy.map(String::from),
+        params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        1,
+        OutputSink::default(),
+        OverflowPolicy::BigInt,
+        false,
+        Arc::default(),
+    )
+}
+
+/// Asserts that a part of a `Solver` gives the expected answer for an
+/// example input, such as `assert_solves!(Snailfish::part_a, EXAMPLE, 4140)`.
+///
+/// A strategy or params to solve with may follow the answer, as in
+/// `assert_solves!(SonarSweep::part_b, EXAMPLE, 5, params: [("window", "5")])`.
+macro_rules! assert_solves {
+    ($solver:ident::$part:ident, $input:expr, $expected:expr) => {
+        $crate::common::testing::assert_solves!(@run $solver::$part, $input, $expected, None, [])
+    };
+    ($solver:ident::$part:ident, $input:expr, $expected:expr, strategy: $strategy:expr) => {
+        $crate::common::testing::assert_solves!(
+            @run $solver::$part, $input, $expected, Some($strategy), []
+        )
+    };
+    (
+        $solver:ident::$part:ident,
+        $input:expr,
+        $expected:expr,
+        params: [$(($name:expr, $value:expr)),* $(,)?]
+    ) => {
+        $crate::common::testing::assert_solves!(
+            @run $solver::$part, $input, $expected, None, [$(($name, $value)),*]
+        )
+    };
+    (
+        @run $solver:ident::$part:ident,
+        $input:expr,
+        $expected:expr,
+        $strategy:expr,
+        [$(($name:expr, $value:expr)),*]
+    ) => {{
+        use $crate::common::Solver;
+        let context = $crate::common::testing::context($strategy, &[$(($name, $value)),*]);
+        let answer = $solver::parse($input, &context)
+            .and_then(|parsed| $solver::$part(&parsed, &context));
+        match answer {
+            Ok(answer) => assert_eq!(
+                answer,
+                $crate::common::SolutionValue::from($expected as $crate::common::iAoc),
+                "{} gave the wrong answer",
+                stringify!($solver::$part)
+            ),
+            Err(err) => panic!("{} failed: {}", stringify!($solver::$part), err),
+        }
+    }};
+}
+
+pub(crate) use assert_solves;
@@ -0,0 +1,51 @@
+use std::fmt::{Display, Formatter, Result as DisplayResult};
+use std::sync::Mutex;
+
+/// A non-fatal issue found while parsing or solving, such as input that was
+/// tolerated or a value that looks suspicious.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    message: String,
+}
+
+impl Warning {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Warning {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        write!(f, "warning: {}", self.message)
+    }
+}
+
+/// Collects warnings during a solver run, so they can be reported after the
+/// answer instead of interleaved with it.
+///
+/// Warnings can be pushed from any thread.
+#[derive(Default)]
+pub struct Warnings {
+    warnings: Mutex<Vec<Warning>>,
+}
+
+impl Warnings {
+    pub fn push<S: Into<String>>(&self, message: S) {
+        self.lock().push(Warning::new(message));
+    }
+
+    /// Removes and returns every warning collected so far.
+    pub fn take(&self) -> Vec<Warning> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Warning>> {
+        // A thread that panicked while pushing cannot leave the vector in a
+        // broken state, so the lock is still usable.
+        self.warnings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
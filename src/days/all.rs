@@ -1,39 +1,269 @@
 use super::*;
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult, SolverFn};
+use crate::common::profile::{self, Counters};
+use crate::common::{cancel, log};
+use crate::common::{
+    AocError, AocResult, DayInfo, InputHash, IntoAocResult, OutputSink, ParseCache, ParserFn,
+    ProgressSink, SolutionValue, Solver, SolverContext, SolverFn, Warning,
+};
+#[cfg(feature = "http")]
+use crate::program::fetch;
 use crate::program::{ProgramArgs, SolutionPart};
 use std::fs;
+use std::io::{self, Read};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
-const SOLVERS: [[SolverFn; 2]; 25] = [
-    [day01::solve_a, day01::solve_b],
-    [day02::solve_a, day02::solve_b],
-    [day03::solve_a, day03::solve_b],
-    [day04::solve_a, day04::solve_b],
-    [day05::solve_a, day05::solve_b],
-    [day06::solve_a, day06::solve_b],
-    [day07::solve_a, day07::solve_b],
-    [day08::solve_a, day08::solve_b],
-    [day09::solve_a, day09::solve_b],
-    [day10::solve_a, day10::solve_b],
-    [day11::solve_a, day11::solve_b],
-    [day12::solve_a, day12::solve_b],
-    [day13::solve_a, day13::solve_b],
-    [day14::solve_a, day14::solve_b],
-    [day15::solve_a, day15::solve_b],
-    [day16::solve_a, day16::solve_b],
-    [day17::solve_a, day17::solve_b],
-    [day18::solve_a, day18::solve_b],
-    [day19::solve_a, day19::solve_b],
-    [day20::solve_a, day20::solve_b],
-    [day21::solve_a, day21::solve_b],
-    [day22::solve_a, day22::solve_b],
-    [day23::solve_a, day23::solve_b],
-    [day24::solve_a, day24::solve_b],
-    [day25::solve_a, day25::solve_b],
+/// A solver for one part of a day.
+#[derive(Clone, Copy)]
+struct PartSolver {
+    solve: SolverFn,
+    /// Whether the part has no puzzle to solve, so the solver always gives
+    /// the same answer.
+    stub: bool,
+}
+
+/// Parses the input of a day, or gets it from the other part of the day if
+/// it already parsed it, timing the parsing apart from the rest of the part.
+fn parsed<S: Solver>(input: &str, context: &SolverContext) -> AocResult<Arc<S::Parsed>> {
+    context.parse_shared(|| context.parse(|| S::parse(input, context)))
+}
+
+fn solve_part_a<S: Solver>(input: &str, context: &SolverContext) -> AocResult<SolutionValue> {
+    let parsed = parsed::<S>(input, context)?;
+    S::part_a(&parsed, context)
+}
+
+fn solve_part_b<S: Solver>(input: &str, context: &SolverContext) -> AocResult<SolutionValue> {
+    let parsed = parsed::<S>(input, context)?;
+    S::part_b(&parsed, context)
+}
+
+/// Parses the input of a day without solving it.
+fn parse_only<S: Solver>(input: &str, context: &SolverContext) -> AocResult<()> {
+    S::parse(input, context)?;
+    Ok(())
+}
+
+/// Adapts a `Solver`, optionally with a part B that is only a stub.
+macro_rules! solvers {
+    ($solver:ty) => {
+        solvers!($solver, false)
+    };
+    ($solver:ty, stub B) => {
+        solvers!($solver, true)
+    };
+    ($solver:ty, $stub_b:expr) => {
+        Solvers {
+            parse: parse_only::<$solver>,
+            parts: [
+                PartSolver {
+                    solve: solve_part_a::<$solver>,
+                    stub: false,
+                },
+                PartSolver {
+                    solve: solve_part_b::<$solver>,
+                    stub: $stub_b,
+                },
+            ],
+        }
+    };
+}
+
+/// Everything that can be done with the input of a day.
+struct Solvers {
+    parse: ParserFn,
+    parts: [PartSolver; 2],
+}
+
+/// Solvers and puzzle details for one day.
+struct Day {
+    /// Title of the puzzle on adventofcode.com.
+    title: &'static str,
+    /// One line on how the solvers work.
+    summary: &'static str,
+    solvers: Solvers,
+}
+
+/// What the puzzle of a day is, as shown alongside its results.
+#[derive(Clone, Copy)]
+pub struct Puzzle {
+    pub day: u8,
+    pub title: &'static str,
+    pub summary: &'static str,
+}
+
+impl Puzzle {
+    /// Page of the puzzle on adventofcode.com.
+    pub fn url(&self) -> String {
+        format!("https://adventofcode.com/2021/day/{}", self.day)
+    }
+}
+
+/// Solvers for both parts of each day, with what its puzzle is about.
+const DAYS: [Day; 25] = [
+    Day {
+        title: "Sonar Sweep",
+        summary: "Counts increases between sums of a sliding window of depths.",
+        solvers: solvers!(day01::SonarSweep),
+    },
+    Day {
+        title: "Dive!",
+        summary: "Follows the submarine's commands, tracking aim for part B.",
+        solvers: solvers!(day02::Dive),
+    },
+    Day {
+        title: "Binary Diagnostic",
+        summary: "Counts bits in each column, then filters the numbers by them.",
+        solvers: solvers!(day03::BinaryDiagnostic),
+    },
+    Day {
+        title: "Giant Squid",
+        summary: "Marks each bingo board as numbers are drawn, using bitsets of winning boards.",
+        solvers: solvers!(day04::GiantSquid),
+    },
+    Day {
+        title: "Hydrothermal Venture",
+        summary: "Draws each vent line into a grid and counts the overlapping points.",
+        solvers: solvers!(day05::HydrothermalVenture),
+    },
+    Day {
+        title: "Lanternfish",
+        summary: "Counts the fish with each timer value rather than simulating each fish.",
+        solvers: solvers!(day06::Lanternfish),
+    },
+    Day {
+        title: "The Treachery of Whales",
+        summary: "Aligns the crabs at the median, or near the average for increasing fuel rates.",
+        solvers: solvers!(day07::TreacheryOfWhales),
+    },
+    Day {
+        title: "Seven Segment Search",
+        summary: "Stores each display as a bitmask and deduces the wiring from segment counts.",
+        solvers: solvers!(day08::SevenSegmentSearch),
+    },
+    Day {
+        title: "Smoke Basin",
+        summary: "Finds the low points, then flood fills the basins between ridges of height 9.",
+        solvers: solvers!(day09::SmokeBasin),
+    },
+    Day {
+        title: "Syntax Scoring",
+        summary: "Matches brackets with a stack, scoring corrupted and incomplete lines.",
+        solvers: solvers!(day10::SyntaxScoring),
+    },
+    Day {
+        title: "Dumbo Octopus",
+        summary: "Simulates the flashes step by step until every octopus flashes at once.",
+        solvers: solvers!(day11::DumboOctopus),
+    },
+    Day {
+        title: "Passage Pathing",
+        summary: "Counts paths with a memoized search over caves and visited small caves.",
+        solvers: solvers!(day12::PassagePathing),
+    },
+    Day {
+        title: "Transparent Origami",
+        summary: "Folds the set of points along each line, then prints the code.",
+        solvers: solvers!(day13::TransparentOrigami),
+    },
+    Day {
+        title: "Extended Polymerization",
+        summary: "Counts pairs of elements instead of building the polymer.",
+        solvers: solvers!(day14::ExtendedPolymerization),
+    },
+    Day {
+        title: "Chiton",
+        summary: "Finds the lowest risk path with A* search over the tiled cavern.",
+        solvers: solvers!(day15::Chiton),
+    },
+    Day {
+        title: "Packet Decoder",
+        summary: "Decodes the bit stream into a tree of packets and evaluates it.",
+        solvers: solvers!(day16::PacketDecoder),
+    },
+    Day {
+        title: "Trick Shot",
+        summary:
+            "Solves for the highest Y velocity directly, then checks each velocity that can hit.",
+        solvers: solvers!(day17::TrickShot),
+    },
+    Day {
+        title: "Snailfish",
+        summary: "Stores each number as a flat list of values and depths to explode and split.",
+        solvers: solvers!(day18::Snailfish),
+    },
+    Day {
+        title: "Beacon Scanner",
+        summary: "Aligns scanners by matching the distances between their beacons.",
+        solvers: solvers!(day19::BeaconScanner),
+    },
+    Day {
+        title: "Trench Map",
+        summary: "Enhances the image step by step, tracking the infinite background.",
+        solvers: solvers!(day20::TrenchMap),
+    },
+    Day {
+        title: "Dirac Dice",
+        summary: "Counts the universes in each game state, grouping rolls by their sum.",
+        solvers: solvers!(day21::DiracDice),
+    },
+    Day {
+        title: "Reactor Reboot",
+        summary: "Splits cuboids around each step so that only disjoint cuboids are lit.",
+        solvers: solvers!(day22::ReactorReboot),
+    },
+    Day {
+        title: "Amphipod",
+        summary: "Finds the cheapest organization with A* search over states packed into 64 bits.",
+        solvers: solvers!(day23::AmphipodBurrow),
+    },
+    Day {
+        title: "Arithmetic Logic Unit",
+        summary: "Finds the relationships between digits that the MONAD checks.",
+        solvers: solvers!(day24::ArithmeticLogicUnit),
+    },
+    Day {
+        title: "Sea Cucumber",
+        summary: "Moves each herd in turn until neither herd can move.",
+        solvers: solvers!(day25::SeaCucumbers, stub B),
+    },
+];
+
+/// Strategies and parameters recognized by each day.
+const INFO: [&DayInfo; 25] = [
+    &day01::INFO,
+    &DayInfo::EMPTY,
+    &DayInfo::EMPTY,
+    &DayInfo::EMPTY,
+    &day05::INFO,
+    &day06::INFO,
+    &day07::INFO,
+    &day08::INFO,
+    &day09::INFO,
+    &DayInfo::EMPTY,
+    &day11::INFO,
+    &day12::INFO,
+    &day13::INFO,
+    &day14::INFO,
+    &day15::INFO,
+    &day16::INFO,
+    &day17::INFO,
+    &day18::INFO,
+    &day19::INFO,
+    &day20::INFO,
+    &day21::INFO,
+    &day22::INFO,
+    &day23::INFO,
+    &day24::INFO,
+    &day25::INFO,
 ];
 
 fn get_solver(args: &ProgramArgs) -> AocResult<SolverFn> {
-    if args.day() as usize > SOLVERS.len() {
+    if args.day() as usize > DAYS.len() {
         return Err(AocError::new("day not implemented"));
     }
 
@@ -41,37 +271,405 @@ fn get_solver(args: &ProgramArgs) -> AocResult<SolverFn> {
         SolutionPart::A => 0,
         SolutionPart::B => 1,
     };
-    return Ok(SOLVERS[(args.day() - 1) as usize][part_index]);
+    Ok(DAYS[(args.day() - 1) as usize].solvers.parts[part_index].solve)
+}
+
+/// Checks if the given part of a day is a stub rather than a real solver.
+pub fn is_stub(day: u8, part: SolutionPart) -> bool {
+    let part_index: usize = match part {
+        SolutionPart::A => 0,
+        SolutionPart::B => 1,
+    };
+    DAYS[(day - 1) as usize].solvers.parts[part_index].stub
+}
+
+/// Gets the title and summary of the puzzle for the given day.
+pub fn puzzle(day: u8) -> Puzzle {
+    let Day { title, summary, .. } = DAYS[(day - 1) as usize];
+    Puzzle {
+        day,
+        title,
+        summary,
+    }
+}
+
+/// Timings of benchmark runs that read the input from disk before solving.
+pub struct ColdTimes {
+    reads: Vec<Duration>,
+    totals: Vec<Duration>,
+}
+
+fn mean(times: &[Duration]) -> Duration {
+    times.iter().sum::<Duration>() / times.len().max(1) as u32
+}
+
+impl ColdTimes {
+    /// Mean time spent reading the input.
+    pub fn read_time(&self) -> Duration {
+        mean(&self.reads)
+    }
+
+    /// Mean time spent reading the input and solving it.
+    pub fn time(&self) -> Duration {
+        mean(&self.totals)
+    }
 }
 
 pub struct Solution {
-    solution: iAoc,
-    time: Duration,
+    solution: SolutionValue,
+    input_hash: InputHash,
+    times: Vec<Duration>,
+    /// Time spent parsing in each run, empty if the solver does not report it.
+    parse_times: Vec<Duration>,
+    cold_times: Option<ColdTimes>,
+    counters: Option<Counters>,
+    warnings: Vec<Warning>,
 }
 
 impl Solution {
-    pub fn new(solution: iAoc, time: Duration) -> Self {
-        Solution { solution, time }
+    pub fn new(
+        solution: SolutionValue,
+        input_hash: InputHash,
+        times: Vec<Duration>,
+        parse_times: Vec<Duration>,
+        cold_times: Option<ColdTimes>,
+        counters: Option<Counters>,
+        warnings: Vec<Warning>,
+    ) -> Self {
+        Solution {
+            solution,
+            input_hash,
+            times,
+            parse_times,
+            cold_times,
+            counters,
+            warnings,
+        }
+    }
+
+    pub fn solution(&self) -> &SolutionValue {
+        &self.solution
     }
 
-    pub fn solution(&self) -> iAoc {
-        self.solution
+    /// Hash of the input the solution was computed from.
+    pub fn input_hash(&self) -> InputHash {
+        self.input_hash
     }
 
-    pub fn time(&self) -> &Duration {
-        &self.time
+    /// Number of times the solver was run.
+    pub fn runs(&self) -> usize {
+        self.times.len()
     }
+
+    /// Mean time across all runs.
+    pub fn time(&self) -> Duration {
+        mean(&self.times)
+    }
+
+    /// Time taken by each run.
+    pub fn times(&self) -> &[Duration] {
+        &self.times
+    }
+
+    /// Time spent parsing in each run, empty if the solver does not report it.
+    pub fn parse_times(&self) -> &[Duration] {
+        &self.parse_times
+    }
+
+    /// Mean time spent parsing the input across all runs, which is part of
+    /// the total time, or `None` if the solver does not report it.
+    pub fn parse_time(&self) -> Option<Duration> {
+        if self.parse_times.is_empty() {
+            None
+        } else {
+            Some(mean(&self.parse_times))
+        }
+    }
+
+    pub fn fastest(&self) -> Duration {
+        self.times.iter().copied().min().unwrap_or_default()
+    }
+
+    pub fn slowest(&self) -> Duration {
+        self.times.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Timings of the runs that re-read the input, if cold benchmarking was
+    /// enabled.
+    pub fn cold_times(&self) -> Option<&ColdTimes> {
+        self.cold_times.as_ref()
+    }
+
+    /// Non-fatal issues reported by the last run.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Operation counts from the last run, if counting was enabled.
+    pub fn counters(&self) -> Option<&Counters> {
+        self.counters.as_ref()
+    }
+}
+
+fn get_parser(args: &ProgramArgs) -> AocResult<ParserFn> {
+    DAYS.get(args.day() as usize - 1)
+        .map(|day| day.solvers.parse)
+        .into_aoc_result_msg("day not implemented")
+}
+
+/// Gets the strategies and parameters recognized by the selected day.
+pub fn info(args: &ProgramArgs) -> AocResult<&'static DayInfo> {
+    INFO.get(args.day() as usize - 1)
+        .copied()
+        .into_aoc_result_msg("day not implemented")
+}
+
+/// Every day that has solvers, in order.
+pub fn implemented_days() -> RangeInclusive<u8> {
+    1..=DAYS.len() as u8
+}
+
+/// Path of the input file for the selected day.
+pub fn input_path(args: &ProgramArgs) -> String {
+    match args.filename() {
+        None => format!("{}/{}.txt", args.input_dir(), args.day()),
+        Some(filename) => format!("{}/{}", args.input_dir(), filename),
+    }
+}
+
+/// Downloads the input from the URL given with `--input`.
+#[cfg(feature = "http")]
+fn read_url(url: &str) -> AocResult<String> {
+    fetch::fetch_url(url).map_err(AocError::missing_input)
+}
+
+#[cfg(not(feature = "http"))]
+fn read_url(_: &str) -> AocResult<String> {
+    Err(AocError::new(
+        "input from a URL requires the http feature, which this build does not have",
+    ))
+}
+
+fn read_input(args: &ProgramArgs) -> AocResult<String> {
+    let mut input = if args.stdin() {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input).into_aoc_result()?;
+        input
+    } else if let Some(url) = args.url() {
+        read_url(url)?
+    } else {
+        let path = input_path(args);
+        match fs::read_to_string(&path) {
+            // A missing default input is downloaded and saved for next time.
+            #[cfg(feature = "http")]
+            Err(err) if err.kind() == io::ErrorKind::NotFound && args.filename().is_none() => {
+                fetch::fetch_input(args, &path).map_err(AocError::missing_input)?
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(AocError::new(format!("{} does not exist", path)).missing_input())
+            }
+            result => result.into_aoc_result()?,
+        }
+    };
+    for adapter in args.adapters() {
+        input = adapter.adapt(input)?;
+    }
+    Ok(input)
+}
+
+fn create_context(args: &ProgramArgs) -> SolverContext {
+    SolverContext::new(
+        args.strategy().clone(),
+        args.params().clone(),
+        args.threads(),
+        OutputSink::default(),
+        args.overflow(),
+        args.strict(),
+        Arc::default(),
+    )
+    .with_progress(ProgressSink::new(args.progress()))
+}
+
+/// Shows the input around the line an error happened on if
+/// `--explain-failure` is set.
+fn explain(args: &ProgramArgs, err: AocError, input: &str) -> AocError {
+    if args.explain_failure() {
+        err.explained(input)
+    } else {
+        err
+    }
+}
+
+/// Parses the input of the selected day without solving it, returning how
+/// long parsing took and any warnings about the input.
+pub fn parse(args: &ProgramArgs) -> AocResult<(Duration, Vec<Warning>)> {
+    let parser = get_parser(args)?;
+    let input = read_input(args)?;
+    let context = create_context(args);
+    let now = Instant::now();
+    parser(&input, &context).map_err(|err| explain(args, err, &input))?;
+    Ok((now.elapsed(), context.warnings().take()))
 }
 
 pub fn solve(args: &ProgramArgs) -> AocResult<Solution> {
     let solver = get_solver(args)?;
-    let filename = match args.filename() {
-        None => format!("input/{}.txt", args.day()),
-        Some(filename) => format!("input/{}", filename),
+    let input = read_input(args)?;
+    solve_input(args, solver, &input, Arc::default())
+}
+
+/// Solves one part of a day for the given input with default options,
+/// without reading or writing any files.
+pub fn solve_day(day: u8, part: SolutionPart, input: &str) -> AocResult<SolutionValue> {
+    if !implemented_days().contains(&day) {
+        return Err(AocError::new("day not implemented"));
+    }
+    let args = ProgramArgs::new(day, part);
+    let solver = get_solver(&args)?;
+    solver(input, &create_context(&args))
+}
+
+/// Solves every selected part of the selected day, reading the input once.
+pub fn solve_parts(args: &ProgramArgs) -> AocResult<Vec<(SolutionPart, AocResult<Solution>)>> {
+    let parts = args
+        .parts()
+        .into_iter()
+        .map(|part| Ok((part, get_solver(&args.with_part(part))?)))
+        .collect::<AocResult<Vec<_>>>()?;
+    let input = read_input(args)?;
+    // Both parts share the input they parse.
+    let parse_cache = Arc::new(ParseCache::default());
+    Ok(parts
+        .into_iter()
+        .map(|(part, solver)| {
+            let solution = solve_input(
+                &args.with_part(part),
+                solver,
+                &input,
+                Arc::clone(&parse_cache),
+            );
+            (part, solution)
+        })
+        .collect())
+}
+
+/// Runs the solver once, on a worker thread if there is a time limit, so
+/// that it can be abandoned once the limit passes.
+///
+/// An abandoned solver is cancelled, which stops it at its next call to
+/// `cancel::check`. Solvers that never check keep running in the
+/// background until they finish or the program exits.
+fn run_solver(
+    args: &ProgramArgs,
+    solver: SolverFn,
+    input: &str,
+    context: &Arc<SolverContext>,
+) -> AocResult<SolutionValue> {
+    // Repeated runs each parse the input, rather than reusing what an earlier
+    // run parsed, so that every run measures the same work.
+    if args.warmup() + args.bench() > 1 || args.bench_cold() {
+        context.parse_cache().clear();
+    }
+    let timeout = match args.timeout() {
+        None => return solver(input, context),
+        Some(timeout) => timeout,
     };
-    let input = fs::read_to_string(filename).into_aoc_result()?;
-    let now = Instant::now();
-    let solution = solver(&input)?;
-    let then = now.elapsed();
-    Ok(Solution::new(solution, then))
+    let token = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    let worker = {
+        let (input, context, token) = (input.to_owned(), Arc::clone(context), Arc::clone(&token));
+        move || {
+            cancel::set_token(token);
+            // Nothing is listening anymore if the solver was abandoned.
+            let _ = sender.send(solver(&input, &context));
+        }
+    };
+    thread::Builder::new()
+        .name(format!("day{:02}", args.day()))
+        .spawn(worker)
+        .into_aoc_result_msg("failed to start the solver thread")?;
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            token.store(true, Ordering::Relaxed);
+            Err(AocError::new(format!(
+                "timed out after {} s",
+                timeout.as_secs_f64()
+            )))
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(AocError::new("solver panicked")),
+    }
+}
+
+fn solve_input(
+    args: &ProgramArgs,
+    solver: SolverFn,
+    input: &str,
+    parse_cache: Arc<ParseCache>,
+) -> AocResult<Solution> {
+    let context = Arc::new(create_context(args).with_parse_cache(parse_cache));
+    if args.verbose() {
+        profile::enable();
+    }
+    log::set_verbosity(args.verbosity());
+    let mut solution = None;
+    let mut check_result = |result: SolutionValue| {
+        if solution
+            .as_ref()
+            .is_some_and(|solution| *solution != result)
+        {
+            return Err(AocError::new("solver gave different answers across runs"));
+        }
+        solution = Some(result);
+        Ok(())
+    };
+    // Warmup runs fill caches and initialize lazy statics, so that the first
+    // measured run is not slower than the rest. Their answers are still
+    // checked against the measured runs.
+    for _ in 0..args.warmup() {
+        let result =
+            run_solver(args, solver, input, &context).map_err(|err| explain(args, err, input))?;
+        check_result(result)?;
+    }
+    let mut times = Vec::with_capacity(args.bench());
+    let mut parse_times = Vec::with_capacity(args.bench());
+    let mut cold_times = args.bench_cold().then(|| ColdTimes {
+        reads: Vec::with_capacity(args.bench()),
+        totals: Vec::with_capacity(args.bench()),
+    });
+    for _ in 0..args.bench() {
+        // A cold run reads the input again before solving it. This does not
+        // evict the file from the OS page cache, but it does include the cost
+        // of the read and of solving from a freshly allocated string.
+        if let Some(cold_times) = &mut cold_times {
+            let now = Instant::now();
+            let input = read_input(args)?;
+            cold_times.reads.push(now.elapsed());
+            let result = run_solver(args, solver, &input, &context)
+                .map_err(|err| explain(args, err, &input))?;
+            cold_times.totals.push(now.elapsed());
+            check_result(result)?;
+        }
+
+        // Every run reports the same warnings, so only the last run's are kept.
+        context.warnings().take();
+        context.take_parse_time();
+        profile::reset();
+        let now = Instant::now();
+        let result =
+            run_solver(args, solver, input, &context).map_err(|err| explain(args, err, input))?;
+        times.push(now.elapsed());
+        parse_times.extend(context.take_parse_time());
+        check_result(result)?;
+    }
+    let solution = solution.into_aoc_result_msg("solver was never run")?;
+    let counters = args.verbose().then(profile::counters);
+    Ok(Solution::new(
+        solution,
+        InputHash::new(input),
+        times,
+        parse_times,
+        cold_times,
+        counters,
+        context.warnings().take(),
+    ))
 }
@@ -1,4 +1,8 @@
-use crate::common::{iAoc, AocResult, IntoAocResult};
+use crate::common::iter::strided_windows;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, SolutionValue, Solver,
+    SolverContext,
+};
 
 fn read_depths(input: &str) -> AocResult<Vec<i32>> {
     input
@@ -8,30 +12,117 @@ fn read_depths(input: &str) -> AocResult<Vec<i32>> {
         .into_aoc_result()
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let depths: Vec<i32> = read_depths(input)?;
-    let result = depths
-        .iter()
-        .zip(depths.iter().skip(1))
-        .fold(
-            0,
-            |result, (prev, next)| if prev < next { result + 1 } else { result },
-        );
-    Ok(result)
+/// Reads a positive integer parameter, or its default if it is not given.
+fn read_positive(context: &SolverContext, name: &str, default: usize) -> AocResult<usize> {
+    match context.param(name) {
+        None => Ok(default),
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|value| *value > 0)
+            .ok_or_else(|| AocError::new(format!("{} must be a positive integer", name))),
+    }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let depths: Vec<i32> = read_depths(input)?;
-    let windows: Vec<i32> = depths
-        .windows(3)
+/// Counts how many times the sum of a window of depths is larger than the sum
+/// of the window before it.
+fn count_increases(depths: &[i32], size: usize, stride: usize) -> iAoc {
+    let sums: Vec<i32> = strided_windows(depths, size, stride)
         .map(|window| window.iter().sum())
         .collect();
-    let result = windows
-        .iter()
-        .zip(windows.iter().skip(1))
-        .fold(
-            0,
-            |result, (prev, next)| if prev < next { result + 1 } else { result },
-        );
-    Ok(result)
+    sums.iter().zip(sums.iter().skip(1)).fold(
+        0,
+        |result, (prev, next)| if prev < next { result + 1 } else { result },
+    )
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[
+        ParamInfo {
+            name: "window",
+            values: "n",
+            description: "Number of depths that part B sums in each window.",
+            default: "3",
+        },
+        ParamInfo {
+            name: "stride",
+            values: "n",
+            description: "Number of depths between the starts of consecutive windows in part B.",
+            default: "1",
+        },
+    ],
+    animated: false,
+};
+
+pub struct SonarSweep;
+
+impl Solver for SonarSweep {
+    type Parsed = Vec<i32>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Vec<i32>> {
+        read_depths(input)
+    }
+
+    fn part_a(depths: &Vec<i32>, _: &SolverContext) -> AocResult<SolutionValue> {
+        Ok(SolutionValue::from(count_increases(depths, 1, 1)))
+    }
+
+    fn part_b(depths: &Vec<i32>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let size = read_positive(context, "window", 3)?;
+        let stride = read_positive(context, "stride", 1)?;
+        Ok(SolutionValue::from(count_increases(depths, size, stride)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::{self, assert_solves};
+
+    const EXAMPLE: &str = include_str!("../../input/test/1.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(SonarSweep::part_a, EXAMPLE, 7);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(SonarSweep::part_b, EXAMPLE, 5);
+    }
+
+    #[test]
+    fn part_b_with_single_depth_windows() {
+        // Every depth compared with the one before it, as in part A.
+        assert_solves!(SonarSweep::part_b, EXAMPLE, 7, params: [("window", "1")]);
+    }
+
+    #[test]
+    fn part_b_with_three_depth_windows() {
+        // Sums 607, 618, 618, 617, 647, 716, 769, 792.
+        assert_solves!(SonarSweep::part_b, EXAMPLE, 5, params: [("window", "3")]);
+    }
+
+    #[test]
+    fn part_b_with_five_depth_windows() {
+        // Sums 1017, 1025, 1065, 1126, 1176, 1239.
+        assert_solves!(SonarSweep::part_b, EXAMPLE, 5, params: [("window", "5")]);
+    }
+
+    #[test]
+    fn part_b_with_strides() {
+        // Sums 607, 618, 647, 769 starting every other depth.
+        assert_solves!(SonarSweep::part_b, EXAMPLE, 3, params: [("window", "3"), ("stride", "2")]);
+        // Sums 607, 617, 769 starting every third depth.
+        assert_solves!(SonarSweep::part_b, EXAMPLE, 2, params: [("window", "3"), ("stride", "3")]);
+    }
+
+    #[test]
+    fn part_b_rejects_zero_window() {
+        let context = testing::context(None, &[("window", "0")]);
+        let depths = SonarSweep::parse(EXAMPLE, &context).unwrap();
+        let err = SonarSweep::part_b(&depths, &context).unwrap_err();
+        assert_eq!(err.message(), "window must be a positive integer");
+    }
 }
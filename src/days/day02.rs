@@ -1,7 +1,9 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::{
+    iAoc, parse_lines, AocError, AocResult, IntoAocResult, SolutionValue, Solver, SolverContext,
+};
 use std::str::FromStr;
 
-enum Command {
+pub enum Command {
     Forward(i64),
     Up(i64),
     Down(i64),
@@ -36,47 +38,69 @@ struct AimPosition {
 }
 
 fn read_commands(input: &str) -> AocResult<Vec<Command>> {
-    input
-        .lines()
-        .map(|line| Command::from_str(line))
-        .collect::<Result<_, _>>()
-        .into_aoc_result()
+    parse_lines(input, Command::from_str)
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let commands = read_commands(input)?;
-    let mut position = Position {
-        horizontal: 0,
-        depth: 0,
-    };
-    for command in commands {
-        match command {
-            Command::Forward(steps) => position.horizontal += steps,
-            Command::Down(steps) => position.depth += steps,
-            Command::Up(steps) => position.depth -= steps,
+pub struct Dive;
+
+impl Solver for Dive {
+    type Parsed = Vec<Command>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Vec<Command>> {
+        read_commands(input)
+    }
+
+    fn part_a(commands: &Vec<Command>, _: &SolverContext) -> AocResult<SolutionValue> {
+        let mut position = Position {
+            horizontal: 0,
+            depth: 0,
+        };
+        for command in commands {
+            match command {
+                Command::Forward(steps) => position.horizontal += steps,
+                Command::Down(steps) => position.depth += steps,
+                Command::Up(steps) => position.depth -= steps,
+            }
         }
+        let result = position.horizontal * position.depth;
+        Ok(SolutionValue::from(result as iAoc))
     }
-    let result = position.horizontal * position.depth;
-    Ok(result as u64)
-}
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let commands = read_commands(input)?;
-    let mut position = AimPosition {
-        horizontal: 0,
-        depth: 0,
-        aim: 0,
-    };
-    for command in commands {
-        match command {
-            Command::Forward(steps) => {
-                position.horizontal += steps;
-                position.depth += position.aim * steps
+    fn part_b(commands: &Vec<Command>, _: &SolverContext) -> AocResult<SolutionValue> {
+        let mut position = AimPosition {
+            horizontal: 0,
+            depth: 0,
+            aim: 0,
+        };
+        for command in commands {
+            match command {
+                Command::Forward(steps) => {
+                    position.horizontal += steps;
+                    position.depth += position.aim * steps
+                }
+                Command::Down(steps) => position.aim += steps,
+                Command::Up(steps) => position.aim -= steps,
             }
-            Command::Down(steps) => position.aim += steps,
-            Command::Up(steps) => position.aim -= steps,
         }
+        let result = position.horizontal * position.depth;
+        Ok(SolutionValue::from(result as iAoc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    const EXAMPLE: &str = include_str!("../../input/test/2.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(Dive::part_a, EXAMPLE, 150);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(Dive::part_b, EXAMPLE, 900);
     }
-    let result = position.horizontal * position.depth;
-    Ok(result as iAoc)
 }
@@ -1,10 +1,13 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::log;
+use crate::common::{
+    iAoc, AocError, AocResult, IntoAocResult, SolutionValue, Solver, SolverContext,
+};
 use std::collections::HashMap;
 use std::num::ParseIntError;
 use std::str::FromStr;
 
 #[derive(Clone)]
-struct BinaryDiagnosticData {
+pub struct BinaryDiagnosticData {
     pub entries: Vec<u32>,
     pub bits_per_line: usize,
 }
@@ -56,6 +59,47 @@ impl BinaryDiagnosticData {
             .count()
     }
 
+    /// Formats the entries in binary, padded to the number of bits per line.
+    ///
+    /// Only a few entries are listed, since the first rounds of filtering
+    /// have most of the input left.
+    pub fn describe(&self) -> String {
+        const LISTED: usize = 8;
+        let mut listed = self
+            .entries
+            .iter()
+            .take(LISTED)
+            .map(|num| format!("{:0width$b}", num, width = self.bits_per_line))
+            .collect::<Vec<_>>();
+        if self.len() > LISTED {
+            listed.push(format!("and {} more", self.len() - LISTED));
+        }
+        listed.join(", ")
+    }
+
+    /// Keeps the entries whose bit at the given position is the most common
+    /// one, or the least common one if `most_common` is false, with ties
+    /// going to 1.
+    pub fn filter_by_bit(self, i: usize, most_common: bool, name: &str) -> Self {
+        let candidates = self.len();
+        let count_at_index = self.count_bits_at_pos(i);
+        let majority = (candidates as f64 / 2.0).ceil() as usize;
+        let most_often_on = count_at_index >= majority;
+        let keep_on = most_often_on == most_common;
+        let filtered = self.filter(|num| (num & (1 << i) != 0) == keep_on);
+        log::debug!(
+            "{} bit {}: {} of {} candidates have a 1, keeping {}s, {} left",
+            name,
+            i,
+            count_at_index,
+            candidates,
+            keep_on as u8,
+            filtered.len()
+        );
+        log::trace!("{} candidates: {}", name, filtered.describe());
+        filtered
+    }
+
     pub fn filter<P>(self, predicate: P) -> Self
     where
         P: FnMut(&u32) -> bool,
@@ -67,52 +111,82 @@ impl BinaryDiagnosticData {
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let data = BinaryDiagnosticData::from_str(input)?;
-    let bit_count = data.count_bits();
-    let majority = (data.len() as f64 / 2.0).ceil() as usize;
-    let gamma = bit_count
-        .into_iter()
-        .filter(|(_, count)| count >= &majority)
-        .fold(0u32, |result, (i, _)| result | (1 << i));
-    let epsilon = !gamma & ((1 << data.bits_per_line) - 1);
-    let result = gamma as iAoc * epsilon as iAoc;
-    Ok(result)
-}
+pub struct BinaryDiagnostic;
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let data = BinaryDiagnosticData::from_str(input)?;
+impl Solver for BinaryDiagnostic {
+    type Parsed = BinaryDiagnosticData;
 
-    let bits = data.bits_per_line;
-    let mut o2_candidates = data.clone();
-    let mut co2_candidates = data;
-    for i in (0..bits).rev() {
-        let o2_finished = o2_candidates.len() == 1;
-        let co2_finished = co2_candidates.len() == 1;
+    fn parse(input: &str, _: &SolverContext) -> AocResult<BinaryDiagnosticData> {
+        BinaryDiagnosticData::from_str(input)
+    }
 
-        if o2_finished && co2_finished {
-            break;
+    fn part_a(data: &BinaryDiagnosticData, _: &SolverContext) -> AocResult<SolutionValue> {
+        let bit_count = data.count_bits();
+        let majority = (data.len() as f64 / 2.0).ceil() as usize;
+        let gamma = bit_count
+            .iter()
+            .filter(|(_, count)| *count >= &majority)
+            .fold(0u32, |result, (i, _)| result | (1 << i));
+        let epsilon = !gamma & ((1 << data.bits_per_line) - 1);
+        for i in (0..data.bits_per_line).rev() {
+            let ones = bit_count.get(&(i as u8)).copied().unwrap_or(0);
+            log::debug!(
+                "bit {}: {} ones, {} zeros, gamma {}, epsilon {}",
+                i,
+                ones,
+                data.len() - ones,
+                (gamma >> i) & 1,
+                (epsilon >> i) & 1
+            );
         }
+        let result = gamma as iAoc * epsilon as iAoc;
+        Ok(SolutionValue::from(result))
+    }
+
+    fn part_b(data: &BinaryDiagnosticData, _: &SolverContext) -> AocResult<SolutionValue> {
+        let bits = data.bits_per_line;
+        let mut o2_candidates = data.clone();
+        let mut co2_candidates = data.clone();
+        for i in (0..bits).rev() {
+            let o2_finished = o2_candidates.len() == 1;
+            let co2_finished = co2_candidates.len() == 1;
 
-        if !o2_finished {
-            let count_at_index = o2_candidates.count_bits_at_pos(i);
-            let majority = (o2_candidates.len() as f64 / 2.0).ceil() as usize;
-            let most_often_on = count_at_index >= majority;
-            o2_candidates = o2_candidates.filter(|num| (num & (1 << i) != 0) == most_often_on);
+            if o2_finished && co2_finished {
+                break;
+            }
+
+            if !o2_finished {
+                o2_candidates = o2_candidates.filter_by_bit(i, true, "O2");
+            }
+            if !co2_finished {
+                co2_candidates = co2_candidates.filter_by_bit(i, false, "CO2");
+            }
         }
-        if !co2_finished {
-            let count_at_index = co2_candidates.count_bits_at_pos(i);
-            let majority = (co2_candidates.len() as f64 / 2.0).ceil() as usize;
-            let most_often_on = count_at_index >= majority;
-            co2_candidates = co2_candidates.filter(|num| (num & (1 << i) != 0) == !most_often_on);
+
+        if o2_candidates.len() != 1 || co2_candidates.len() != 1 {
+            return Err(AocError::new("value reduction did not complete"));
         }
+        let o2_generator_rating = o2_candidates.entries[0];
+        let co2_scrubber_rating = co2_candidates.entries[0];
+        let result = o2_generator_rating as iAoc * co2_scrubber_rating as iAoc;
+        Ok(SolutionValue::from(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    const EXAMPLE: &str = include_str!("../../input/test/3.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(BinaryDiagnostic::part_a, EXAMPLE, 198);
     }
 
-    if o2_candidates.len() != 1 || co2_candidates.len() != 1 {
-        return Err(AocError::new("value reduction did not complete"));
+    #[test]
+    fn part_b_example() {
+        assert_solves!(BinaryDiagnostic::part_b, EXAMPLE, 230);
     }
-    let o2_generator_rating = o2_candidates.entries[0];
-    let co2_scrubber_rating = co2_candidates.entries[0];
-    let result = o2_generator_rating as iAoc * co2_scrubber_rating as iAoc;
-    Ok(result)
 }
@@ -1,11 +1,13 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
-use num::Integer;
+use crate::common::{
+    iAoc, AocError, AocResult, IntoAocResult, SolutionValue, Solver, SolverContext,
+};
 use std::collections::HashMap;
 use std::num::ParseIntError;
 
 const BOARD_SIZE: usize = 5;
 
-struct BingoBoard {
+#[derive(Clone)]
+pub struct BingoBoard {
     // index_to_num: Vec<Vec<u32>>,
     num_to_index: HashMap<u32, (usize, usize)>,
     markings: [u8; BOARD_SIZE],
@@ -40,7 +42,7 @@ impl BingoBoard {
                 return column_winner;
             }
         }
-        return false;
+        false
     }
 
     pub fn sum_unmarked(&self) -> u32 {
@@ -95,42 +97,48 @@ fn parse_input(input: &str) -> AocResult<(Vec<u32>, Vec<BingoBoard>)> {
     Ok((numbers, boards))
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let (numbers, mut boards) = parse_input(input)?;
-    for num in numbers {
-        for board in &mut boards {
-            if board.mark(num) {
-                if board.is_winner() {
-                    let score = board.sum_unmarked() as iAoc * num as iAoc;
-                    return Ok(score);
-                }
-            }
-        }
-    }
-    Err(AocError::new("no board won"))
-}
-
-fn check_bit(bits: &Vec<u64>, i: usize) -> bool {
+fn check_bit(bits: &[u64], i: usize) -> bool {
     bits[i >> 6] & (1 << (i & 0x3F)) != 0
 }
 
-fn set_bit(bits: &mut Vec<u64>, i: usize) {
+fn set_bit(bits: &mut [u64], i: usize) {
     bits[i >> 6] |= 1 << (i & 0x3F);
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let (numbers, mut boards) = parse_input(input)?;
-    let mut winning_boards: Vec<u64> = vec![0; boards.len().div_ceil(&64)];
-    let mut winning_board_count = 0;
-    let all_but_one = boards.len() - 1;
-    for num in numbers {
-        for i in 0..boards.len() {
-            let board = &mut boards[i];
-            if !check_bit(&winning_boards, i) && board.mark(num) {
-                if board.is_winner() {
+pub struct GiantSquid;
+
+impl Solver for GiantSquid {
+    /// The numbers drawn, in order, and the boards.
+    type Parsed = (Vec<u32>, Vec<BingoBoard>);
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Self::Parsed> {
+        parse_input(input)
+    }
+
+    fn part_a((numbers, boards): &Self::Parsed, _: &SolverContext) -> AocResult<SolutionValue> {
+        let mut boards = boards.clone();
+        for num in numbers {
+            for board in &mut boards {
+                if board.mark(*num) && board.is_winner() {
+                    let score = board.sum_unmarked() as iAoc * *num as iAoc;
+                    return Ok(SolutionValue::from(score));
+                }
+            }
+        }
+        Err(AocError::new("no board won"))
+    }
+
+    fn part_b((numbers, boards): &Self::Parsed, _: &SolverContext) -> AocResult<SolutionValue> {
+        let mut boards = boards.clone();
+        let mut winning_boards: Vec<u64> = vec![0; boards.len().div_ceil(64)];
+        let mut winning_board_count = 0;
+        let all_but_one = boards.len() - 1;
+        for num in numbers {
+            for (i, board) in boards.iter_mut().enumerate() {
+                if !check_bit(&winning_boards, i) && board.mark(*num) && board.is_winner() {
                     if winning_board_count == all_but_one {
-                        let score = board.sum_unmarked() as iAoc * num as iAoc;
-                        return Ok(score);
+                        let score = board.sum_unmarked() as iAoc * *num as iAoc;
+                        return Ok(SolutionValue::from(score));
                     } else {
                         winning_board_count += 1;
                         set_bit(&mut winning_boards, i);
@@ -138,6 +146,24 @@ pub fn solve_b(input: &str) -> AocResult<iAoc> {
                 }
             }
         }
+        Err(AocError::new("all boards never won"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    const EXAMPLE: &str = include_str!("../../input/test/4.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(GiantSquid::part_a, EXAMPLE, 4512);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(GiantSquid::part_b, EXAMPLE, 1924);
     }
-    Err(AocError::new("all boards never won"))
 }
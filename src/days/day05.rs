@@ -1,10 +1,17 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+#[cfg(feature = "visualize")]
+use crate::common::image;
+#[cfg(feature = "visualize")]
+use crate::common::svg::Document;
+use crate::common::{
+    iAoc, parse_lines, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, ParseOptions,
+    SolutionValue, Solver, SolverContext,
+};
 use num::range_step_inclusive;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -16,11 +23,75 @@ impl Point {
     }
 }
 
-struct LineSegment {
+#[derive(Clone)]
+pub struct LineSegment {
     pub begin: Point,
     pub end: Point,
 }
 
+impl LineSegment {
+    /// Parses a segment from the coordinates `x1`, `y1`, `x2`, and `y2`.
+    fn from_coordinates(coordinates: [&str; 4]) -> AocResult<Self> {
+        let [x1, y1, x2, y2] = coordinates.map(|coordinate| {
+            coordinate
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| AocError::new(format!("invalid coordinate '{}'", coordinate.trim())))
+        });
+        Ok(LineSegment {
+            begin: Point::new(x1?, y1?),
+            end: Point::new(x2?, y2?),
+        })
+    }
+
+    /// Parses a segment from tab-separated coordinates, such as `0\t9\t5\t9`.
+    fn from_tsv(input: &str, options: &ParseOptions) -> AocResult<Self> {
+        let mut fields = input.split('\t');
+        let mut next = || {
+            fields
+                .next()
+                .into_aoc_result_msg("segment must have 4 fields")
+        };
+        let segment = Self::from_coordinates([next()?, next()?, next()?, next()?])?;
+        options.expect_end(fields, "segment fields")?;
+        Ok(segment)
+    }
+
+    /// Parses a segment from a flat JSON object, such as
+    /// `{"x1": 0, "y1": 9, "x2": 5, "y2": 9}`.
+    fn from_json(input: &str, options: &ParseOptions) -> AocResult<Self> {
+        const KEYS: [&str; 4] = ["x1", "y1", "x2", "y2"];
+        let fields = input
+            .trim()
+            .strip_prefix('{')
+            .and_then(|input| input.strip_suffix('}'))
+            .into_aoc_result_msg("segment must be a JSON object")?;
+        let mut coordinates = [None; 4];
+        for field in fields.split(',') {
+            let (key, value) = field
+                .split_once(':')
+                .into_aoc_result_msg("segment field must be of the form \"key\": value")?;
+            let key = key.trim();
+            let key = key
+                .strip_prefix('"')
+                .and_then(|key| key.strip_suffix('"'))
+                .ok_or_else(|| AocError::new(format!("segment key {} is not a string", key)))?;
+            match KEYS.iter().position(|known| *known == key) {
+                None => options.tolerate(format!("unknown segment key \"{}\"", key))?,
+                Some(i) => {
+                    if coordinates[i].replace(value).is_some() {
+                        options.tolerate(format!("segment key \"{}\" is given twice", key))?;
+                    }
+                }
+            }
+        }
+        if let Some(i) = coordinates.iter().position(Option::is_none) {
+            return Err(AocError::new(format!("segment is missing \"{}\"", KEYS[i])));
+        }
+        Self::from_coordinates(coordinates.map(Option::unwrap_or_default))
+    }
+}
+
 impl FromStr for LineSegment {
     type Err = AocError;
 
@@ -28,20 +99,82 @@ impl FromStr for LineSegment {
         let (first, second) = input.split_once(" -> ").into_aoc_result()?;
         let (x1, y1) = first.split_once(',').into_aoc_result()?;
         let (x2, y2) = second.split_once(',').into_aoc_result()?;
-        Ok(LineSegment {
-            begin: Point::new(
-                x1.parse::<i32>().into_aoc_result()?,
-                y1.parse::<i32>().into_aoc_result()?,
-            ),
-            end: Point::new(
-                x2.parse::<i32>().into_aoc_result()?,
-                y2.parse::<i32>().into_aoc_result()?,
-            ),
-        })
+        Self::from_coordinates([x1, y1, x2, y2])
+    }
+}
+
+/// Format of the line segments in the input.
+#[derive(Clone, Copy)]
+enum SegmentFormat {
+    /// Picks one of the other formats based on the first line.
+    Auto,
+    /// The puzzle's format, such as `0,9 -> 5,9`.
+    Arrow,
+    /// Tab-separated coordinates, such as `0\t9\t5\t9`.
+    Tsv,
+    /// One JSON object per line, such as `{"x1": 0, "y1": 9, "x2": 5, "y2": 9}`.
+    JsonLines,
+}
+
+impl FromStr for SegmentFormat {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "auto" => Ok(Self::Auto),
+            "arrow" => Ok(Self::Arrow),
+            "tsv" => Ok(Self::Tsv),
+            "jsonl" => Ok(Self::JsonLines),
+            _ => Err(AocError::new("format must be auto, arrow, tsv, or jsonl")),
+        }
+    }
+}
+
+impl SegmentFormat {
+    /// Resolves `Auto` to the format that the input appears to be in.
+    fn detect(self, input: &str) -> Self {
+        let first_line = input.lines().next().unwrap_or_default();
+        match self {
+            Self::Auto if first_line.trim_start().starts_with('{') => Self::JsonLines,
+            Self::Auto if first_line.contains('\t') => Self::Tsv,
+            Self::Auto => Self::Arrow,
+            format => format,
+        }
+    }
+
+    fn parse(self, line: &str, options: &ParseOptions) -> AocResult<LineSegment> {
+        match self {
+            Self::Auto | Self::Arrow => LineSegment::from_str(line),
+            Self::Tsv => LineSegment::from_tsv(line, options),
+            Self::JsonLines => LineSegment::from_json(line, options),
+        }
+    }
+}
+
+/// Parses the line segments, in the format given by the `format` parameter.
+///
+/// Segments that are not horizontal, vertical, or 45-degree diagonal are
+/// drawn as a diagonal that stops at the shorter side, so they are reported.
+fn parse_input(input: &str, context: &SolverContext) -> AocResult<Vec<LineSegment>> {
+    let options = context.parse_options();
+    let format = match context.param("format") {
+        None => SegmentFormat::Auto,
+        Some(format) => SegmentFormat::from_str(format)?,
     }
+    .detect(input);
+    parse_lines(input, |line| {
+        let seg = format.parse(line, options)?;
+        let dx = (seg.end.x - seg.begin.x).abs();
+        let dy = (seg.end.y - seg.begin.y).abs();
+        options.check(
+            dx == 0 || dy == 0 || dx == dy,
+            format!("line {} is not horizontal, vertical, or diagonal", line),
+        )?;
+        Ok(seg)
+    })
 }
 
-fn create_grid(segments: Vec<LineSegment>) -> HashMap<Point, i32> {
+fn create_grid(segments: &[LineSegment]) -> HashMap<Point, i32> {
     let mut grid = HashMap::new();
     for seg in segments {
         // Do not need to worry about slope due to guarantee of the problem,
@@ -70,32 +203,274 @@ fn create_grid(segments: Vec<LineSegment>) -> HashMap<Point, i32> {
     grid
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let mut segments: Vec<LineSegment> = input
-        .lines()
-        .map(|line| LineSegment::from_str(line))
-        .collect::<Result<_, _>>()
-        .into_aoc_result()?;
-    segments = segments
-        .into_iter()
-        .filter(|seg| seg.begin.x == seg.end.x || seg.begin.y == seg.end.y)
-        .collect();
+/// Format of the heatmap image written for the overlap grid.
+#[cfg(feature = "visualize")]
+#[derive(Clone, Copy)]
+enum HeatmapFormat {
+    /// Grayscale, from black (no vents) to white (most overlaps).
+    Pgm,
+    /// Color, from black through red and yellow to white.
+    Ppm,
+    /// Vector image of the vent lines, with overlaps marked in color.
+    Svg,
+}
+
+#[cfg(feature = "visualize")]
+impl FromStr for HeatmapFormat {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "pgm" => Ok(Self::Pgm),
+            "ppm" => Ok(Self::Ppm),
+            "svg" => Ok(Self::Svg),
+            _ => Err(AocError::new("heatmap must be pgm, ppm, or svg")),
+        }
+    }
+}
+
+/// Maps a brightness level to a black-red-yellow-white color ramp.
+#[cfg(feature = "visualize")]
+fn heat_color(level: u8) -> [u8; 3] {
+    let level = level as u32 * 3;
+    let channel = |offset: u32| level.saturating_sub(offset).min(255) as u8;
+    [channel(0), channel(255), channel(510)]
+}
+
+/// Writes a heatmap of the overlap grid if one was requested.
+///
+/// Overlap counts are clipped to the `clip` parameter and scaled so that the
+/// clipped maximum is the brightest pixel.
+#[cfg(feature = "visualize")]
+fn write_heatmap(
+    context: &SolverContext,
+    part: &str,
+    segments: &[LineSegment],
+    grid: &HashMap<Point, i32>,
+    threshold: i32,
+) -> AocResult<()> {
+    let format = match context.param("heatmap") {
+        None => return Ok(()),
+        Some(format) => HeatmapFormat::from_str(format)?,
+    };
+    let clip = match context.param("clip") {
+        None => i32::MAX,
+        Some(clip) => clip
+            .parse::<i32>()
+            .ok()
+            .filter(|clip| *clip > 0)
+            .into_aoc_result_msg("clip must be a positive integer")?,
+    };
+
+    let min_x = grid.keys().map(|point| point.x).min().unwrap_or(0);
+    let max_x = grid.keys().map(|point| point.x).max().unwrap_or(0);
+    let min_y = grid.keys().map(|point| point.y).min().unwrap_or(0);
+    let max_y = grid.keys().map(|point| point.y).max().unwrap_or(0);
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+    let max_overlaps = grid.values().copied().max().unwrap_or(0).min(clip).max(1);
+    let level = |x: usize, y: usize| {
+        let overlaps = grid
+            .get(&Point::new(x as i32 + min_x, y as i32 + min_y))
+            .copied()
+            .unwrap_or(0);
+        (overlaps.min(max_overlaps) * 255 / max_overlaps) as u8
+    };
+
+    let output = context.output();
+    match format {
+        HeatmapFormat::Pgm => {
+            let name = format!("05.{}.heatmap.pgm", part);
+            image::write_pgm(output, &name, width, height, level)
+        }
+        HeatmapFormat::Ppm => {
+            let name = format!("05.{}.heatmap.ppm", part);
+            image::write_ppm(output, &name, width, height, |x, y| heat_color(level(x, y)))
+        }
+        HeatmapFormat::Svg => {
+            let mut document = Document::new(width as f64, height as f64);
+            for seg in segments {
+                let begin = ((seg.begin.x - min_x) as f64, (seg.begin.y - min_y) as f64);
+                let end = ((seg.end.x - min_x) as f64, (seg.end.y - min_y) as f64);
+                document.polyline(&[begin, end], "gray");
+            }
+            for (point, overlaps) in grid {
+                if *overlaps >= threshold {
+                    let (x, y) = ((point.x - min_x) as usize, (point.y - min_y) as usize);
+                    let [r, g, b] = heat_color(level(x, y));
+                    let fill = format!("rgb({},{},{})", r, g, b);
+                    document.circle(x as f64, y as f64, 1.5, &fill);
+                }
+            }
+            let name = format!("05.{}.heatmap.svg", part);
+            document.write(output, &name)
+        }
+    }
+}
+
+#[cfg(not(feature = "visualize"))]
+fn write_heatmap(
+    context: &SolverContext,
+    _: &str,
+    _: &[LineSegment],
+    _: &HashMap<Point, i32>,
+    _: i32,
+) -> AocResult<()> {
+    context.reject_param("heatmap", "visualize")
+}
+
+/// Reads the `threshold` parameter, the number of overlapping lines at which
+/// a point counts as dangerous.
+fn read_threshold(context: &SolverContext) -> AocResult<i32> {
+    match context.param("threshold") {
+        None => Ok(2),
+        Some(threshold) => threshold
+            .parse::<i32>()
+            .ok()
+            .filter(|threshold| *threshold > 0)
+            .into_aoc_result_msg("threshold must be a positive integer"),
+    }
+}
+
+/// Writes how many points reach each threshold from 2 to the most overlaps
+/// if the `report` parameter is set.
+fn write_report(context: &SolverContext, part: &str, grid: &HashMap<Point, i32>) -> AocResult<()> {
+    let enabled = match context.param("report") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("report must be true or false")?,
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    let max_overlaps = grid.values().copied().max().unwrap_or(0);
+    let mut text = String::from("threshold points\n");
+    for threshold in 2..=max_overlaps {
+        text += &format!("{} {}\n", threshold, count_dangerous(grid, threshold));
+    }
+    context
+        .output()
+        .write(&format!("05.{}.report.txt", part), text.as_bytes())
+}
+
+/// Counts the points where at least `threshold` lines overlap.
+fn count_dangerous(grid: &HashMap<Point, i32>, threshold: i32) -> usize {
+    grid.values()
+        .filter(|&&overlaps| overlaps >= threshold)
+        .count()
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[
+        ParamInfo {
+            name: "format",
+            values: "auto|arrow|tsv|jsonl",
+            description: "Format of the line segments: the puzzle's x1,y1 -> x2,y2, tab-separated x1 y1 x2 y2, or one JSON object with keys x1, y1, x2, and y2 per line.",
+            default: "auto, which picks a format based on the first line",
+        },
+        ParamInfo {
+            name: "heatmap",
+            values: "pgm|ppm|svg",
+            description: "Writes a heatmap of vent overlaps to output/05.<part>.heatmap.<format>.",
+            default: "no heatmap",
+        },
+        ParamInfo {
+            name: "clip",
+            values: "n",
+            description: "Clips overlap counts in the heatmap to at most n.",
+            default: "no clipping",
+        },
+        ParamInfo {
+            name: "threshold",
+            values: "n",
+            description: "Number of overlapping lines at which a point counts as dangerous.",
+            default: "2",
+        },
+        ParamInfo {
+            name: "report",
+            values: "true|false",
+            description: "Writes how many points have at least n overlaps, for every n from 2 to the most overlaps, to output/05.<part>.report.txt.",
+            default: "false",
+        },
+    ],
+    animated: false,
+};
+
+pub struct HydrothermalVenture;
+
+impl Solver for HydrothermalVenture {
+    type Parsed = Vec<LineSegment>;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<Vec<LineSegment>> {
+        parse_input(input, context)
+    }
 
-    let grid = create_grid(segments);
-    let result = grid.values().filter(|&&overlaps| overlaps >= 2).count();
+    fn part_a(segments: &Vec<LineSegment>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let segments = segments
+            .iter()
+            .filter(|seg| seg.begin.x == seg.end.x || seg.begin.y == seg.end.y)
+            .cloned()
+            .collect::<Vec<_>>();
 
-    Ok(result as iAoc)
+        let grid = create_grid(&segments);
+        let threshold = read_threshold(context)?;
+        write_heatmap(context, "A", &segments, &grid, threshold)?;
+        write_report(context, "A", &grid)?;
+        let result = count_dangerous(&grid, threshold);
+
+        Ok(SolutionValue::from(result as iAoc))
+    }
+
+    fn part_b(segments: &Vec<LineSegment>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let grid = create_grid(segments);
+        let threshold = read_threshold(context)?;
+        write_heatmap(context, "B", segments, &grid, threshold)?;
+        write_report(context, "B", &grid)?;
+        let result = count_dangerous(&grid, threshold);
+
+        Ok(SolutionValue::from(result as iAoc))
+    }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let segments: Vec<LineSegment> = input
-        .lines()
-        .map(|line| LineSegment::from_str(line))
-        .collect::<Result<_, _>>()
-        .into_aoc_result()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
 
-    let grid = create_grid(segments);
-    let result = grid.values().filter(|&&overlaps| overlaps >= 2).count();
+    const EXAMPLE: &str = include_str!("../../input/test/5.1.txt");
+    const TSV_EXAMPLE: &str = include_str!("../../input/test/5.2.txt");
+    const JSONL_EXAMPLE: &str = include_str!("../../input/test/5.3.txt");
 
-    Ok(result as iAoc)
+    #[test]
+    fn part_a_example() {
+        assert_solves!(HydrothermalVenture::part_a, EXAMPLE, 5);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(HydrothermalVenture::part_b, EXAMPLE, 12);
+    }
+
+    #[test]
+    fn part_a_tsv_example() {
+        assert_solves!(HydrothermalVenture::part_a, TSV_EXAMPLE, 5);
+    }
+
+    #[test]
+    fn part_b_tsv_example() {
+        assert_solves!(HydrothermalVenture::part_b, TSV_EXAMPLE, 12);
+    }
+
+    #[test]
+    fn part_a_jsonl_example() {
+        assert_solves!(HydrothermalVenture::part_a, JSONL_EXAMPLE, 5);
+    }
+
+    #[test]
+    fn part_b_jsonl_example() {
+        assert_solves!(HydrothermalVenture::part_b, JSONL_EXAMPLE, 12);
+    }
 }
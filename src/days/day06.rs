@@ -1,4 +1,8 @@
-use crate::common::{iAoc, AocResult, IntoAocResult};
+use crate::common::math::OverflowPolicy;
+use crate::common::{
+    iAoc, AocResult, DayInfo, IntoAocResult, ParamInfo, SolutionValue, Solver, SolverContext,
+};
+use num::BigUint;
 
 fn parse_input(input: &str) -> AocResult<Vec<u8>> {
     input
@@ -12,33 +16,110 @@ const fn max(a: usize, b: usize) -> usize {
     [a, b][(a < b) as usize]
 }
 
-fn count_lanternfish(input: &str, days: usize) -> AocResult<iAoc> {
-    let lanternfish = parse_input(input.trim())?;
-    const FISH_TIMER: usize = 6;
-    const NEW_FISH_TIMER: usize = 8;
+const FISH_TIMER: usize = 6;
+const NEW_FISH_TIMER: usize = 8;
 
-    const LENGTH: usize = max(FISH_TIMER, NEW_FISH_TIMER) + 1;
+const LENGTH: usize = max(FISH_TIMER, NEW_FISH_TIMER) + 1;
+
+/// Advances the frequency of each timer value by the given number of days,
+/// using `add` to combine counts.
+fn simulate<T, F>(mut timers: [T; LENGTH], days: usize, add: F) -> AocResult<[T; LENGTH]>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> AocResult<T>,
+{
+    for _ in 0..days {
+        let new_fish = timers[0].clone();
+        timers.rotate_left(1);
+        timers[FISH_TIMER] = add(&timers[FISH_TIMER], &new_fish)?;
+        timers[NEW_FISH_TIMER] = new_fish;
+    }
+    Ok(timers)
+}
+
+fn count_lanternfish(
+    lanternfish: &[u8],
+    days: usize,
+    context: &SolverContext,
+) -> AocResult<SolutionValue> {
+    let overflow = context.overflow();
 
     // Stores the frequency of each timer value.
-    let mut timers: [u64; LENGTH] = [0; LENGTH];
+    let mut timers: [iAoc; LENGTH] = [0; LENGTH];
     for fish in lanternfish {
-        timers[fish as usize] += 1;
+        timers[*fish as usize] += 1;
     }
 
-    for _ in 0..days {
-        let new_fish = timers[0];
-        timers.rotate_left(1);
-        timers[FISH_TIMER] += new_fish;
-        timers[NEW_FISH_TIMER] = new_fish;
+    let count =
+        simulate(timers, days, |a, b| overflow.add(*a, *b)).and_then(|timers| overflow.sum(timers));
+    match count {
+        Ok(count) => Ok(SolutionValue::Number(count)),
+        Err(_) if overflow == OverflowPolicy::BigInt => {
+            // The population grows exponentially, so it no longer fits after a
+            // few hundred days. Start over with big integers.
+            let timers = simulate(timers.map(BigUint::from), days, |a, b| Ok(a + b))?;
+            Ok(SolutionValue::Text(
+                timers.iter().sum::<BigUint>().to_string(),
+            ))
+        }
+        Err(err) => Err(err),
     }
+}
 
-    Ok(timers.iter().sum::<iAoc>())
+/// Number of days to simulate, which can be overridden with the `days` parameter.
+fn days(context: &SolverContext, default: usize) -> AocResult<usize> {
+    match context.param("days") {
+        None => Ok(default),
+        Some(days) => days
+            .parse::<usize>()
+            .into_aoc_result_msg("days must be a non-negative integer"),
+    }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    count_lanternfish(input, 80)
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[ParamInfo {
+        name: "days",
+        values: "n",
+        description: "Number of days to simulate.",
+        default: "80 for part A, 256 for part B",
+    }],
+    animated: false,
+};
+
+pub struct Lanternfish;
+
+impl Solver for Lanternfish {
+    /// The timer of each fish.
+    type Parsed = Vec<u8>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Vec<u8>> {
+        parse_input(input.trim())
+    }
+
+    fn part_a(lanternfish: &Vec<u8>, context: &SolverContext) -> AocResult<SolutionValue> {
+        count_lanternfish(lanternfish, days(context, 80)?, context)
+    }
+
+    fn part_b(lanternfish: &Vec<u8>, context: &SolverContext) -> AocResult<SolutionValue> {
+        count_lanternfish(lanternfish, days(context, 256)?, context)
+    }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    count_lanternfish(input, 256)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    const EXAMPLE: &str = include_str!("../../input/test/6.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(Lanternfish::part_a, EXAMPLE, 5934);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(Lanternfish::part_b, EXAMPLE, 26984457539);
+    }
 }
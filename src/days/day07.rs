@@ -1,5 +1,9 @@
-use crate::common::{iAoc, AocResult, IntoAocResult};
+use crate::common::{
+    iAoc, parallel, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, Rng, SolutionValue,
+    Solver, SolverContext, StrategyInfo,
+};
 use num::Integer;
+use std::str::FromStr;
 
 fn parse_input(input: &str) -> AocResult<Vec<i32>> {
     input
@@ -9,25 +13,85 @@ fn parse_input(input: &str) -> AocResult<Vec<i32>> {
         .into_aoc_result()
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let mut positions = parse_input(input.trim())?;
+/// Generates crabs at random positions with the size given as
+/// `<crabs>,<width>`, for benchmarking on position ranges wider than the
+/// input's.
+fn generate(size: &str) -> AocResult<Vec<i32>> {
+    let (crabs, width) = size
+        .split_once(',')
+        .and_then(|(crabs, width)| {
+            Some((crabs.parse::<usize>().ok()?, width.parse::<usize>().ok()?))
+        })
+        .filter(|(crabs, width)| *crabs > 0 && (1..=i32::MAX as usize).contains(width))
+        .into_aoc_result_msg("generate must be a number of crabs and a width like 1000,100000")?;
+    let mut rng = Rng::new(7);
+    Ok((0..crabs).map(|_| rng.below(width) as i32).collect())
+}
 
-    positions.sort();
-    let mid = positions.len() / 2;
-    let median = positions[mid];
+/// Reads the crab positions from the input, or generates them if the
+/// `generate` param is set.
+fn read_positions(input: &str, context: &SolverContext) -> AocResult<Vec<i32>> {
+    match context.param("generate") {
+        None => parse_input(input.trim()),
+        Some(size) => generate(size),
+    }
+}
 
-    let result: i32 = positions.into_iter().map(|pos| (pos - median).abs()).sum();
+/// How much fuel a crab burns for each step it moves.
+#[derive(Clone, Copy)]
+enum FuelRate {
+    /// Every step costs 1 fuel.
+    Constant,
+    /// Each step costs 1 more fuel than the one before.
+    Increasing,
+}
 
-    Ok(result as iAoc)
+/// Strategy used to find the cheapest position to align to.
+#[derive(Clone, Copy)]
+enum AlignStrategy {
+    /// Finds the best position directly from the median or the average.
+    Analytic,
+    /// Computes the cost of every position between the outermost crabs.
+    Sweep,
+    /// Runs both and fails if they disagree.
+    CrossCheck,
 }
 
-fn calculate_fuel_cost(steps: i32) -> i32 {
+impl FromStr for AlignStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "analytic" => Ok(Self::Analytic),
+            "sweep" => Ok(Self::Sweep),
+            "cross-check" => Ok(Self::CrossCheck),
+            _ => Err(AocError::new(
+                "strategy must be analytic, sweep, or cross-check",
+            )),
+        }
+    }
+}
+
+fn calculate_fuel_cost(steps: i64) -> i64 {
     (steps * (steps + 1)) / 2
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let positions = parse_input(input.trim())?;
+/// Finds the cheapest cost to align the crabs to the median.
+fn align_to_median(mut positions: Vec<i32>) -> AocResult<i64> {
+    positions.sort();
+    let mid = positions.len() / 2;
+    let median = *positions
+        .get(mid)
+        .into_aoc_result_msg("there are no crabs")? as i64;
+
+    Ok(positions
+        .into_iter()
+        .map(|pos| (pos as i64 - median).abs())
+        .sum())
+}
 
+/// Finds the cheapest cost to align the crabs near the average.
+fn align_to_average(positions: Vec<i32>) -> AocResult<i64> {
     /*
 
         Let C be the set of crab positions, where c is a single position.
@@ -114,21 +178,204 @@ pub fn solve_b(input: &str) -> AocResult<iAoc> {
 
     */
 
-    let min = positions
-        .iter()
-        .sum::<i32>()
-        .div_floor(&(positions.len() as i32));
+    if positions.is_empty() {
+        return Err(AocError::new("there are no crabs"));
+    }
+    let positions = positions.into_iter().map(i64::from).collect::<Vec<_>>();
+    let min = Integer::div_floor(&positions.iter().sum::<i64>(), &(positions.len() as i64));
     let max = min + 1;
 
-    let result1: i32 = positions
+    let result1: i64 = positions
         .iter()
         .map(|pos| calculate_fuel_cost((pos - min).abs()))
         .sum();
-    let result2: i32 = positions
+    let result2: i64 = positions
         .into_iter()
         .map(|pos| calculate_fuel_cost((pos - max).abs()))
         .sum();
-    let result = result1.min(result2);
+    Ok(result1.min(result2))
+}
+
+/*
+
+    The sweep computes the cost of every target t between the outermost crabs
+    without visiting every crab for every target.
+
+    With L(t) crabs at or left of t, whose positions sum to S(t), and n crabs
+    whose positions sum to T, the total distance is:
+
+        D(t) = sum_c |c-t| = (t L(t) - S(t)) + ((T - S(t)) - t (n - L(t)))
 
-    Ok(result as iAoc)
+    L and S are prefix sums over the crabs counted at each position, so every
+    D(t) takes constant time.
+
+    With increasing fuel rates, each crab costs d (d+1) / 2 = (d^2 + d) / 2
+    for a distance d. The squares need no absolute value, so with Q the sum of
+    the squared positions:
+
+        sum_c (c-t)^2 = Q - 2 t T + n t^2
+
+        F(t) = (Q - 2 t T + n t^2 + D(t)) / 2
+
+    Both take O(n + range) time in total, and the targets are split across
+    threads.
+
+*/
+
+/// The crabs at or left of a target position.
+struct Prefix {
+    target: i64,
+    count: i64,
+    sum: i64,
+}
+
+/// Finds the cheapest cost by computing the cost of every position between
+/// the outermost crabs.
+fn sweep(positions: &[i32], rate: FuelRate, threads: usize) -> AocResult<i64> {
+    let lo = *positions
+        .iter()
+        .min()
+        .into_aoc_result_msg("there are no crabs")? as i64;
+    let hi = *positions.iter().max().into_aoc_result()? as i64;
+    let mut counts = vec![0i64; (hi - lo + 1) as usize];
+    for pos in positions {
+        counts[(*pos as i64 - lo) as usize] += 1;
+    }
+
+    let mut prefixes = Vec::with_capacity(counts.len());
+    let (mut count, mut sum) = (0, 0);
+    for (target, crabs) in (lo..=hi).zip(counts) {
+        count += crabs;
+        sum += crabs * target;
+        prefixes.push(Prefix { target, count, sum });
+    }
+
+    let n = positions.len() as i64;
+    let total = sum;
+    let squares = positions
+        .iter()
+        .map(|pos| (*pos as i64).pow(2))
+        .sum::<i64>();
+    let cost = |Prefix {
+                    target: t,
+                    count,
+                    sum,
+                }: &Prefix| {
+        let distance = (t * count - sum) + ((total - sum) - t * (n - count));
+        match rate {
+            FuelRate::Constant => distance,
+            FuelRate::Increasing => (squares - 2 * t * total + n * t * t + distance) / 2,
+        }
+    };
+    parallel::map_chunks(&prefixes, threads, |prefixes| {
+        prefixes.iter().map(cost).min()
+    })
+    .into_iter()
+    .flatten()
+    .min()
+    .into_aoc_result()
+}
+
+/// Finds the cheapest cost to align the crabs with the requested strategy.
+fn cheapest(positions: Vec<i32>, rate: FuelRate, context: &SolverContext) -> AocResult<i64> {
+    let analytic = |positions| match rate {
+        FuelRate::Constant => align_to_median(positions),
+        FuelRate::Increasing => align_to_average(positions),
+    };
+    match context.strategy_or(AlignStrategy::Analytic)? {
+        AlignStrategy::Analytic => analytic(positions),
+        AlignStrategy::Sweep => sweep(&positions, rate, context.threads()),
+        AlignStrategy::CrossCheck => {
+            let swept = sweep(&positions, rate, context.threads())?;
+            let analytic = analytic(positions)?;
+            if analytic != swept {
+                Err(AocError::new(format!(
+                    "analytic solution found cost {} but the sweep found cost {}",
+                    analytic, swept
+                )))
+            } else {
+                Ok(analytic)
+            }
+        }
+    }
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "analytic",
+            description: "Finds the best position directly from the median or the average.",
+        },
+        StrategyInfo {
+            name: "sweep",
+            description: "Computes the cost of every position between the outermost crabs with prefix sums, split across threads.",
+        },
+        StrategyInfo {
+            name: "cross-check",
+            description: "Runs both and fails if they disagree.",
+        },
+    ],
+    params: &[ParamInfo {
+        name: "generate",
+        values: "<crabs>,<width>",
+        description: "Ignores the input and uses the given number of crabs at random positions below the width, for benchmarking.",
+        default: "the input",
+    }],
+    animated: false,
+};
+
+pub struct TreacheryOfWhales;
+
+impl Solver for TreacheryOfWhales {
+    /// The position of each crab.
+    type Parsed = Vec<i32>;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<Vec<i32>> {
+        read_positions(input, context)
+    }
+
+    fn part_a(positions: &Vec<i32>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let result = cheapest(positions.clone(), FuelRate::Constant, context)?;
+        Ok(SolutionValue::from(result as iAoc))
+    }
+
+    fn part_b(positions: &Vec<i32>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let result = cheapest(positions.clone(), FuelRate::Increasing, context)?;
+        Ok(SolutionValue::from(result as iAoc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::{self, assert_solves};
+
+    const EXAMPLE: &str = include_str!("../../input/test/7.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(TreacheryOfWhales::part_a, EXAMPLE, 37);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(TreacheryOfWhales::part_b, EXAMPLE, 168);
+    }
+
+    #[test]
+    fn every_strategy_rejects_no_crabs() {
+        for rate in [FuelRate::Constant, FuelRate::Increasing] {
+            for strategy in ["analytic", "sweep", "cross-check"] {
+                let context = testing::context(Some(strategy), &[]);
+                let err = cheapest(Vec::new(), rate, &context).unwrap_err();
+                assert_eq!(err.message(), "there are no crabs");
+            }
+        }
+    }
+
+    #[test]
+    fn generate_rejects_no_crabs() {
+        let context = testing::context(None, &[("generate", "0,100")]);
+        assert!(TreacheryOfWhales::parse("", &context).is_err());
+    }
 }
@@ -1,5 +1,9 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, SolutionValue, Solver, SolverContext,
+    StrategyInfo,
+};
 use std::collections::HashSet;
+use std::str::FromStr;
 
 /// Each segment of a seven segment display can be mapped to a single bit.
 /// Thus, an entire display can be stored as an 8-bit integer (or byte).
@@ -44,7 +48,7 @@ impl SevenSegment {
                 'e' => Ok(E),
                 'f' => Ok(F),
                 'g' => Ok(G),
-                _ => return Err(AocError::new("invalid character for seven segment display")),
+                _ => Err(AocError::new("invalid character for seven segment display")),
             })
             .try_fold(0, |acc, seg| Ok(acc | seg? as u8))
     }
@@ -66,7 +70,7 @@ impl SevenSegment {
 ///     Consider "bc" => 0b000110.
 ///     This display can only be 0b0100100, so bits B and C can be mapped to {C, F}.
 ///     The key must be further expored to figure out which bit maps to C and which maps to F.
-struct SegmentWiring {
+pub struct SegmentWiring {
     key: Vec<u8>,
     reading: Vec<u8>,
 }
@@ -77,9 +81,9 @@ impl SegmentWiring {
             .iter()
             .map(|output| {
                 let mut result = 0;
-                for bit in 0..7 {
+                for (bit, segment) in mapping.iter().enumerate() {
                     if output & (1 << bit) != 0 {
-                        result |= mapping[bit];
+                        result |= segment;
                     }
                 }
 
@@ -93,65 +97,13 @@ impl SegmentWiring {
             })
             .try_fold(0u64, |acc, digit| Ok(10 * acc + digit? as u64))
     }
-}
 
-fn parse_input(input: &str) -> AocResult<Vec<SegmentWiring>> {
-    input
-        .lines()
-        .map::<AocResult<_>, _>(|line| {
-            let (input, output) = line.split_once(" | ").into_aoc_result()?;
-            Ok(SegmentWiring {
-                key: input
-                    .split(' ')
-                    .map(|s| SevenSegment::from_str(s))
-                    .collect::<Result<_, _>>()?,
-                reading: output
-                    .split(' ')
-                    .map(|s| SevenSegment::from_str(s))
-                    .collect::<Result<_, _>>()?,
-            })
-        })
-        .collect::<AocResult<Vec<_>>>()
-}
-
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    const DESIRED_DIGITS: [usize; 4] = [1, 4, 7, 8];
-
-    // The number of bits that should be set for numbers we're interested in.
-    let desired_count_ones: HashSet<u32> = DESIRED_DIGITS
-        .iter()
-        .map(|digit| SevenSegment::DIGIT_DISPLAY[*digit].count_ones())
-        .collect();
-
-    let wirings = parse_input(input)?;
-    let result: usize = wirings
-        .into_iter()
-        .map(|wiring| {
-            wiring
-                .reading
-                .into_iter()
-                .filter(|out| desired_count_ones.contains(&out.count_ones()))
-                .count()
-        })
-        .sum();
-    Ok(result as iAoc)
-}
-
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    // Maps the number of bits set to the potential digits it could be.
-    let mut ones_count_to_digit: Vec<Vec<usize>> = std::iter::repeat(vec![]).take(8).collect();
-    for (digit, display) in SevenSegment::DIGIT_DISPLAY.iter().enumerate() {
-        ones_count_to_digit[display.count_ones() as usize].push(digit);
-    }
-
-    let wirings = parse_input(input)?;
-    let mut result: iAoc = 0;
-
-    for wiring in &wirings {
+    /// Decodes the reading by narrowing down the segments each bit could map to.
+    pub fn decode_by_deduction(&self, ones_count_to_digit: &[Vec<usize>]) -> AocResult<u64> {
         // Maps a single segment bit to the potential segments it can be,
         // represented by a bit string.
         let mut segment_mapping: [u8; 7] = [0b1111111; 7];
-        for key in &wiring.key {
+        for key in &self.key {
             // For each bit, update the potential the segment mapping.
             // If the bit is on, then it must map to the union (bitwise OR) of potential bit mappings.
             // If the bit is off, then it must not map to the union of potential bit mappings,
@@ -165,8 +117,7 @@ pub fn solve_b(input: &str) -> AocResult<iAoc> {
                 });
             potential_if_inactive = !potential_if_inactive & 0b1111111;
 
-            for bit in 0..7 {
-                let entry = &mut segment_mapping[bit];
+            for (bit, entry) in segment_mapping.iter_mut().enumerate() {
                 *entry &= if key & (1 << bit) != 0 {
                     potential_if_active
                 } else {
@@ -194,9 +145,247 @@ pub fn solve_b(input: &str) -> AocResult<iAoc> {
             }
         }
 
-        // Read back the display and add it to the result.
-        result += wiring.read(segment_mapping)?;
+        self.read(segment_mapping)
     }
 
-    Ok(result as iAoc)
+    /// Decodes the reading using segment frequencies.
+    ///
+    /// Across the ten digits, each segment is lit a fixed number of times, no
+    /// matter how the wires are scrambled. Summing those counts over the lit
+    /// segments of a display gives a signature that is different for every digit.
+    pub fn decode_by_frequency(&self) -> AocResult<u64> {
+        let signature = |displays: &[u8], display: u8| -> u32 {
+            (0..7)
+                .filter(|bit| display & (1 << bit) != 0)
+                .map(|bit| {
+                    displays
+                        .iter()
+                        .filter(|other| *other & (1 << bit) != 0)
+                        .count() as u32
+                })
+                .sum()
+        };
+
+        let digit_signatures = SevenSegment::DIGIT_DISPLAY
+            .iter()
+            .map(|display| signature(&SevenSegment::DIGIT_DISPLAY, *display))
+            .collect::<Vec<_>>();
+
+        self.reading
+            .iter()
+            .map(|output| {
+                let output_signature = signature(&self.key, *output);
+                digit_signatures
+                    .iter()
+                    .position(|&signature| signature == output_signature)
+                    .into_aoc_result_msg("failed to map output to a proper digit")
+            })
+            .try_fold(0u64, |acc, digit| Ok(10 * acc + digit? as u64))
+    }
+}
+
+fn parse_input(input: &str) -> AocResult<Vec<SegmentWiring>> {
+    input
+        .lines()
+        .map::<AocResult<_>, _>(|line| {
+            let (input, output) = line.split_once(" | ").into_aoc_result()?;
+            Ok(SegmentWiring {
+                key: input
+                    .split(' ')
+                    .map(SevenSegment::from_str)
+                    .collect::<Result<_, _>>()?,
+                reading: output
+                    .split(' ')
+                    .map(SevenSegment::from_str)
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect::<AocResult<Vec<_>>>()
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "deduction",
+            description: "Narrows down the possible segments for each wire.",
+        },
+        StrategyInfo {
+            name: "frequency",
+            description: "Identifies digits by their segment frequency signatures.",
+        },
+    ],
+    params: &[],
+    animated: false,
+};
+
+/// Strategy used to decode the scrambled displays.
+#[derive(Clone, Copy)]
+enum DecodeStrategy {
+    /// Narrows down the possible segments for each wire.
+    Deduction,
+    /// Identifies digits by their segment frequency signatures.
+    Frequency,
+}
+
+impl FromStr for DecodeStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "deduction" => Ok(Self::Deduction),
+            "frequency" => Ok(Self::Frequency),
+            _ => Err(AocError::new("strategy must be deduction or frequency")),
+        }
+    }
+}
+
+/// Maps the number of bits set to the potential digits it could be.
+fn ones_count_to_digit() -> Vec<Vec<usize>> {
+    let mut ones_count_to_digit: Vec<Vec<usize>> = std::iter::repeat_n(vec![], 8).collect();
+    for (digit, display) in SevenSegment::DIGIT_DISPLAY.iter().enumerate() {
+        ones_count_to_digit[display.count_ones() as usize].push(digit);
+    }
+    ones_count_to_digit
+}
+
+pub struct SevenSegmentSearch;
+
+impl Solver for SevenSegmentSearch {
+    type Parsed = Vec<SegmentWiring>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Vec<SegmentWiring>> {
+        parse_input(input)
+    }
+
+    fn part_a(wirings: &Vec<SegmentWiring>, _: &SolverContext) -> AocResult<SolutionValue> {
+        const DESIRED_DIGITS: [usize; 4] = [1, 4, 7, 8];
+
+        // The number of bits that should be set for numbers we're interested in.
+        let desired_count_ones: HashSet<u32> = DESIRED_DIGITS
+            .iter()
+            .map(|digit| SevenSegment::DIGIT_DISPLAY[*digit].count_ones())
+            .collect();
+
+        let result: usize = wirings
+            .iter()
+            .map(|wiring| {
+                wiring
+                    .reading
+                    .iter()
+                    .filter(|out| desired_count_ones.contains(&out.count_ones()))
+                    .count()
+            })
+            .sum();
+        Ok(SolutionValue::from(result as iAoc))
+    }
+
+    fn part_b(wirings: &Vec<SegmentWiring>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let strategy = context.strategy_or(DecodeStrategy::Deduction)?;
+        let ones_count_to_digit = ones_count_to_digit();
+
+        let mut result: iAoc = 0;
+        for wiring in wirings {
+            // Read back the display and add it to the result.
+            result += match strategy {
+                DecodeStrategy::Frequency => wiring.decode_by_frequency()?,
+                DecodeStrategy::Deduction => wiring.decode_by_deduction(&ones_count_to_digit)?,
+            };
+        }
+
+        Ok(SolutionValue::from(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+    use crate::common::Rng;
+
+    const SINGLE_ENTRY_EXAMPLE: &str = include_str!("../../input/test/8.1.txt");
+    const EXAMPLE: &str = include_str!("../../input/test/8.2.txt");
+    const INPUT: &str = include_str!("../../input/8.txt");
+
+    impl SegmentWiring {
+        /// Generates a randomly scrambled wiring, returning it with the value of its reading.
+        fn scrambled(rng: &mut Rng, reading_len: usize) -> (Self, u64) {
+            let mut wires = [0, 1, 2, 3, 4, 5, 6];
+            rng.shuffle(&mut wires);
+            let scramble = |display: u8| {
+                wires
+                    .iter()
+                    .enumerate()
+                    .filter(|(segment, _)| display & (1 << segment) != 0)
+                    .fold(0, |acc, (_, wire)| acc | (1 << wire))
+            };
+
+            let mut key = SevenSegment::DIGIT_DISPLAY
+                .iter()
+                .map(|display| scramble(*display))
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut key);
+
+            let digits = (0..reading_len).map(|_| rng.below(10)).collect::<Vec<_>>();
+            let reading = digits
+                .iter()
+                .map(|digit| scramble(SevenSegment::DIGIT_DISPLAY[*digit]))
+                .collect();
+            let value = digits.iter().fold(0, |acc, digit| 10 * acc + *digit as u64);
+            (SegmentWiring { key, reading }, value)
+        }
+    }
+
+    #[test]
+    fn part_a_single_entry_example() {
+        assert_solves!(SevenSegmentSearch::part_a, SINGLE_ENTRY_EXAMPLE, 0);
+    }
+
+    #[test]
+    fn part_b_single_entry_example() {
+        assert_solves!(SevenSegmentSearch::part_b, SINGLE_ENTRY_EXAMPLE, 5353);
+    }
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(SevenSegmentSearch::part_a, EXAMPLE, 26);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(SevenSegmentSearch::part_b, EXAMPLE, 61229);
+    }
+
+    #[test]
+    fn frequency_examples() {
+        assert_solves!(SevenSegmentSearch::part_b, SINGLE_ENTRY_EXAMPLE, 5353, strategy: "frequency");
+        assert_solves!(SevenSegmentSearch::part_b, EXAMPLE, 61229, strategy: "frequency");
+    }
+
+    #[test]
+    fn decoders_agree_on_the_examples_and_input() {
+        let ones_count_to_digit = ones_count_to_digit();
+        for input in [SINGLE_ENTRY_EXAMPLE, EXAMPLE, INPUT] {
+            for wiring in parse_input(input).unwrap() {
+                assert_eq!(
+                    wiring.decode_by_deduction(&ones_count_to_digit).unwrap(),
+                    wiring.decode_by_frequency().unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decoders_read_generated_scrambles() {
+        const SCRAMBLES: usize = 1000;
+        let ones_count_to_digit = ones_count_to_digit();
+        let mut rng = Rng::new(8);
+        for _ in 0..SCRAMBLES {
+            let (wiring, expected) = SegmentWiring::scrambled(&mut rng, 4);
+            assert_eq!(
+                wiring.decode_by_deduction(&ones_count_to_digit).unwrap(),
+                expected
+            );
+            assert_eq!(wiring.decode_by_frequency().unwrap(), expected);
+        }
+    }
 }
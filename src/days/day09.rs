@@ -1,8 +1,16 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
-use std::collections::{HashSet, VecDeque};
+#[cfg(feature = "visualize")]
+use crate::common::image;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, SolutionValue, Solver,
+    SolverContext, StrategyInfo,
+};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Write;
 use std::str::FromStr;
 
-struct HeightMap {
+type Point = (usize, usize);
+
+pub struct HeightMap {
     map: Vec<Vec<u32>>,
     height: usize,
     width: usize,
@@ -46,6 +54,158 @@ impl HeightMap {
             Some((row, col + 1)),
         ]
     }
+
+    /// Splits the height map into basins separated by ridges of height 9.
+    pub fn terrain(&self) -> Terrain {
+        let mut labels = vec![vec![None; self.width]; self.height];
+        let mut basin_sizes = Vec::new();
+        let mut ridges = Vec::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let point = (row, col);
+                if self.get(point) == 9 {
+                    ridges.push(point);
+                } else if labels[row][col].is_none() {
+                    let basin = basin_sizes.len();
+                    // Current basin size.
+                    let mut basin_size = 0;
+                    // Points to explore.
+                    let mut explore_queue = VecDeque::new();
+                    explore_queue.push_back(point);
+
+                    while let Some(point @ (row, col)) = explore_queue.pop_front() {
+                        if labels[row][col].is_some() {
+                            continue;
+                        }
+                        labels[row][col] = Some(basin);
+                        basin_size += 1;
+                        for &neighbor @ (row, col) in self.get_neighbors(point).iter().flatten() {
+                            if self.get(neighbor) != 9 && labels[row][col].is_none() {
+                                explore_queue.push_back(neighbor);
+                            }
+                        }
+                    }
+                    basin_sizes.push(basin_size);
+                }
+            }
+        }
+
+        // Two basins touch if a single ridge point separates them.
+        let mut adjacent_basins = vec![BTreeSet::new(); basin_sizes.len()];
+        for &point in &ridges {
+            let bordering = self
+                .get_neighbors(point)
+                .iter()
+                .flatten()
+                .filter_map(|(row, col)| *labels.get(*row)?.get(*col)?)
+                .collect::<BTreeSet<_>>();
+            for &basin in &bordering {
+                adjacent_basins[basin].extend(bordering.iter().filter(|&&other| other != basin));
+            }
+        }
+
+        Terrain {
+            labels,
+            basin_sizes,
+            ridges,
+            adjacent_basins,
+        }
+    }
+}
+
+/// The basins of a height map and the ridges between them.
+pub struct Terrain {
+    /// The basin each point belongs to, or `None` for ridge points. Only the
+    /// terrain image reads them.
+    #[cfg_attr(not(feature = "visualize"), allow(dead_code))]
+    labels: Vec<Vec<Option<usize>>>,
+    basin_sizes: Vec<usize>,
+    /// Points of height 9, which belong to no basin.
+    ridges: Vec<Point>,
+    /// For each basin, the basins separated from it by a single ridge point.
+    adjacent_basins: Vec<BTreeSet<usize>>,
+}
+
+impl Terrain {
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        writeln!(report, "basins: {}", self.basin_sizes.len()).unwrap();
+        writeln!(report, "ridge points: {}", self.ridges.len()).unwrap();
+        for (basin, (size, adjacent)) in self
+            .basin_sizes
+            .iter()
+            .zip(&self.adjacent_basins)
+            .enumerate()
+        {
+            let adjacent = adjacent
+                .iter()
+                .map(|other| other.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                report,
+                "basin {} (size {}): touches {}",
+                basin, size, adjacent
+            )
+            .unwrap();
+        }
+        report
+    }
+}
+
+/// Format of the terrain analysis written for part B.
+#[derive(Clone, Copy)]
+enum TerrainFormat {
+    /// Basin sizes and adjacency as text.
+    Text,
+    /// Grayscale image with white ridges and each basin in its own shade.
+    #[cfg(feature = "visualize")]
+    Pgm,
+}
+
+impl FromStr for TerrainFormat {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "text" => Ok(Self::Text),
+            #[cfg(feature = "visualize")]
+            "pgm" => Ok(Self::Pgm),
+            #[cfg(not(feature = "visualize"))]
+            "pgm" => Err(AocError::new(
+                "terrain pgm requires the visualize feature, which this build does not have",
+            )),
+            _ => Err(AocError::new("terrain must be text or pgm")),
+        }
+    }
+}
+
+fn write_terrain(
+    context: &SolverContext,
+    terrain: &Terrain,
+    format: TerrainFormat,
+) -> AocResult<()> {
+    match format {
+        TerrainFormat::Text => context
+            .output()
+            .write("09.B.terrain.txt", terrain.report().as_bytes()),
+        #[cfg(feature = "visualize")]
+        TerrainFormat::Pgm => {
+            let height = terrain.labels.len();
+            let width = terrain.labels.first().map_or(0, |row| row.len());
+            image::write_pgm(
+                context.output(),
+                "09.B.terrain.pgm",
+                width,
+                height,
+                |x, y| match terrain.labels[y][x] {
+                    None => 255,
+                    // Spread neighboring labels across the gray range so basins stand apart.
+                    Some(basin) => (32 + basin * 37 % 160) as u8,
+                },
+            )
+        }
+    }
 }
 
 impl FromStr for HeightMap {
@@ -65,61 +225,178 @@ impl FromStr for HeightMap {
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let height_map = HeightMap::from_str(input)?;
-    let mut sum_risk_levels = 0;
-    for row in 0..height_map.height {
-        for col in 0..height_map.width {
-            let point = (row, col);
-            if height_map.is_low_point(point) {
-                sum_risk_levels += height_map.get(point) + 1;
+/// Parses a row of the height map, keeping each height as a byte.
+fn parse_row(line: &str) -> AocResult<Vec<u8>> {
+    line.bytes()
+        .map(|byte| match byte {
+            b'0'..=b'9' => Ok(byte - b'0'),
+            _ => Err(AocError::new(format!("invalid height '{}'", byte as char))),
+        })
+        .collect()
+}
+
+/// Sums the risk levels of the low points while only holding three parsed rows
+/// of the height map at a time, so the grid of heights is never built.
+fn sum_risk_levels_streaming<'a, I>(lines: I) -> AocResult<u64>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut lines = lines.into_iter();
+    // Points outside of the map count as height 9, like in `HeightMap::get`.
+    let height = |row: &[u8], col: usize| row.get(col).copied().unwrap_or(9);
+
+    let mut sum = 0;
+    let mut previous = Vec::new();
+    let mut current = match lines.next() {
+        None => return Ok(0),
+        Some(line) => parse_row(line)?,
+    };
+    loop {
+        let next = lines.next().map(parse_row).transpose()?;
+        let below = next.as_deref().unwrap_or_default();
+        for (col, &pos) in current.iter().enumerate() {
+            let is_low_point = pos < height(&previous, col)
+                && pos < height(below, col)
+                && (col == 0 || pos < current[col - 1])
+                && pos < height(&current, col + 1);
+            if is_low_point {
+                sum += pos as u64 + 1;
             }
         }
+        match next {
+            None => return Ok(sum),
+            Some(next) => previous = std::mem::replace(&mut current, next),
+        }
     }
-    Ok(sum_risk_levels as iAoc)
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let height_map = HeightMap::from_str(input)?;
-
-    let mut basin_sizes: Vec<usize> = Vec::new();
-    let mut visited: HashSet<(usize, usize)> = HashSet::new();
-    for row in 0..height_map.height {
-        for col in 0..height_map.width {
-            let point = (row, col);
-            if height_map.get(point) != 9 && !visited.contains(&point) {
-                // Current basin size.
-                let mut basin_size = 0;
-                // Points to explore.
-                let mut explore_queue = VecDeque::new();
-                explore_queue.push_back(point);
-
-                while !explore_queue.is_empty() {
-                    let point = explore_queue.pop_front().unwrap();
-                    if visited.contains(&point) {
-                        continue;
-                    }
-                    visited.insert(point);
-                    basin_size += 1;
-                    for neighbor in height_map.get_neighbors(point) {
-                        if let Some(neighbor) = neighbor {
-                            if !visited.contains(&neighbor) && height_map.get(neighbor) != 9 {
-                                explore_queue.push_back(neighbor);
-                            }
-                        }
-                    }
+/// Strategy used to find the low points in part A.
+#[derive(Clone, Copy)]
+enum LowPointStrategy {
+    /// Parses the whole height map into a grid, then checks every point.
+    Grid,
+    /// Checks each row as it is parsed, keeping only its neighboring rows.
+    Streaming,
+}
+
+impl FromStr for LowPointStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "grid" => Ok(Self::Grid),
+            "streaming" => Ok(Self::Streaming),
+            _ => Err(AocError::new("strategy must be grid or streaming")),
+        }
+    }
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "grid",
+            description: "Parses the whole height map into a grid, then checks every point.",
+        },
+        StrategyInfo {
+            name: "streaming",
+            description:
+                "Checks each row for low points as it is parsed, keeping only the rows around it.",
+        },
+    ],
+    params: &[ParamInfo {
+        name: "terrain",
+        values: "text|pgm",
+        description:
+            "Writes the basins and ridges found in part B to output/09.B.terrain.<format>.",
+        default: "no terrain",
+    }],
+    animated: false,
+};
+
+/// The height map, parsed as the strategy for part A needs it.
+pub enum Heights {
+    Map(HeightMap),
+    /// The text of the height map, which the streaming strategy parses a few
+    /// rows at a time rather than building the grid. The text itself is kept
+    /// whole, since parsing happens once for both parts.
+    Text(String),
+}
+
+pub struct SmokeBasin;
+
+impl Solver for SmokeBasin {
+    type Parsed = Heights;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<Heights> {
+        match context.strategy_or(LowPointStrategy::Grid)? {
+            LowPointStrategy::Grid => Ok(Heights::Map(HeightMap::from_str(input)?)),
+            LowPointStrategy::Streaming => Ok(Heights::Text(input.to_owned())),
+        }
+    }
+
+    fn part_a(heights: &Heights, _: &SolverContext) -> AocResult<SolutionValue> {
+        let height_map = match heights {
+            Heights::Map(height_map) => height_map,
+            Heights::Text(text) => {
+                let result = sum_risk_levels_streaming(text.lines())?;
+                return Ok(SolutionValue::from(result as iAoc));
+            }
+        };
+
+        let mut sum_risk_levels = 0;
+        for row in 0..height_map.height {
+            for col in 0..height_map.width {
+                let point = (row, col);
+                if height_map.is_low_point(point) {
+                    sum_risk_levels += height_map.get(point) + 1;
                 }
-                basin_sizes.push(basin_size);
             }
         }
+        Ok(SolutionValue::from(sum_risk_levels as iAoc))
     }
 
-    if basin_sizes.len() < 3 {
-        return Err(AocError::new("did not find 3 basins"));
+    fn part_b(heights: &Heights, context: &SolverContext) -> AocResult<SolutionValue> {
+        let terrain = match heights {
+            Heights::Map(height_map) => height_map.terrain(),
+            // Finding the basins needs the whole map anyway.
+            Heights::Text(text) => context.parse(|| HeightMap::from_str(text))?.terrain(),
+        };
+        if let Some(format) = context.param("terrain") {
+            write_terrain(context, &terrain, TerrainFormat::from_str(format)?)?;
+        }
+
+        let mut basin_sizes = terrain.basin_sizes;
+        if basin_sizes.len() < 3 {
+            return Err(AocError::new("did not find 3 basins"));
+        }
+
+        basin_sizes.sort_by(|a, b| b.cmp(a));
+        let result = basin_sizes[0] * basin_sizes[1] * basin_sizes[2];
+
+        Ok(SolutionValue::from(result as iAoc))
     }
+}
 
-    basin_sizes.sort_by(|a, b| b.cmp(a));
-    let result = basin_sizes[0] * basin_sizes[1] * basin_sizes[2];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
 
-    Ok(result as iAoc)
+    const EXAMPLE: &str = include_str!("../../input/test/9.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(SmokeBasin::part_a, EXAMPLE, 15);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(SmokeBasin::part_b, EXAMPLE, 1134);
+    }
+
+    #[test]
+    fn streaming_examples() {
+        assert_solves!(SmokeBasin::part_a, EXAMPLE, 15, strategy: "streaming");
+        assert_solves!(SmokeBasin::part_b, EXAMPLE, 1134, strategy: "streaming");
+    }
 }
@@ -1,4 +1,7 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::parallel;
+use crate::common::{
+    iAoc, AocError, AocResult, IntoAocResult, SolutionValue, Solver, SolverContext,
+};
 
 #[derive(PartialEq, Eq)]
 enum ChunkDelimiter {
@@ -74,16 +77,6 @@ fn corrupted_syntax_score(line: &str) -> AocResult<iAoc> {
     Ok(score)
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let result = input
-        .lines()
-        .map(|line| corrupted_syntax_score(line))
-        .collect::<Result<Vec<iAoc>, _>>()?
-        .iter()
-        .sum();
-    Ok(result)
-}
-
 fn is_corrupted(line: &str) -> bool {
     let mut stack = Vec::new();
     for ch in line.chars() {
@@ -126,14 +119,71 @@ fn incomplete_correction_score(line: &str) -> AocResult<iAoc> {
     })
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let mut scores = input
-        .lines()
-        .filter(|line| !is_corrupted(line))
-        .map(|line| incomplete_correction_score(line))
-        .collect::<Result<Vec<iAoc>, _>>()?;
-    scores.sort();
-    let mid = scores.len() / 2;
-    let result = scores[mid];
-    Ok(result)
+pub struct SyntaxScoring;
+
+impl Solver for SyntaxScoring {
+    type Parsed = Vec<String>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Vec<String>> {
+        // There is nothing to parse, but every character must be a delimiter.
+        for ch in input.lines().flat_map(str::chars) {
+            if ChunkDelimiter::from_begin(ch).is_none() && ChunkDelimiter::from_end(ch).is_none() {
+                return Err(AocError::new("unexpected char found"));
+            }
+        }
+        Ok(input.lines().map(str::to_owned).collect())
+    }
+
+    fn part_a(lines: &Vec<String>, context: &SolverContext) -> AocResult<SolutionValue> {
+        // Every line is scored on its own, so chunks of lines are scored in parallel.
+        let result = parallel::map_chunks(lines, context.threads(), |lines| {
+            lines
+                .iter()
+                .map(|line| corrupted_syntax_score(line))
+                .sum::<AocResult<iAoc>>()
+        })
+        .into_iter()
+        .sum::<AocResult<iAoc>>()?;
+        Ok(SolutionValue::from(result))
+    }
+
+    fn part_b(lines: &Vec<String>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let chunk_scores = parallel::map_chunks(lines, context.threads(), |lines| {
+            lines
+                .iter()
+                .filter(|line| !is_corrupted(line))
+                .map(|line| incomplete_correction_score(line))
+                .collect::<AocResult<Vec<iAoc>>>()
+        });
+        let mut scores = Vec::with_capacity(lines.len());
+        for chunk in chunk_scores {
+            scores.extend(chunk?);
+        }
+
+        // Only the middle score is needed, so there is no need to sort them all.
+        let mid = scores.len() / 2;
+        if scores.is_empty() {
+            return Err(AocError::new("no incomplete lines"));
+        }
+        let (_, result, _) = scores.select_nth_unstable(mid);
+        Ok(SolutionValue::from(*result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    const EXAMPLE: &str = include_str!("../../input/test/10.1.txt");
+
+    #[test]
+    fn part_a_example() {
+        assert_solves!(SyntaxScoring::part_a, EXAMPLE, 26397);
+    }
+
+    #[test]
+    fn part_b_example() {
+        assert_solves!(SyntaxScoring::part_b, EXAMPLE, 288957);
+    }
 }
@@ -1,112 +1,354 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::animate::Animator;
+use crate::common::grid::{Grid2D, NeighborLists};
+use crate::common::{
+    cancel, iAoc, parse_lines, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, Rng,
+    SolutionValue, Solver, SolverContext, StrategyInfo,
+};
 use std::collections::VecDeque;
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 use std::str::FromStr;
 
-struct DumboEnergyLevels {
-    map: Vec<Vec<u32>>,
-    height: usize,
-    width: usize,
+#[derive(Clone)]
+pub struct DumboEnergyLevels {
+    /// Energy level of each octopus. A level never passes 17, a full octopus
+    /// energized by all eight neighbors, so it fits in a byte.
+    grid: Grid2D<u8>,
+    /// Indices of the octopi around each octopus, if they are cached rather
+    /// than computed on every flash.
+    neighbors: Option<NeighborLists>,
+    /// Octopi waiting to flash, reused across steps.
+    to_flash: VecDeque<usize>,
+    steps: usize,
 }
 
-const NEIGHBORS: [(isize, isize); 8] = [
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, -1),
-    (0, 1),
-    (1, -1),
-    (1, 0),
-    (1, 1),
-];
-
 impl DumboEnergyLevels {
-    pub fn new(map: Vec<Vec<u32>>) -> Self {
-        let height = map.len();
-        let width = map.first().map(|row| row.len()).unwrap_or(0);
-        DumboEnergyLevels { map, height, width }
+    pub fn new(grid: Grid2D<u8>) -> Self {
+        DumboEnergyLevels {
+            neighbors: None,
+            grid,
+            to_flash: VecDeque::new(),
+            steps: 0,
+        }
+    }
+
+    /// Generates a grid of random energy levels with the size given as
+    /// `<width>x<height>`, for benchmarking part A on grids larger than the
+    /// input.
+    pub fn generate(size: &str) -> AocResult<Self> {
+        let (width, height) = size
+            .split_once('x')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .into_aoc_result_msg("generate must be a size like 100x100")?;
+        if width == 0 || height == 0 {
+            return Err(AocError::new("generate size must not be empty"));
+        }
+        let mut rng = Rng::new(11);
+        let rows = (0..height)
+            .map(|_| (0..width).map(|_| rng.below(10) as u8).collect())
+            .collect::<Vec<_>>();
+        Ok(DumboEnergyLevels::new(Grid2D::from_rows(rows)?))
+    }
+
+    /// Caches the neighbors of every octopus up front.
+    pub fn cache_neighbors(&mut self) {
+        self.neighbors = Some(self.grid.neighbor_lists());
     }
 
     pub fn size(&self) -> usize {
-        self.height * self.width
+        self.grid.len()
     }
 
     pub fn step(&mut self) -> usize {
-        let mut to_flash = VecDeque::new();
-        for (y, row) in self.map.iter_mut().enumerate() {
-            for (x, energy_level) in row.iter_mut().enumerate() {
-                *energy_level += 1;
-                if *energy_level > 9 {
-                    to_flash.push_back((x, y));
-                }
+        self.step_with(|_, _, _| ())
+    }
+
+    /// Runs a single step like `step`, calling `on_flash` with the step
+    /// number (starting at 1) and position of each octopus as it flashes.
+    pub fn step_with<F>(&mut self, mut on_flash: F) -> usize
+    where
+        F: FnMut(usize, usize, usize),
+    {
+        self.steps += 1;
+        let to_flash = &mut self.to_flash;
+        for (index, energy_level) in self.grid.cells_mut().iter_mut().enumerate() {
+            *energy_level += 1;
+            if *energy_level > 9 {
+                to_flash.push_back(index);
             }
         }
 
         let mut flashes = 0;
-        while !to_flash.is_empty() {
-            let (x, y) = to_flash.pop_front().unwrap();
-            let energy_level = &mut self.map[y][x];
+        while let Some(index) = to_flash.pop_front() {
+            let energy_level = &mut self.grid[index];
             if *energy_level > 9 {
                 flashes += 1;
                 *energy_level = 0;
-                for (dx, dy) in NEIGHBORS.iter() {
-                    let neighbor_y = y.overflowing_add(*dy as usize).0;
-                    let neighbor_x = x.overflowing_add(*dx as usize).0;
-                    self.map
-                        .get_mut(neighbor_y)
-                        .and_then(|row| row.get_mut(neighbor_x))
-                        .map(|neighbor_energy| {
-                            if *neighbor_energy != 0 {
-                                *neighbor_energy += 1;
-                                if *neighbor_energy > 9 {
-                                    to_flash.push_back((neighbor_x, neighbor_y));
-                                }
-                            }
-                        });
+                let (x, y) = self.grid.point(index);
+                on_flash(self.steps, x, y);
+                match &self.neighbors {
+                    Some(neighbors) => {
+                        for &neighbor in neighbors.get(index) {
+                            Self::energize(&mut self.grid, to_flash, neighbor);
+                        }
+                    }
+                    None => {
+                        for neighbor in self.grid.neighbors(index) {
+                            Self::energize(&mut self.grid, to_flash, neighbor);
+                        }
+                    }
                 }
             }
         }
 
         flashes
     }
+
+    /// Raises the energy level of an octopus that has not flashed yet this
+    /// step, queueing it to flash if it is full.
+    fn energize(grid: &mut Grid2D<u8>, to_flash: &mut VecDeque<usize>, index: usize) {
+        let energy_level = &mut grid[index];
+        if *energy_level != 0 {
+            *energy_level += 1;
+            if *energy_level > 9 {
+                to_flash.push_back(index);
+            }
+        }
+    }
+}
+
+impl Display for DumboEnergyLevels {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        for row in self.grid.rows() {
+            for energy_level in row {
+                // Octopi that just flashed stand out from the rest.
+                match energy_level {
+                    0 => write!(f, "#")?,
+                    _ => write!(f, "{}", energy_level)?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
 
 impl FromStr for DumboEnergyLevels {
     type Err = AocError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let map = input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|ch| ch.to_digit(10).into_aoc_result())
-                    .collect::<Result<_, _>>()
-            })
-            .collect::<Result<_, _>>()?;
-        Ok(DumboEnergyLevels::new(map))
+        let rows = parse_lines(input, |line| {
+            line.chars()
+                .map(|ch| {
+                    ch.to_digit(10)
+                        .map(|digit| digit as u8)
+                        .into_aoc_result_msg("energy level must be a digit")
+                })
+                .collect()
+        })?;
+        Ok(DumboEnergyLevels::new(Grid2D::from_rows(rows)?))
+    }
+}
+
+/// How each octopus finds its neighbors when it flashes.
+#[derive(Clone, Copy)]
+enum NeighborStrategy {
+    /// Computes the neighbors from the position of the octopus.
+    Computed,
+    /// Looks the neighbors up in lists built before the first step.
+    Cached,
+}
+
+impl FromStr for NeighborStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "computed" => Ok(Self::Computed),
+            "cached" => Ok(Self::Cached),
+            _ => Err(AocError::new("strategy must be computed or cached")),
+        }
+    }
+}
+
+/// Copies the parsed octopi to step them, caching their neighbors if the
+/// strategy calls for it.
+fn prepare_octopi(
+    octopi: &DumboEnergyLevels,
+    context: &SolverContext,
+) -> AocResult<DumboEnergyLevels> {
+    let mut octopi = octopi.clone();
+    if let NeighborStrategy::Cached = context.strategy_or(NeighborStrategy::Computed)? {
+        octopi.cache_neighbors();
+    }
+    Ok(octopi)
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "computed",
+            description: "Computes the neighbors of an octopus each time it flashes.",
+        },
+        StrategyInfo {
+            name: "cached",
+            description: "Builds the neighbor lists of every octopus before the first step.",
+        },
+    ],
+    params: &[
+        ParamInfo {
+            name: "flashes",
+            values: "true|false",
+            description: "Writes every flash as a step and position to output/11.<part>.flashes.txt.",
+            default: "false",
+        },
+        ParamInfo {
+            name: "steps",
+            values: "n",
+            description: "Number of steps to count flashes over in part A.",
+            default: "100",
+        },
+        ParamInfo {
+            name: "generate",
+            values: "<width>x<height>",
+            description: "Ignores the input and uses a grid of random energy levels of the given size, for benchmarking part A. Random octopi may never all flash at once, so part B rejects it.",
+            default: "the input",
+        },
+    ],
+    animated: true,
+};
+
+/// Steps the octopi, logging each flash if the `flashes` parameter is set.
+struct FlashLog {
+    enabled: bool,
+    text: String,
+}
+
+impl FlashLog {
+    pub fn new(context: &SolverContext) -> AocResult<Self> {
+        let enabled = match context.param("flashes") {
+            None => false,
+            Some(enabled) => enabled
+                .parse::<bool>()
+                .into_aoc_result_msg("flashes must be true or false")?,
+        };
+        Ok(FlashLog {
+            enabled,
+            text: String::new(),
+        })
+    }
+
+    pub fn step(&mut self, octopi: &mut DumboEnergyLevels) -> usize {
+        if !self.enabled {
+            return octopi.step();
+        }
+        let text = &mut self.text;
+        octopi.step_with(|step, x, y| *text += &format!("{} {} {}\n", step, x, y))
+    }
+
+    pub fn write(&self, context: &SolverContext, part: &str) -> AocResult<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let name = format!("11.{}.flashes.txt", part);
+        context.output().write(&name, self.text.as_bytes())
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let mut octopi = DumboEnergyLevels::from_str(input)?;
+pub struct DumboOctopus;
+
+impl Solver for DumboOctopus {
+    type Parsed = DumboEnergyLevels;
+
+    /// Reads the octopi from the input, or generates them if the `generate`
+    /// param is set.
+    fn parse(input: &str, context: &SolverContext) -> AocResult<DumboEnergyLevels> {
+        match context.param("generate") {
+            None => DumboEnergyLevels::from_str(input),
+            Some(size) => DumboEnergyLevels::generate(size),
+        }
+    }
+
+    fn part_a(octopi: &DumboEnergyLevels, context: &SolverContext) -> AocResult<SolutionValue> {
+        let mut octopi = prepare_octopi(octopi, context)?;
+        let steps = match context.param("steps") {
+            None => 100,
+            Some(steps) => steps
+                .parse::<usize>()
+                .into_aoc_result_msg("steps must be a non-negative integer")?,
+        };
+        let mut log = FlashLog::new(context)?;
+        let mut animator = Animator::new(context, "11.A")?;
+        animator.frame(|| octopi.to_string())?;
+
+        let mut total_flashes: iAoc = 0;
+        for _ in 0..steps {
+            total_flashes += log.step(&mut octopi) as iAoc;
+            animator.frame(|| octopi.to_string())?;
+        }
+        log.write(context, "A")?;
+        Ok(SolutionValue::from(total_flashes))
+    }
+
+    fn part_b(octopi: &DumboEnergyLevels, context: &SolverContext) -> AocResult<SolutionValue> {
+        if context.param("generate").is_some() {
+            return Err(AocError::new(
+                "generated octopi may never all flash at once, so generate only applies to part A",
+            ));
+        }
+        let mut octopi = prepare_octopi(octopi, context)?;
+        let mut log = FlashLog::new(context)?;
+        let mut animator = Animator::new(context, "11.B")?;
+        animator.frame(|| octopi.to_string())?;
+        let total = octopi.size();
 
-    let mut total_flashes: iAoc = 0;
-    for _ in 0..100 {
-        total_flashes += octopi.step() as iAoc;
+        let mut step: iAoc = 0;
+        loop {
+            cancel::check()?;
+            step += 1;
+
+            let flashes = log.step(&mut octopi);
+            animator.frame(|| octopi.to_string())?;
+            if flashes == total {
+                break;
+            }
+        }
+        log.write(context, "B")?;
+        Ok(SolutionValue::from(step))
     }
-    Ok(total_flashes)
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let mut octopi = DumboEnergyLevels::from_str(input)?;
-    let total = octopi.size();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::{self, assert_solves};
+
+    const EXAMPLE: &str = include_str!("../../input/test/11.1.txt");
 
-    let mut step: iAoc = 0;
-    loop {
-        step += 1;
+    #[test]
+    fn examples() {
+        for strategy in ["computed", "cached"] {
+            assert_solves!(DumboOctopus::part_a, EXAMPLE, 1656, strategy: strategy);
+            assert_solves!(DumboOctopus::part_b, EXAMPLE, 195, strategy: strategy);
+        }
+    }
+
+    #[test]
+    fn generates_grids_for_part_a_only() {
+        let context = testing::context(None, &[("generate", "20x10"), ("steps", "10")]);
+        let octopi = DumboOctopus::parse("", &context).unwrap();
+        assert_eq!(octopi.size(), 200);
+        assert!(DumboOctopus::part_a(&octopi, &context).is_ok());
+        let err = DumboOctopus::part_b(&octopi, &context).unwrap_err();
+        assert!(err
+            .message()
+            .starts_with("generated octopi may never all flash"));
+    }
 
-        if octopi.step() == total {
-            break;
+    #[test]
+    fn rejects_empty_generated_grids() {
+        for size in ["0x0", "0x10", "10x0"] {
+            let err = DumboEnergyLevels::generate(size).err().unwrap();
+            assert_eq!(err.message(), "generate size must not be empty");
         }
     }
-    Ok(step)
 }
@@ -1,16 +1,21 @@
-use crate::common::{iAoc, AocResult, IntoAocResult};
+use crate::common::math::OverflowPolicy;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, ParseOptions, SolutionValue,
+    Solver, SolverContext,
+};
 use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 
-struct Cave<'a> {
-    name: &'a str,
+struct Cave {
+    name: String,
     small: bool,
-    adjacent: Vec<&'a str>,
+    adjacent: Vec<String>,
 }
 
-impl<'a> Cave<'a> {
-    pub fn new(name: &'a str) -> Self {
+impl Cave {
+    pub fn new(name: &str) -> Self {
         Cave {
-            name,
+            name: name.to_owned(),
             small: name.chars().all(|ch| ch.is_ascii_lowercase()),
             adjacent: Vec::new(),
         }
@@ -29,34 +34,119 @@ impl<'a> Cave<'a> {
     }
 }
 
-struct CaveSystem<'a> {
-    caves: HashMap<&'a str, Cave<'a>>,
+/// Why a cave system has no countable set of paths.
+enum CaveError<'a> {
+    /// The `start` or `end` cave does not exist.
+    Missing(&'static str),
+    /// No path leads from `start` to `end`.
+    EndUnreachable,
+    /// Two large caves are connected, so a path can go back and forth between
+    /// them forever.
+    AdjacentLargeCaves(&'a str, &'a str),
 }
 
-impl<'a> CaveSystem<'a> {
+impl Display for CaveError<'_> {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        match self {
+            Self::Missing(name) => write!(f, "cave system has no {} cave", name),
+            Self::EndUnreachable => write!(f, "end cave cannot be reached from start"),
+            Self::AdjacentLargeCaves(a, b) => write!(
+                f,
+                "large caves {} and {} are connected, so there are infinitely many paths",
+                a, b
+            ),
+        }
+    }
+}
+
+impl From<CaveError<'_>> for AocError {
+    fn from(error: CaveError) -> Self {
+        AocError::new(error.to_string())
+    }
+}
+
+pub struct CaveSystem {
+    caves: HashMap<String, Cave>,
+}
+
+impl CaveSystem {
     pub fn new() -> Self {
         CaveSystem {
             caves: HashMap::new(),
         }
     }
 
-    pub fn from_str(input: &'a str) -> AocResult<Self> {
+    pub fn parse(input: &str, options: &ParseOptions) -> AocResult<Self> {
         let mut system = CaveSystem::new();
         let caves = &mut system.caves;
         for line in input.lines() {
             let (from, to) = line.split_once('-').into_aoc_result()?;
+            let duplicate = caves
+                .get(from)
+                .is_some_and(|cave| cave.adjacent.iter().any(|adj| adj == to));
+            if duplicate {
+                options.tolerate(format!("passage {} is listed more than once", line))?;
+                continue;
+            }
+            caves
+                .entry(from.to_owned())
+                .or_insert_with(|| Cave::new(from))
+                .adjacent
+                .push(to.to_owned());
             caves
-                .entry(from)
-                .or_insert(Cave::new(from))
+                .entry(to.to_owned())
+                .or_insert_with(|| Cave::new(to))
                 .adjacent
-                .push(to);
-            caves.entry(to).or_insert(Cave::new(to)).adjacent.push(from);
+                .push(from.to_owned());
         }
+        system.validate(options)?;
         Ok(system)
     }
 
-    fn count_paths_dfs(
-        &self,
+    /// Checks that paths from `start` to `end` exist and can be counted.
+    ///
+    /// Small caves are visited at most twice, so only connected large caves
+    /// can make the number of paths infinite. Caves that cannot be reached
+    /// from `start` do not affect the count, so they are only reported.
+    fn validate(&self, options: &ParseOptions) -> AocResult<()> {
+        for name in ["start", "end"] {
+            if !self.caves.contains_key(name) {
+                return Err(CaveError::Missing(name).into());
+            }
+        }
+
+        let mut reached = HashSet::from(["start"]);
+        let mut queue = vec!["start"];
+        while let Some(name) = queue.pop() {
+            let cave = &self.caves[name];
+            for adj in &cave.adjacent {
+                if !cave.is_small() && !self.caves[adj].is_small() {
+                    return Err(CaveError::AdjacentLargeCaves(name, adj).into());
+                }
+                if reached.insert(adj.as_str()) {
+                    queue.push(adj);
+                }
+            }
+        }
+        if !reached.contains("end") {
+            return Err(CaveError::EndUnreachable.into());
+        }
+
+        let mut isolated = self
+            .caves
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !reached.contains(name))
+            .collect::<Vec<_>>();
+        isolated.sort_unstable();
+        options.check(
+            isolated.is_empty(),
+            format!("caves {} cannot be reached from start", isolated.join(", ")),
+        )
+    }
+
+    fn count_paths_dfs<'a>(
+        &'a self,
         location: &'a str,
         visited: &mut HashSet<&'a str>,
         mut allow_extra_cave: bool,
@@ -100,14 +190,198 @@ impl<'a> CaveSystem<'a> {
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let system = CaveSystem::from_str(input)?;
-    let result = system.count_paths(false)?;
-    Ok(result)
+/// Where a path is: the cave it is entering, the small caves it has visited
+/// as bits, and whether it may still visit one small cave twice.
+type PathState<'a> = (&'a str, u64, bool);
+
+/// Counts the paths from every state to `end` by their length, without
+/// listing the paths themselves.
+///
+/// Paths from a state only depend on the state, not on how the path got
+/// there, so the counts for each state are memoized.
+struct PathLengths<'a> {
+    system: &'a CaveSystem,
+    /// Bit of each small cave in the visited set.
+    bits: HashMap<&'a str, u64>,
+    overflow: OverflowPolicy,
+    /// Number of paths from each state to `end`, indexed by the number of
+    /// passages they take.
+    memo: HashMap<PathState<'a>, Vec<u64>>,
+}
+
+impl<'a> PathLengths<'a> {
+    pub fn new(system: &'a CaveSystem, overflow: OverflowPolicy) -> AocResult<Self> {
+        let mut small = system
+            .caves
+            .values()
+            .filter(|cave| cave.is_small())
+            .map(|cave| cave.name.as_str())
+            .collect::<Vec<_>>();
+        if small.len() > u64::BITS as usize {
+            return Err(AocError::new(format!(
+                "path lengths can only be counted with at most {} small caves",
+                u64::BITS
+            )));
+        }
+        small.sort_unstable();
+        let bits = small
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name, 1 << i))
+            .collect();
+        Ok(PathLengths {
+            system,
+            bits,
+            overflow,
+            memo: HashMap::new(),
+        })
+    }
+
+    /// Enters the cave of the state, returning the visited caves and whether
+    /// a small cave may still be visited twice afterwards, or `None` if the
+    /// cave cannot be entered. Follows the same rules as `count_paths_dfs`.
+    fn enter(&self, (location, visited, allow_extra_cave): PathState<'a>) -> Option<(u64, bool)> {
+        let cave = &self.system.caves[location];
+        if !cave.is_small() {
+            return Some((visited, allow_extra_cave));
+        }
+        let bit = self.bits[location];
+        if visited & bit == 0 {
+            Some((visited | bit, allow_extra_cave))
+        } else if allow_extra_cave && !cave.is_start() {
+            Some((visited, false))
+        } else {
+            None
+        }
+    }
+
+    /// Number of paths from the state to `end`, indexed by length.
+    pub fn lengths(&mut self, state: PathState<'a>) -> AocResult<Vec<u64>> {
+        if let Some(lengths) = self.memo.get(&state) {
+            return Ok(lengths.clone());
+        }
+        let (location, ..) = state;
+        let system = self.system;
+        let cave = &system.caves[location];
+        let lengths = if cave.is_end() {
+            vec![1]
+        } else {
+            let mut lengths = Vec::new();
+            if let Some((visited, allow_extra_cave)) = self.enter(state) {
+                for adj in &cave.adjacent {
+                    let adj_lengths = self.lengths((adj.as_str(), visited, allow_extra_cave))?;
+                    if lengths.len() < adj_lengths.len() + 1 {
+                        lengths.resize(adj_lengths.len() + 1, 0);
+                    }
+                    for (length, count) in adj_lengths.into_iter().enumerate() {
+                        lengths[length + 1] = self.overflow.add(lengths[length + 1], count)?;
+                    }
+                }
+            }
+            lengths
+        };
+        self.memo.insert(state, lengths.clone());
+        Ok(lengths)
+    }
+
+    /// Finds one path of the given length from the state to `end`, picking
+    /// the first passage in the input that still leads to such a path.
+    pub fn path(&mut self, mut state: PathState<'a>, length: usize) -> AocResult<Vec<&'a str>> {
+        let mut path = vec![state.0];
+        for remaining in (0..length).rev() {
+            let (visited, allow_extra_cave) = self
+                .enter(state)
+                .into_aoc_result_msg("path enters a cave it cannot")?;
+            let mut next = None;
+            let system = self.system;
+            for adj in &system.caves[state.0].adjacent {
+                let adj_state = (adj.as_str(), visited, allow_extra_cave);
+                if self
+                    .lengths(adj_state)?
+                    .get(remaining)
+                    .is_some_and(|n| *n > 0)
+                {
+                    next = Some(adj_state);
+                    break;
+                }
+            }
+            state = next.into_aoc_result_msg("no path has the requested length")?;
+            path.push(state.0);
+        }
+        Ok(path)
+    }
+}
+
+/// Writes a histogram of path lengths and the shortest and longest paths if
+/// the `analysis` parameter is set.
+fn write_analysis(
+    context: &SolverContext,
+    part: &str,
+    system: &CaveSystem,
+    allow_extra_cave: bool,
+) -> AocResult<()> {
+    let enabled = match context.param("analysis") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("analysis must be true or false")?,
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    let mut path_lengths = PathLengths::new(system, context.overflow())?;
+    let start = ("start", 0, allow_extra_cave);
+    let lengths = path_lengths.lengths(start)?;
+    let found = |length: &usize| lengths[*length] > 0;
+    let shortest = (0..lengths.len()).find(found);
+    let longest = (0..lengths.len()).rev().find(found);
+
+    let mut text = String::new();
+    for (name, length) in [("shortest", shortest), ("longest", longest)] {
+        if let Some(length) = length {
+            let path = path_lengths.path(start, length)?;
+            text += &format!("{} {} {}\n", name, length, path.join(","));
+        }
+    }
+    text += "length paths\n";
+    for (length, count) in lengths.iter().enumerate().filter(|(_, count)| **count > 0) {
+        text += &format!("{} {}\n", length, count);
+    }
+    context
+        .output()
+        .write(&format!("12.{}.analysis.txt", part), text.as_bytes())
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let system = CaveSystem::from_str(input)?;
-    let result = system.count_paths(true)?;
-    Ok(result)
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[ParamInfo {
+        name: "analysis",
+        values: "true|false",
+        description: "Writes the shortest and longest paths and how many paths have each length, in passages, to output/12.<part>.analysis.txt.",
+        default: "false",
+    }],
+    animated: false,
+};
+
+pub struct PassagePathing;
+
+impl Solver for PassagePathing {
+    type Parsed = CaveSystem;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<CaveSystem> {
+        CaveSystem::parse(input, context.parse_options())
+    }
+
+    fn part_a(system: &CaveSystem, context: &SolverContext) -> AocResult<SolutionValue> {
+        write_analysis(context, "A", system, false)?;
+        let result = system.count_paths(false)?;
+        Ok(SolutionValue::from(result))
+    }
+
+    fn part_b(system: &CaveSystem, context: &SolverContext) -> AocResult<SolutionValue> {
+        write_analysis(context, "B", system, true)?;
+        let result = system.count_paths(true)?;
+        Ok(SolutionValue::from(result))
+    }
 }
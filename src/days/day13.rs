@@ -1,31 +1,53 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+#[cfg(feature = "visualize")]
+use crate::common::image;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, OutputSink, ParamInfo, ParseOptions, Rng,
+    SolutionValue, Solver, SolverContext, StrategyInfo, Warnings,
+};
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::Write;
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 use std::str::FromStr;
 
 type Point = (usize, usize);
 
-#[derive(Clone, Copy)]
-enum Fold {
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Fold {
     X(usize),
     Y(usize),
 }
 
+impl Fold {
+    /// Whether any point lies beyond the fold line, so that folding moves it.
+    fn moves_any(&self, points: &HashSet<Point>) -> bool {
+        match *self {
+            Fold::X(fold_x) => points.iter().any(|(x, _)| *x > fold_x),
+            Fold::Y(fold_y) => points.iter().any(|(_, y)| *y > fold_y),
+        }
+    }
+}
+
+impl Display for Fold {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        match self {
+            Fold::X(x) => write!(f, "x={}", x),
+            Fold::Y(y) => write!(f, "y={}", y),
+        }
+    }
+}
+
 enum PaperInstructionsParsingState {
     Points,
     Folds,
 }
 
-struct PaperInstructions {
+#[derive(Clone)]
+pub struct PaperInstructions {
     points: HashSet<Point>,
     fold_lines: Vec<Fold>,
 }
 
-impl FromStr for PaperInstructions {
-    type Err = AocError;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
+impl PaperInstructions {
+    pub fn parse(input: &str, options: &ParseOptions) -> AocResult<Self> {
         let mut result = PaperInstructions::new();
 
         let mut state = PaperInstructionsParsingState::Points;
@@ -37,12 +59,20 @@ impl FromStr for PaperInstructions {
                 match state {
                     PaperInstructionsParsingState::Points => {
                         let (x, y) = line.split_once(',').into_aoc_result()?;
-                        result.points.insert((
+                        let point = (
                             x.parse::<usize>().into_aoc_result()?,
                             y.parse::<usize>().into_aoc_result()?,
-                        ));
+                        );
+                        options.check(
+                            result.points.insert(point),
+                            format!("point {} is listed more than once", line),
+                        )?;
                     }
                     PaperInstructionsParsingState::Folds => {
+                        options.check(
+                            line.starts_with("fold along "),
+                            format!("unexpected fold line {}", line),
+                        )?;
                         let equals_index = line.find('=').into_aoc_result()?;
                         if equals_index == 0 {
                             return Err(AocError::new("invalid fold line"));
@@ -67,9 +97,7 @@ impl FromStr for PaperInstructions {
 
         Ok(result)
     }
-}
 
-impl PaperInstructions {
     pub fn new() -> Self {
         PaperInstructions {
             points: HashSet::new(),
@@ -98,61 +126,316 @@ impl PaperInstructions {
         }
     }
 
-    pub fn into_folded(self) -> Self {
+    /// Describes why the fold at `index` leaves the given points where they
+    /// are, or returns `None` if it moves some of them.
+    ///
+    /// Folds only ever move points toward the origin, so a fold along the same
+    /// line as an earlier fold never has any points beyond it.
+    fn redundancy(folds: &[Fold], index: usize, points: &HashSet<Point>) -> Option<String> {
+        let fold = folds[index];
+        if let Some(earlier) = folds[..index].iter().position(|f| *f == fold) {
+            Some(format!(
+                "fold {} along {} repeats fold {}",
+                index + 1,
+                fold,
+                earlier + 1
+            ))
+        } else if !fold.moves_any(points) {
+            Some(format!(
+                "fold {} along {} has no points beyond it",
+                index + 1,
+                fold
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Applies every fold in order, passing the point set after each fold to `on_fold`.
+    ///
+    /// Folds that leave the points where they are get a warning, and are
+    /// skipped entirely if `skip_redundant` is set.
+    pub fn into_folded_with<F>(
+        self,
+        skip_redundant: bool,
+        warnings: &Warnings,
+        mut on_fold: F,
+    ) -> AocResult<Self>
+    where
+        F: FnMut(&HashSet<Point>) -> AocResult<()>,
+    {
         let mut points = self.points;
-        for fold in self.fold_lines {
-            points = PaperInstructions::fold(points, fold);
+        for (i, fold) in self.fold_lines.iter().enumerate() {
+            if let Some(redundancy) = Self::redundancy(&self.fold_lines, i, &points) {
+                warnings.push(redundancy);
+                if skip_redundant {
+                    continue;
+                }
+            }
+            points = PaperInstructions::fold(points, *fold);
+            on_fold(&points)?;
         }
-        PaperInstructions {
+        Ok(PaperInstructions {
             points,
             fold_lines: Vec::new(),
+        })
+    }
+}
+
+/// Format for the frames written after each fold.
+#[derive(Clone, Copy)]
+enum FrameFormat {
+    /// Plain text, using `#` for points.
+    Text,
+    /// PBM image, viewable by most image tools.
+    #[cfg(feature = "visualize")]
+    Pbm,
+}
+
+impl FromStr for FrameFormat {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "text" => Ok(Self::Text),
+            #[cfg(feature = "visualize")]
+            "pbm" => Ok(Self::Pbm),
+            #[cfg(not(feature = "visualize"))]
+            "pbm" => Err(AocError::new(
+                "frames pbm requires the visualize feature, which this build does not have",
+            )),
+            _ => Err(AocError::new("frames must be text or pbm")),
         }
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let instr = PaperInstructions::from_str(input)?;
-    let result = PaperInstructions::fold(
-        instr.points,
-        *instr
-            .fold_lines
-            .first()
-            .into_aoc_result_msg("no first fold")?,
-    )
-    .len();
-    Ok(result as iAoc)
-}
-
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let instr = PaperInstructions::from_str(input)?;
-    let folded = instr.into_folded();
-
-    let max_x = folded
-        .points
-        .iter()
-        .map(|(x, _)| x)
-        .max()
-        .into_aoc_result()?;
-    let max_y = folded
-        .points
-        .iter()
-        .map(|(_, y)| y)
-        .max()
-        .into_aoc_result()?;
-
-    let mut grid_raw = vec![' ' as u8; (max_x + 2) * (max_y + 1)];
+/// Renders the points as text, using `#` for points and spaces elsewhere.
+fn render(points: &HashSet<Point>) -> AocResult<Vec<u8>> {
+    let max_x = points.iter().map(|(x, _)| x).max().into_aoc_result()?;
+    let max_y = points.iter().map(|(_, y)| y).max().into_aoc_result()?;
+
+    let mut grid_raw = vec![b' '; (max_x + 2) * (max_y + 1)];
     let mut grid_base: Vec<_> = grid_raw.as_mut_slice().chunks_mut(max_x + 2).collect();
     let grid = grid_base.as_mut_slice();
 
-    for (x, y) in &folded.points {
-        grid[*y][*x] = '#' as u8;
+    for (x, y) in points {
+        grid[*y][*x] = b'#';
     }
-
-    let mut output_file = File::create("output/13.B.txt").into_aoc_result()?;
     for row in grid {
-        row[max_x + 1] = '\n' as u8;
-        output_file.write_all(row).into_aoc_result()?;
+        row[max_x + 1] = b'\n';
+    }
+
+    Ok(grid_raw)
+}
+
+fn write_frame(
+    output: &OutputSink,
+    frame_number: usize,
+    points: &HashSet<Point>,
+    format: FrameFormat,
+) -> AocResult<()> {
+    match format {
+        FrameFormat::Text => output.write(
+            &format!("13.B.fold{:02}.txt", frame_number),
+            &render(points)?,
+        ),
+        #[cfg(feature = "visualize")]
+        FrameFormat::Pbm => {
+            let width = points.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+            let height = points.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+            image::write_pbm(
+                output,
+                &format!("13.B.fold{:02}.pbm", frame_number),
+                width,
+                height,
+                |x, y| points.contains(&(x, y)),
+            )
+        }
     }
+}
+
+/// Strategy used to fold the paper.
+#[derive(Clone, Copy)]
+enum FoldStrategy {
+    /// Applies the folds in order.
+    Fold,
+    /// Checks redundant fold detection on the input and on generated fold
+    /// sequences before folding.
+    CrossCheck,
+}
 
-    Ok(0 as iAoc)
+impl FromStr for FoldStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "fold" => Ok(Self::Fold),
+            "cross-check" => Ok(Self::CrossCheck),
+            _ => Err(AocError::new("strategy must be fold or cross-check")),
+        }
+    }
+}
+
+/// Generates random points and a fold sequence that includes repeated folds
+/// and folds with nothing beyond them.
+fn generate_instructions(rng: &mut Rng) -> PaperInstructions {
+    const POINTS: usize = 50;
+    const FOLDS: usize = 12;
+    let (mut width, mut height) = (1 + rng.below(64), 1 + rng.below(64));
+    let points = (0..POINTS)
+        .map(|_| (rng.below(width), rng.below(height)))
+        .collect();
+    // Lines in the far half of the paper, so no point is folded past the
+    // other edge.
+    let line = |rng: &mut Rng, size: usize| size / 2 + rng.below(size - size / 2);
+    let mut fold_lines: Vec<Fold> = Vec::new();
+    for _ in 0..FOLDS {
+        let fold = match rng.below(4) {
+            0 if !fold_lines.is_empty() => fold_lines[rng.below(fold_lines.len())],
+            // A line past the edge of the paper has nothing beyond it.
+            1 => Fold::X(width + rng.below(8)),
+            2 => Fold::X(line(rng, width)),
+            _ => Fold::Y(line(rng, height)),
+        };
+        // Points on a fold line stay where they are, so the paper still
+        // reaches the line.
+        match fold {
+            Fold::X(x) => width = width.min(x + 1),
+            Fold::Y(y) => height = height.min(y + 1),
+        }
+        fold_lines.push(fold);
+    }
+    PaperInstructions { points, fold_lines }
+}
+
+/// Checks that every fold reported as redundant leaves the points unchanged,
+/// and that skipping those folds gives the same result.
+fn check_redundant_folds(instr: &PaperInstructions) -> AocResult<()> {
+    let mut points = instr.points.clone();
+    for (i, fold) in instr.fold_lines.iter().enumerate() {
+        let redundancy = PaperInstructions::redundancy(&instr.fold_lines, i, &points);
+        let folded = PaperInstructions::fold(points.clone(), *fold);
+        if let Some(redundancy) = redundancy {
+            if folded != points {
+                return Err(AocError::new(format!(
+                    "{}, but folding moved points",
+                    redundancy
+                )));
+            }
+        }
+        points = folded;
+    }
+
+    let skipped = PaperInstructions {
+        points: instr.points.clone(),
+        fold_lines: instr.fold_lines.clone(),
+    }
+    .into_folded_with(true, &Warnings::default(), |_| Ok(()))?;
+    if skipped.points != points {
+        return Err(AocError::new(
+            "skipping redundant folds changed the folded points",
+        ));
+    }
+    Ok(())
+}
+
+fn cross_check(instr: &PaperInstructions) -> AocResult<()> {
+    const SEQUENCES: usize = 200;
+    check_redundant_folds(instr)?;
+    let mut rng = Rng::new(13);
+    for _ in 0..SEQUENCES {
+        check_redundant_folds(&generate_instructions(&mut rng))?;
+    }
+    Ok(())
+}
+
+/// Whether redundant folds are skipped, from the `skip-redundant` parameter.
+fn skip_redundant(context: &SolverContext) -> AocResult<bool> {
+    match context.param("skip-redundant") {
+        None => Ok(false),
+        Some(skip) => skip
+            .parse::<bool>()
+            .into_aoc_result_msg("skip-redundant must be true or false"),
+    }
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "fold",
+            description: "Applies the folds in order.",
+        },
+        StrategyInfo {
+            name: "cross-check",
+            description: "Checks redundant fold detection on the input and on generated fold sequences before folding.",
+        },
+    ],
+    params: &[
+        ParamInfo {
+            name: "frames",
+            values: "text|pbm",
+            description: "Writes the paper after each fold in part B to output/13.B.foldNN.<format>.",
+            default: "no frames",
+        },
+        ParamInfo {
+            name: "skip-redundant",
+            values: "true|false",
+            description: "Skips folds that repeat an earlier fold or have no points beyond them, which are always reported as warnings.",
+            default: "false",
+        },
+    ],
+    animated: false,
+};
+
+pub struct TransparentOrigami;
+
+impl Solver for TransparentOrigami {
+    type Parsed = PaperInstructions;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<PaperInstructions> {
+        PaperInstructions::parse(input, context.parse_options())
+    }
+
+    fn part_a(instr: &PaperInstructions, context: &SolverContext) -> AocResult<SolutionValue> {
+        if let FoldStrategy::CrossCheck = context.strategy_or(FoldStrategy::Fold)? {
+            cross_check(instr)?;
+        }
+        let first = *instr
+            .fold_lines
+            .first()
+            .into_aoc_result_msg("no first fold")?;
+        if let Some(redundancy) = PaperInstructions::redundancy(&instr.fold_lines, 0, &instr.points)
+        {
+            context.warnings().push(redundancy);
+        }
+        let result = PaperInstructions::fold(instr.points.clone(), first).len();
+        Ok(SolutionValue::from(result as iAoc))
+    }
+
+    fn part_b(instr: &PaperInstructions, context: &SolverContext) -> AocResult<SolutionValue> {
+        if let FoldStrategy::CrossCheck = context.strategy_or(FoldStrategy::Fold)? {
+            cross_check(instr)?;
+        }
+        let instr = instr.clone();
+        let skip_redundant = skip_redundant(context)?;
+        let warnings = context.warnings();
+        let folded = match context.param("frames") {
+            None => instr.into_folded_with(skip_redundant, warnings, |_| Ok(()))?,
+            Some(format) => {
+                // Write one frame per fold so the folding process can be animated.
+                let format = FrameFormat::from_str(format)?;
+                let mut frame_number = 0;
+                instr.into_folded_with(skip_redundant, warnings, |points| {
+                    frame_number += 1;
+                    write_frame(context.output(), frame_number, points, format)
+                })?
+            }
+        };
+
+        context
+            .output()
+            .write("13.B.txt", &render(&folded.points)?)?;
+
+        Ok(SolutionValue::from(0))
+    }
 }
@@ -1,38 +1,51 @@
-use crate::common::{iAoc, AocResult, IntoAocResult};
+use crate::common::iter::str_windows;
+use crate::common::math::OverflowPolicy;
+use crate::common::{
+    iAoc, AocResult, DayInfo, IntoAocResult, ParamInfo, ParseOptions, SolutionValue, Solver,
+    SolverContext,
+};
 use std::collections::HashMap;
 
-struct PolymerData {
-    template: String,
-    insertion_rules: HashMap<(char, char), char>,
+/// How a pair with no insertion rule showed up during the simulation, where
+/// it passes through a step unchanged.
+pub struct UncoveredPair {
+    /// First step the pair was present at, where step 0 is the template.
+    first_step: usize,
+    /// Number of steps the pair was present at.
+    steps: usize,
+    /// Number of times the pair occurred, summed over those steps.
+    occurrences: usize,
 }
 
-/// Creates an iterator over slices of the source string in overlapping windows
-/// of a dedicated size.
-fn char_windows<'a>(src: &'a str, size: usize) -> impl Iterator<Item = &'a str> {
-    src.char_indices().flat_map(move |(from, _)| {
-        src[from..]
-            .char_indices()
-            .skip(size - 1)
-            .next()
-            .map(|(to, c)| &src[from..(from + to + c.len_utf8())])
-    })
+type Coverage = HashMap<(char, char), UncoveredPair>;
+
+pub struct PolymerData {
+    template: String,
+    insertion_rules: HashMap<(char, char), char>,
 }
 
 impl PolymerData {
-    pub fn from_str(input: &str) -> AocResult<PolymerData> {
+    pub fn parse(input: &str, options: &ParseOptions) -> AocResult<PolymerData> {
         let mut lines = input.lines();
         let template = lines.next().into_aoc_result()?.to_owned();
         let mut insertion_rules = HashMap::new();
         for line in lines.skip(1) {
             let (existing, between) = line.split_once(" -> ").into_aoc_result()?;
             let mut chars = existing.chars();
-            insertion_rules.insert(
-                (
-                    chars.next().into_aoc_result()?,
-                    chars.next().into_aoc_result()?,
-                ),
-                between.chars().next().into_aoc_result()?,
+            let pair = (
+                chars.next().into_aoc_result()?,
+                chars.next().into_aoc_result()?,
             );
+            options.expect_end(chars, "insertion rule pair")?;
+            let mut chars = between.chars();
+            let element = chars.next().into_aoc_result()?;
+            options.expect_end(chars, "inserted element")?;
+            if let Some(previous) = insertion_rules.insert(pair, element) {
+                options.check(
+                    previous == element,
+                    format!("conflicting insertion rules for {}", existing),
+                )?;
+            }
         }
         Ok(PolymerData {
             template,
@@ -40,12 +53,20 @@ impl PolymerData {
         })
     }
 
-    pub fn transform(&self, steps: usize) -> AocResult<HashMap<char, usize>> {
+    /// Applies the insertion rules for the given number of steps and counts
+    /// the elements in the result, recording every pair that has no rule in
+    /// `coverage`.
+    pub fn transform(
+        &self,
+        steps: usize,
+        overflow: OverflowPolicy,
+        coverage: &mut Coverage,
+    ) -> AocResult<HashMap<char, usize>> {
         // Maps a pair to the number of times it occurs.
         let mut pair_occurrences: HashMap<(char, char), usize> = HashMap::new();
 
         // Load all initial pairs into the map.
-        for pair in char_windows(&self.template, 2) {
+        for pair in str_windows(&self.template, 2) {
             let mut chars = pair.chars();
             *pair_occurrences
                 .entry((
@@ -55,16 +76,31 @@ impl PolymerData {
                 .or_insert(0) += 1;
         }
 
-        for _ in 0..steps {
+        for step in 0..steps {
             // Build the next map of pair occurrences using the previous map.
             let mut next_pair_occurrences = HashMap::new();
 
+            let mut add_occurrences = |pair, count| -> AocResult<()> {
+                let occurrences = next_pair_occurrences.entry(pair).or_insert(0);
+                *occurrences = overflow.add(*occurrences, count)?;
+                Ok(())
+            };
+
             for (pair, count) in pair_occurrences {
                 match self.insertion_rules.get(&pair) {
-                    None => *next_pair_occurrences.entry(pair).or_insert(0) += count,
+                    None => {
+                        let uncovered = coverage.entry(pair).or_insert(UncoveredPair {
+                            first_step: step,
+                            steps: 0,
+                            occurrences: 0,
+                        });
+                        uncovered.steps += 1;
+                        uncovered.occurrences = overflow.add(uncovered.occurrences, count)?;
+                        add_occurrences(pair, count)?
+                    }
                     Some(insert) => {
-                        *next_pair_occurrences.entry((pair.0, *insert)).or_insert(0) += count;
-                        *next_pair_occurrences.entry((*insert, pair.1)).or_insert(0) += count;
+                        add_occurrences((pair.0, *insert), count)?;
+                        add_occurrences((*insert, pair.1), count)?;
                     }
                 }
             }
@@ -81,21 +117,68 @@ impl PolymerData {
         // the count for the last character in the original string.
         let mut occurrences = HashMap::new();
         for ((first, _), count) in pair_occurrences {
-            *occurrences.entry(first).or_insert(0) += count;
+            let occurrences = occurrences.entry(first).or_insert(0);
+            *occurrences = overflow.add(*occurrences, count)?;
         }
 
-        *occurrences
+        let last = occurrences
             .entry(self.template.chars().last().into_aoc_result()?)
-            .or_insert(0) += 1;
+            .or_insert(0);
+        *last = overflow.add(*last, 1)?;
 
         Ok(occurrences)
     }
 }
 
-fn solve(input: &str, steps: usize) -> AocResult<iAoc> {
-    let data = PolymerData::from_str(input)?;
+/// Writes the pairs that had no insertion rule if the `coverage` parameter is
+/// set, since a rule list cut short when editing an input passes those pairs
+/// through silently.
+fn write_coverage(context: &SolverContext, part: &str, coverage: &Coverage) -> AocResult<()> {
+    let enabled = match context.param("coverage") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("coverage must be true or false")?,
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    let mut pairs = coverage.iter().collect::<Vec<_>>();
+    pairs.sort_by_key(|(pair, uncovered)| (uncovered.first_step, **pair));
+    let mut text = String::from("pair first_step steps occurrences\n");
+    for ((left, right), uncovered) in pairs {
+        text += &format!(
+            "{}{} {} {} {}\n",
+            left, right, uncovered.first_step, uncovered.steps, uncovered.occurrences
+        );
+    }
+    if !coverage.is_empty() {
+        context
+            .warnings()
+            .push(format!("{} pairs have no insertion rule", coverage.len()));
+    }
+    context
+        .output()
+        .write(&format!("14.{}.coverage.txt", part), text.as_bytes())
+}
 
-    let occurrences = data.transform(steps)?;
+fn solve(
+    data: &PolymerData,
+    context: &SolverContext,
+    part: &str,
+    default_steps: usize,
+) -> AocResult<SolutionValue> {
+    // The number of steps can be overridden with the `steps` parameter.
+    let steps = match context.param("steps") {
+        None => default_steps,
+        Some(steps) => steps
+            .parse::<usize>()
+            .into_aoc_result_msg("steps must be a non-negative integer")?,
+    };
+    let mut coverage = Coverage::new();
+    let occurrences = data.transform(steps, context.overflow(), &mut coverage)?;
+    write_coverage(context, part, &coverage)?;
 
     let (_, max_count) = occurrences
         .iter()
@@ -107,13 +190,42 @@ fn solve(input: &str, steps: usize) -> AocResult<iAoc> {
         .into_aoc_result()?;
 
     let result = max_count - min_count;
-    Ok(result as iAoc)
+    Ok(SolutionValue::from(result as iAoc))
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    solve(input, 10)
-}
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[
+        ParamInfo {
+            name: "steps",
+            values: "n",
+            description: "Number of insertion steps to apply.",
+            default: "10 for part A, 40 for part B",
+        },
+        ParamInfo {
+            name: "coverage",
+            values: "true|false",
+            description: "Writes each pair with no insertion rule, the step it first appears at, and how often it occurs to output/14.<part>.coverage.txt.",
+            default: "false",
+        },
+    ],
+    animated: false,
+};
+
+pub struct ExtendedPolymerization;
+
+impl Solver for ExtendedPolymerization {
+    type Parsed = PolymerData;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<PolymerData> {
+        PolymerData::parse(input, context.parse_options())
+    }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    solve(input, 40)
+    fn part_a(data: &PolymerData, context: &SolverContext) -> AocResult<SolutionValue> {
+        solve(data, context, "A", 10)
+    }
+
+    fn part_b(data: &PolymerData, context: &SolverContext) -> AocResult<SolutionValue> {
+        solve(data, context, "B", 40)
+    }
 }
@@ -1,4 +1,8 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::search::{self, check_admissible};
+use crate::common::{cancel, math, SolutionValue, Solver};
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, Rng, SolverContext, StrategyInfo,
+};
 use num::Integer;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
@@ -9,31 +13,74 @@ type Point = (usize, usize);
 const NEIGHBORS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
 
 fn manhatten_distance((x1, y1): &Point, (x2, y2): &Point) -> usize {
-    let dist_x = if x1 < x2 { x2 - x1 } else { x1 - x2 };
-    let dist_y = if y1 < y2 { y2 - y1 } else { y1 - y2 };
+    let dist_x = x2.abs_diff(*x1);
+    let dist_y = y2.abs_diff(*y1);
     dist_x + dist_y
 }
 
-struct Cavern {
+#[derive(Clone)]
+pub struct Cavern {
     flat_grid: Vec<u32>,
     height: usize,
     width: usize,
+    // Number of times the grid is tiled in each direction.
+    tiles: usize,
+}
+
+/// Parses a row of single-digit risk levels, such as `1163751742`.
+fn parse_digit_row(line: &str) -> AocResult<Vec<u32>> {
+    line.chars()
+        .map(|ch| {
+            ch.to_digit(10)
+                .ok_or_else(|| AocError::new(format!("invalid risk level '{}'", ch)))
+        })
+        .collect()
+}
+
+/// Parses a row of comma-separated risk levels, such as `1,16,0,37`.
+fn parse_csv_row(line: &str) -> AocResult<Vec<u32>> {
+    line.split(',')
+        .map(|value| {
+            value
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| AocError::new(format!("invalid risk level '{}'", value.trim())))
+        })
+        .collect()
 }
 
 impl FromStr for Cavern {
     type Err = AocError;
 
+    /// Parses the cavern from rows of single digits, or from rows of
+    /// comma-separated numbers if the input contains any commas. The second
+    /// format allows risk levels above 9.
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let height = input.lines().count();
-        let width = input.lines().next().into_aoc_result_msg("no rows")?.len();
-        let flat_grid = input
+        let parse_row = if input.contains(',') {
+            parse_csv_row
+        } else {
+            parse_digit_row
+        };
+        let rows = input
             .lines()
-            .flat_map(|line| line.chars().map(|ch| ch.to_digit(10).into_aoc_result()))
-            .collect::<Result<_, _>>()?;
+            .map(parse_row)
+            .collect::<AocResult<Vec<_>>>()?;
+        let height = rows.len();
+        let width = rows.first().into_aoc_result_msg("no rows")?.len();
+        if let Some((y, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(AocError::new(format!(
+                "row {} has {} risk levels, but the first row has {}",
+                y + 1,
+                row.len(),
+                width
+            )));
+        }
+        let flat_grid = rows.into_iter().flatten().collect();
         Ok(Cavern {
             flat_grid,
             height,
             width,
+            tiles: 1,
         })
     }
 }
@@ -63,33 +110,153 @@ impl Cavern {
     pub fn get(&self, (x, y): &Point) -> Option<u32> {
         let (cluster_y, base_y) = y.div_mod_floor(&self.height);
         let (cluster_x, base_x) = x.div_mod_floor(&self.width);
-        if cluster_y > 5 || cluster_x > 5 {
+        if cluster_y >= self.tiles || cluster_x >= self.tiles {
             None
         } else {
             match self.width.overflowing_mul(base_y) {
                 (_, true) => None,
                 (offset, false) => match offset.overflowing_add(base_x) {
                     (_, true) => None,
-                    (index, false) => self.flat_grid.get(index).copied().and_then(|value| {
-                        Some((value as usize + cluster_y + cluster_x - 1).mod_floor(&9) as u32 + 1)
+                    (index, false) => self.flat_grid.get(index).copied().map(|value| {
+                        match cluster_y + cluster_x {
+                            0 => value,
+                            // Only risk levels from 1 to 9 are tiled, which `tiled` checks.
+                            clusters => math::modadd(value as usize - 1, clusters, 9) as u32 + 1,
+                        }
                     }),
                 },
             }
         }
     }
 
+    /// Tiles the cavern `tiles` times in each direction, computing the wrapped
+    /// risk level on every lookup.
+    ///
+    /// Risk levels wrap around from 9 back to 1, so every risk level must be
+    /// in that range.
+    pub fn tiled(self, tiles: usize) -> AocResult<Self> {
+        if let Some(value) = self
+            .flat_grid
+            .iter()
+            .find(|value| !(1..=9).contains(*value))
+        {
+            return Err(AocError::new(format!(
+                "cannot tile a cavern with risk level {}, which must be from 1 to 9",
+                value
+            )));
+        }
+        Ok(Cavern { tiles, ..self })
+    }
+
+    /// Width and height of the cavern, including every tile.
+    fn size(&self) -> Point {
+        (self.tiles * self.width, self.tiles * self.height)
+    }
+
+    /// Lowest risk level of any point in the cavern.
+    fn min_risk(&self) -> usize {
+        if self.tiles > 1 {
+            // Tiling wraps some risk levels around to 1.
+            1
+        } else {
+            self.flat_grid.iter().min().copied().unwrap_or_default() as usize
+        }
+    }
+
+    /// Lower bound on the risk of any path from the given point to the end.
+    ///
+    /// Every step costs at least the lowest risk level, which is 0 for caverns
+    /// that have points with no risk at all.
+    fn heuristic(&self, point: &Point, end: &Point) -> usize {
+        manhatten_distance(point, end) * self.min_risk()
+    }
+
+    /// Materializes every tile of the cavern up front, so that lookups no
+    /// longer need to compute the wrapped risk level.
+    pub fn expanded(&self) -> Self {
+        let width = self.width * self.tiles;
+        let height = self.height * self.tiles;
+        let flat_grid = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|point| self.get(&point).unwrap_or_default())
+            .collect();
+        Cavern {
+            flat_grid,
+            height,
+            width,
+            tiles: 1,
+        }
+    }
+
+    /// Iterator over the points adjacent to the given point that are inside the cavern.
+    fn neighbors<'a>(&'a self, (x, y): Point) -> impl Iterator<Item = Point> + 'a {
+        NEIGHBORS
+            .iter()
+            .map(move |(dx, dy)| {
+                (
+                    x.overflowing_add(*dx as usize).0,
+                    y.overflowing_add(*dy as usize).0,
+                )
+            })
+            .filter(move |neighbor| self.get(neighbor).is_some())
+    }
+
     /// Finds the safest path using the A* algorithm.
     pub fn safest_path(&self, start: Point, end: Point) -> AocResult<usize> {
         // Heuristic function uses the distance between the current point and end point.
-        let h = |point: &Point| manhatten_distance(point, &end);
+        let min_risk = self.min_risk();
+        self.search(start, end, |point| {
+            manhatten_distance(point, &end) * min_risk
+        })
+    }
 
+    /// Finds the lowest total risk of reaching each point from the nearest of
+    /// `sources` with Dijkstra's algorithm, stopping at the first point in
+    /// `targets` that is reached if any are given.
+    pub fn lowest_risks(
+        &self,
+        sources: &[Point],
+        targets: Option<&[Point]>,
+    ) -> AocResult<search::LowestCosts<Point>> {
+        search::lowest_costs(
+            sources.iter().copied(),
+            |point| {
+                self.neighbors(*point).map(move |neighbor| {
+                    (neighbor, self.get(&neighbor).unwrap_or_default() as usize)
+                })
+            },
+            |point| targets.is_some_and(|targets| targets.contains(point)),
+        )
+    }
+
+    /// Checks that the heuristic used by `safest_path` never overestimates the
+    /// remaining risk, using points sampled around the start.
+    pub fn check_heuristic(&self, start: Point, end: Point) -> AocResult<usize> {
+        const SAMPLES: usize = 8;
+        const MAX_WALK: usize = 1000;
+        check_admissible(
+            &start,
+            SAMPLES,
+            MAX_WALK,
+            &mut Rng::new(15),
+            |point| self.neighbors(*point).collect(),
+            |point| self.heuristic(point, &end),
+            |point| self.search(*point, end, |_| 0).ok(),
+        )
+    }
+
+    /// Implements the A* algorithm using the given heuristic.
+    fn search<H>(&self, start: Point, end: Point, h: H) -> AocResult<usize>
+    where
+        H: Fn(&Point) -> usize,
+    {
         let start_f_score = h(&start);
 
         let mut f_scores = HashMap::new();
         f_scores.insert(start, start_f_score);
 
         let mut g_scores = HashMap::new();
-        g_scores.insert(start, 0);
+        g_scores.insert(start, 0usize);
 
         let mut open_set = BinaryHeap::new();
         open_set.push(PathState {
@@ -102,6 +269,8 @@ impl Cavern {
             cost: f_score,
         }) = open_set.pop()
         {
+            cancel::check()?;
+
             // We have reached our destination.
             if position == end {
                 return Ok(f_score);
@@ -114,16 +283,16 @@ impl Cavern {
 
             let g_score = g_scores.get(&position).copied().unwrap();
 
-            for (dx, dy) in NEIGHBORS {
-                let neighbor = (
-                    position.0.overflowing_add(dx as usize).0,
-                    position.1.overflowing_add(dy as usize).0,
-                );
+            for neighbor in self.neighbors(position) {
                 if let Some(neighbor_cost) = self.get(&neighbor) {
-                    let tentative_g_score = g_score + neighbor_cost;
-                    let neighbor_g_score = g_scores.entry(neighbor).or_insert(u32::MAX);
+                    let tentative_g_score = g_score
+                        .checked_add(neighbor_cost as usize)
+                        .into_aoc_result_msg("total risk overflowed")?;
+                    let neighbor_g_score = g_scores.entry(neighbor).or_insert(usize::MAX);
                     if tentative_g_score < *neighbor_g_score {
-                        let new_f_score = tentative_g_score as usize + h(&neighbor);
+                        let new_f_score = tentative_g_score
+                            .checked_add(h(&neighbor))
+                            .into_aoc_result_msg("total risk overflowed")?;
                         *f_scores.entry(neighbor).or_default() = new_f_score;
                         *neighbor_g_score = tentative_g_score;
                         open_set.push(PathState {
@@ -139,14 +308,359 @@ impl Cavern {
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let cavern = Cavern::from_str(input)?;
-    let result = cavern.safest_path((0, 0), (cavern.width - 1, cavern.height - 1))?;
-    Ok(result as iAoc)
+/// Strategy used to find the safest path.
+#[derive(Clone, Copy)]
+enum SearchStrategy {
+    /// A* search, guided by the Manhattan distance to the end.
+    AStar,
+    /// Verifies the A* heuristic on sampled points before searching.
+    CheckHeuristic,
+    /// A* search over a grid with every tile precomputed up front.
+    Expanded,
+}
+
+impl FromStr for SearchStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "astar" => Ok(Self::AStar),
+            "check-heuristic" => Ok(Self::CheckHeuristic),
+            "expanded" => Ok(Self::Expanded),
+            _ => Err(AocError::new(
+                "strategy must be astar, check-heuristic, or expanded",
+            )),
+        }
+    }
+}
+
+/// Parses points given to the `from` or `to` parameter, such as `0,0;9,9`,
+/// checking that each one is inside the cavern.
+fn parse_points(name: &str, input: &str, cavern: &Cavern) -> AocResult<Vec<Point>> {
+    let (width, height) = cavern.size();
+    input
+        .split(';')
+        .map(|point| {
+            let (x, y) = point
+                .split_once(',')
+                .into_aoc_result_msg(&format!("{} must be points such as 0,0;9,9", name))?;
+            let x = x
+                .trim()
+                .parse::<usize>()
+                .into_aoc_result_msg(&format!("invalid x coordinate '{}' in {}", x, name))?;
+            let y = y
+                .trim()
+                .parse::<usize>()
+                .into_aoc_result_msg(&format!("invalid y coordinate '{}' in {}", y, name))?;
+            if x >= width || y >= height {
+                return Err(AocError::new(format!(
+                    "{} point {},{} is outside the {}x{} cavern",
+                    name, x, y, width, height
+                )));
+            }
+            Ok((x, y))
+        })
+        .collect()
+}
+
+/// Writes the lowest risk of reaching every point from the sources if the
+/// `risks` parameter is set, as rows of tab-separated risks.
+fn write_risks(
+    context: &SolverContext,
+    part: &str,
+    cavern: &Cavern,
+    risks: &HashMap<Point, usize>,
+) -> AocResult<()> {
+    let (width, height) = cavern.size();
+    let mut text = String::new();
+    for y in 0..height {
+        let row = (0..width)
+            .map(|x| {
+                risks
+                    .get(&(x, y))
+                    .map(ToString::to_string)
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        text += &row.join("\t");
+        text += "\n";
+    }
+    context
+        .output()
+        .write(&format!("15.{}.risks.tsv", part), text.as_bytes())
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let cavern = Cavern::from_str(input)?;
-    let result = cavern.safest_path((0, 0), (5 * cavern.width - 1, 5 * cavern.height - 1))?;
+fn solve(
+    context: &SolverContext,
+    part: &str,
+    cavern: &Cavern,
+    strategy: SearchStrategy,
+) -> AocResult<iAoc> {
+    let write = match context.param("risks") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("risks must be true or false")?,
+    };
+    let from = context
+        .param("from")
+        .map(|from| parse_points("from", from, cavern))
+        .transpose()?;
+    let to = context
+        .param("to")
+        .map(|to| parse_points("to", to, cavern))
+        .transpose()?;
+
+    let (width, height) = cavern.size();
+    let start = (0, 0);
+    let end = (width - 1, height - 1);
+    if !write && from.is_none() && to.is_none() {
+        let result = match strategy {
+            SearchStrategy::AStar => cavern.safest_path(start, end)?,
+            SearchStrategy::CheckHeuristic => {
+                cavern.check_heuristic(start, end)?;
+                cavern.safest_path(start, end)?
+            }
+            SearchStrategy::Expanded => cavern.expanded().safest_path(start, end)?,
+        };
+        return Ok(result as iAoc);
+    }
+
+    // Queries other than the puzzle's own use Dijkstra's algorithm, since
+    // the A* heuristic only guides the search toward a single end.
+    let sources = from.unwrap_or_else(|| vec![start]);
+    let targets = to.unwrap_or_else(|| vec![end]);
+    let result = if write {
+        let risks = cavern.lowest_risks(&sources, None)?.costs;
+        write_risks(context, part, cavern, &risks)?;
+        targets
+            .iter()
+            .filter_map(|target| risks.get(target))
+            .min()
+            .copied()
+    } else {
+        cavern
+            .lowest_risks(&sources, Some(&targets))?
+            .goal
+            .map(|(_, risk)| risk)
+    };
+    let result = result.into_aoc_result_msg("no path found")?;
     Ok(result as iAoc)
 }
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "astar",
+            description: "A* search, guided by the Manhattan distance to the end.",
+        },
+        StrategyInfo {
+            name: "check-heuristic",
+            description: "Verifies the A* heuristic on sampled points before searching.",
+        },
+        StrategyInfo {
+            name: "expanded",
+            description: "A* search over a grid with every tile precomputed up front.",
+        },
+    ],
+    params: &[
+        ParamInfo {
+            name: "from",
+            values: "x,y;...",
+            description: "Points to start from, taking the lowest risk from whichever is nearest.",
+            default: "0,0",
+        },
+        ParamInfo {
+            name: "to",
+            values: "x,y;...",
+            description: "Points to end at, taking the lowest risk to whichever is nearest.",
+            default: "the bottom right corner",
+        },
+        ParamInfo {
+            name: "risks",
+            values: "true|false",
+            description: "Writes the lowest risk of reaching every point from the start points, as rows of tab-separated risks, to output/15.<part>.risks.tsv.",
+            default: "false",
+        },
+    ],
+    animated: false,
+};
+
+pub struct Chiton;
+
+impl Solver for Chiton {
+    type Parsed = Cavern;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Cavern> {
+        Cavern::from_str(input)
+    }
+
+    fn part_a(cavern: &Cavern, context: &SolverContext) -> AocResult<SolutionValue> {
+        let strategy = context.strategy_or(SearchStrategy::AStar)?;
+        solve(context, "A", cavern, strategy).map(SolutionValue::from)
+    }
+
+    fn part_b(cavern: &Cavern, context: &SolverContext) -> AocResult<SolutionValue> {
+        let strategy = context.strategy_or(SearchStrategy::AStar)?;
+        let cavern = cavern.clone().tiled(5)?;
+        solve(context, "B", &cavern, strategy).map(SolutionValue::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::math::OverflowPolicy;
+    use crate::common::testing::assert_solves;
+    use crate::common::OutputSink;
+    use std::sync::Arc;
+
+    const EXAMPLE: &str = include_str!("../../input/test/15.1.txt");
+
+    /// Checks the heuristic against Dijkstra's algorithm on points reached by
+    /// random walks from the top left, returning how many points were checked.
+    fn check_random_walks<H>(cavern: &Cavern, heuristic: H) -> AocResult<usize>
+    where
+        H: Fn(&Point, &Point) -> usize,
+    {
+        let (width, height) = cavern.size();
+        let end = (width - 1, height - 1);
+        check_admissible(
+            &(0, 0),
+            50,
+            200,
+            &mut Rng::new(15),
+            |point| cavern.neighbors(*point).collect(),
+            |point| heuristic(point, &end),
+            |point| cavern.search(*point, end, |_| 0).ok(),
+        )
+    }
+
+    #[test]
+    fn examples() {
+        assert_solves!(Chiton::part_a, EXAMPLE, 40);
+        assert_solves!(Chiton::part_b, EXAMPLE, 315);
+    }
+
+    #[test]
+    fn reads_comma_separated_risk_levels() {
+        let csv = include_str!("../../input/test/15.2.txt");
+        assert!(csv.contains(','));
+        assert_solves!(Chiton::part_a, csv, 40);
+        assert_solves!(Chiton::part_b, csv, 315);
+    }
+
+    #[test]
+    fn allows_no_risk_and_risks_above_9() {
+        let cavern = include_str!("../../input/test/15.3.txt");
+        for strategy in ["astar", "check-heuristic", "expanded"] {
+            assert_solves!(Chiton::part_a, cavern, 24, strategy: strategy);
+        }
+        let err = Chiton::part_b(
+            &Cavern::from_str(cavern).unwrap(),
+            &SolverContext::default(),
+        )
+        .unwrap_err();
+        assert!(err
+            .message()
+            .starts_with("cannot tile a cavern with risk level 0"));
+    }
+
+    #[test]
+    fn total_risk_can_exceed_u32() {
+        let cavern = "1,3000000000\n3000000000,3000000000";
+        assert_solves!(Chiton::part_a, cavern, 6000000000i64);
+        assert_solves!(Chiton::part_a, cavern, 6000000000i64, params: [("to", "1,1")]);
+    }
+
+    #[test]
+    fn total_risk_overflow_is_an_error() {
+        let cavern = Cavern::from_str("0,1\n1,1").unwrap();
+        let err = cavern.search((0, 0), (1, 1), |_| usize::MAX).unwrap_err();
+        assert_eq!(err.message(), "total risk overflowed");
+    }
+
+    #[test]
+    fn custom_sources_and_targets_on_the_example() {
+        assert_solves!(Chiton::part_a, EXAMPLE, 36, params: [("to", "9,0")]);
+        assert_solves!(
+            Chiton::part_a,
+            EXAMPLE,
+            40,
+            params: [("from", "9,9"), ("to", "0,0")]
+        );
+        assert_solves!(
+            Chiton::part_a,
+            EXAMPLE,
+            21,
+            params: [("from", "0,0;9,0"), ("to", "0,9;9,9")]
+        );
+        assert_solves!(Chiton::part_b, EXAMPLE, 315, params: [("to", "49,49")]);
+
+        let cavern = Cavern::from_str(EXAMPLE).unwrap();
+        let err = parse_points("to", "10,0", &cavern).unwrap_err();
+        assert_eq!(err.message(), "to point 10,0 is outside the 10x10 cavern");
+    }
+
+    #[test]
+    fn lowest_risks_agree_with_search_at_every_point() {
+        let cavern = Cavern::from_str(EXAMPLE).unwrap();
+        let risks = cavern.lowest_risks(&[(0, 0)], None).unwrap();
+        assert!(risks.goal.is_none());
+        assert_eq!(risks.costs.len(), 100);
+        for (point, risk) in &risks.costs {
+            assert_eq!(Some(*risk), cavern.search((0, 0), *point, |_| 0).ok());
+        }
+
+        let risks = cavern.lowest_risks(&[(0, 0)], Some(&[(9, 9)])).unwrap();
+        assert_eq!(risks.goal, Some(((9, 9), 40)));
+    }
+
+    #[test]
+    fn writes_the_risk_map() {
+        let directory =
+            std::env::temp_dir().join(format!("aoc-day15-risks-{}", std::process::id()));
+        let context = SolverContext::new(
+            None,
+            HashMap::from([("risks".to_string(), "true".to_string())]),
+            1,
+            OutputSink::new(&directory),
+            OverflowPolicy::BigInt,
+            false,
+            Arc::default(),
+        );
+        let cavern = Cavern::from_str(EXAMPLE).unwrap();
+        let answer = Chiton::part_a(&cavern, &context).unwrap();
+        assert_eq!(answer, SolutionValue::from(40 as iAoc));
+
+        let text = context.output().read("15.A.risks.tsv").unwrap().unwrap();
+        std::fs::remove_dir_all(directory).unwrap();
+        let rows = text
+            .lines()
+            .map(|line| line.split('\t').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 10);
+        assert!(rows.iter().all(|row| row.len() == 10));
+        assert_eq!(rows[0][0], "0");
+        assert_eq!(rows[0][9], "36");
+        assert_eq!(rows[9][9], "40");
+    }
+
+    #[test]
+    fn heuristic_is_admissible_on_the_example() {
+        let cavern = Cavern::from_str(EXAMPLE).unwrap();
+        for cavern in [cavern.clone(), cavern.tiled(5).unwrap()] {
+            let checked =
+                check_random_walks(&cavern, |point, end| cavern.heuristic(point, end)).unwrap();
+            assert_eq!(checked, 50);
+        }
+    }
+
+    #[test]
+    fn overestimating_heuristic_is_caught() {
+        let cavern = Cavern::from_str(EXAMPLE).unwrap();
+        let err = check_random_walks(&cavern, |point, end| 10 * cavern.heuristic(point, end))
+            .unwrap_err();
+        assert!(err.message().starts_with("heuristic estimated"));
+    }
+}
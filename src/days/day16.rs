@@ -1,9 +1,19 @@
-use crate::common::{iAoc, AocResult};
+use crate::common::math::OverflowPolicy;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, ParseOptions, Rng, SolutionValue,
+    Solver, SolverContext,
+};
+use std::cell::Cell;
+use std::iter::successors;
+use std::panic;
+use std::sync::Once;
 
 mod bits {
-    use crate::common::{AocError, AocResult, IntoAocResult};
+    use crate::common::math::OverflowPolicy;
+    use crate::common::{AocError, AocResult, IntoAocResult, Rng};
     use itertools::Itertools;
     use num::{FromPrimitive, Integer};
+    use std::fmt::{Display, Formatter, Result as DisplayResult};
 
     pub type Input = Vec<u8>;
 
@@ -30,7 +40,7 @@ mod bits {
     }
 
     #[repr(u8)]
-    #[derive(FromPrimitive)]
+    #[derive(Clone, Copy, FromPrimitive)]
     pub enum TypeId {
         Literal = 4,
         Sum = 0,
@@ -42,6 +52,21 @@ mod bits {
         EqualTo = 7,
     }
 
+    impl TypeId {
+        pub fn name(&self) -> &'static str {
+            match self {
+                Self::Literal => "literal",
+                Self::Sum => "sum",
+                Self::Product => "product",
+                Self::Minimum => "minimum",
+                Self::Maximum => "maximum",
+                Self::GreaterThan => "greater than",
+                Self::LessThan => "less than",
+                Self::EqualTo => "equal to",
+            }
+        }
+    }
+
     pub struct Header {
         version: u8,
         type_id: TypeId,
@@ -51,6 +76,77 @@ mod bits {
         header: Header,
         literal: u64,
         subpackets: Vec<Packet>,
+        /// Number of bits the packet takes up, including its subpackets.
+        bit_length: usize,
+    }
+
+    /// Statistics about a packet and all of its subpackets.
+    pub struct PacketStats {
+        /// Packet counts, indexed by type id.
+        by_type: [usize; 8],
+        /// Packet counts, indexed by version.
+        by_version: [usize; 8],
+        /// Packet counts, indexed by depth below the outermost packet.
+        by_depth: Vec<usize>,
+        /// Literal counts, indexed by the number of bits needed to hold the
+        /// literal's value.
+        by_literal_bits: [usize; 65],
+        /// Bits taken up by the outermost packet.
+        bit_length: usize,
+    }
+
+    impl PacketStats {
+        fn add(&mut self, packet: &Packet, depth: usize) {
+            self.by_type[packet.header.type_id as usize] += 1;
+            self.by_version[packet.header.version as usize] += 1;
+            if self.by_depth.len() <= depth {
+                self.by_depth.resize(depth + 1, 0);
+            }
+            self.by_depth[depth] += 1;
+            if let TypeId::Literal = packet.header.type_id {
+                self.by_literal_bits[(64 - packet.literal.leading_zeros()) as usize] += 1;
+            }
+            for subpacket in &packet.subpackets {
+                self.add(subpacket, depth + 1);
+            }
+        }
+    }
+
+    impl Display for PacketStats {
+        fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+            let packets = self.by_type.iter().sum::<usize>();
+            writeln!(f, "{} packets in {} bits", packets, self.bit_length)?;
+
+            writeln!(f, "\nPackets by type:")?;
+            for (type_id, count) in self.by_type.iter().enumerate() {
+                if let Some(type_id) = TypeId::from_usize(type_id) {
+                    writeln!(f, "  {:<12} {}", type_id.name(), count)?;
+                }
+            }
+
+            writeln!(f, "\nPackets by version:")?;
+            for (version, count) in self.by_version.iter().enumerate() {
+                writeln!(f, "  {:<12} {}", version, count)?;
+            }
+
+            writeln!(f, "\nPackets by depth:")?;
+            for (depth, count) in self.by_depth.iter().enumerate() {
+                writeln!(f, "  {:<12} {}", depth, count)?;
+            }
+
+            writeln!(f, "\nLiterals by value:")?;
+            for (bits, count) in self.by_literal_bits.iter().enumerate() {
+                if *count == 0 {
+                    continue;
+                }
+                let range = match bits {
+                    0 => String::from("0"),
+                    _ => format!("2^{}..2^{}", bits - 1, bits),
+                };
+                writeln!(f, "  {:<12} {}", range, count)?;
+            }
+            Ok(())
+        }
     }
 
     impl Packet {
@@ -59,9 +155,22 @@ mod bits {
                 header,
                 literal: 0,
                 subpackets: Vec::new(),
+                bit_length: 0,
             }
         }
 
+        pub fn stats(&self) -> PacketStats {
+            let mut stats = PacketStats {
+                by_type: [0; 8],
+                by_version: [0; 8],
+                by_depth: Vec::new(),
+                by_literal_bits: [0; 65],
+                bit_length: self.bit_length,
+            };
+            stats.add(self, 0);
+            stats
+        }
+
         pub fn sum_versions(&self) -> u64 {
             self.header.version as u64
                 + self
@@ -70,13 +179,16 @@ mod bits {
                     .fold(0u64, |sum, subpacket| sum + subpacket.sum_versions())
         }
 
-        pub fn value(&self) -> AocResult<u64> {
+        pub fn value(&self, overflow: OverflowPolicy) -> AocResult<u64> {
             use TypeId::*;
-            let mut subvalues = self.subpackets.iter().map(|subpacket| subpacket.value());
+            let mut subvalues = self
+                .subpackets
+                .iter()
+                .map(|subpacket| subpacket.value(overflow));
             match self.header.type_id {
                 Literal => Ok(self.literal),
-                Sum => subvalues.try_fold(0u64, |sum, value| Ok(sum + value?)),
-                Product => subvalues.try_fold(1u64, |prod, value| Ok(prod * value?)),
+                Sum => subvalues.try_fold(0u64, |sum, value| overflow.add(sum, value?)),
+                Product => subvalues.try_fold(1u64, |prod, value| overflow.mul(prod, value?)),
                 Minimum => subvalues.try_fold(u64::MAX, |min, value| match value {
                     Err(_) => value,
                     Ok(value) => Ok(if value < min { value } else { min }),
@@ -143,17 +255,14 @@ mod bits {
                 let diff = self.bit_index - num_bits;
                 self.input
                     .get(self.byte_index)
-                    .and_then(|val| {
-                        Some((val & (((1usize << num_bits) - 1) << diff) as u8) >> diff)
-                    })
-                    .and_then(|val| {
+                    .map(|val| (val & (((1usize << num_bits) - 1) << diff) as u8) >> diff)
+                    .inspect(|_| {
                         self.bit_index = if diff == 0 {
                             self.byte_index += 1;
                             8
                         } else {
                             diff
                         };
-                        Some(val)
                     })
             } else {
                 // Not enough bits in the current byte, need the next byte.
@@ -164,21 +273,18 @@ mod bits {
                     .copied()
                     .and_then(|first| {
                         self.byte_index += 1;
-                        self.input.get(self.byte_index).and_then(|second| {
-                            Some(
-                                ((first & ((1 << self.bit_index) - 1)) << diff)
-                                    | ((second & (((1 << diff) - 1) << not_diff)) >> not_diff),
-                            )
+                        self.input.get(self.byte_index).map(|second| {
+                            ((first & ((1 << self.bit_index) - 1)) << diff)
+                                | ((second & (((1 << diff) - 1) << not_diff)) >> not_diff)
                         })
                     })
-                    .and_then(|val| {
+                    .inspect(|_| {
                         self.bit_index = if not_diff == 0 {
                             self.byte_index += 1;
                             8
                         } else {
                             not_diff
                         };
-                        Some(val)
                     })
             }
         }
@@ -206,89 +312,528 @@ mod bits {
             }
         }
 
-        fn read_packet(&mut self) -> AocResult<Packet> {
-            let mut packet = Packet::new(self.read_header()?);
-            match packet.header.type_id {
-                TypeId::Literal => packet.literal = self.read_literal()?,
-                _ => packet.subpackets = self.read_operator()?,
+        /// Reads a field, reporting where it started if it could not be read.
+        fn read_field<T, F>(&mut self, expected: &'static str, read: F) -> Result<T, DecodeError>
+        where
+            F: FnOnce(&mut Self) -> Option<T>,
+        {
+            let bit_offset = self.global_bit_index();
+            read(self).ok_or(DecodeError {
+                bit_offset,
+                expected,
+            })
+        }
+
+        fn read_packet(&mut self) -> Result<Packet, Truncated> {
+            let start = self.global_bit_index();
+            let header = self.read_header().map_err(|error| Truncated {
+                packet: None,
+                error,
+            })?;
+            let mut packet = Packet::new(header);
+            let result = match packet.header.type_id {
+                TypeId::Literal => self.read_literal(&mut packet.literal),
+                _ => self.read_operator(&mut packet.subpackets),
             };
+            packet.bit_length = self.global_bit_index() - start;
 
-            Ok(packet)
+            match result {
+                Ok(()) => Ok(packet),
+                Err(error) => Err(Truncated {
+                    packet: Some(packet),
+                    error,
+                }),
+            }
         }
 
-        fn read_header(&mut self) -> AocResult<Header> {
-            let version = self
-                .read_up_to_8(3)
-                .into_aoc_result_msg("missing 3-bit version")?;
-            let type_id = self
-                .read_up_to_8(3)
-                .into_aoc_result_msg("missing 3-bit type id")?;
-            let type_id = TypeId::from_u8(type_id).into_aoc_result_msg("invalid type id")?;
+        fn read_header(&mut self) -> Result<Header, DecodeError> {
+            let version = self.read_field("3-bit version", |reader| reader.read_up_to_8(3))?;
+            let type_id = self.read_field("3-bit type id", |reader| {
+                reader.read_up_to_8(3).and_then(TypeId::from_u8)
+            })?;
             Ok(Header { version, type_id })
         }
 
-        fn read_literal(&mut self) -> AocResult<u64> {
-            let mut literal: u64 = 0;
+        fn read_literal(&mut self, literal: &mut u64) -> Result<(), DecodeError> {
             let mut more_to_read = true;
             while more_to_read {
-                let next_bits = self
-                    .read_up_to_8(5)
-                    .into_aoc_result_msg("missing 5-bit literal chunk")?;
+                let next_bits =
+                    self.read_field("5-bit literal chunk", |reader| reader.read_up_to_8(5))?;
                 more_to_read = next_bits & (1 << 4) != 0;
-                literal <<= 4;
-                literal |= (next_bits & ((1 << 4) - 1)) as u64;
+                *literal <<= 4;
+                *literal |= (next_bits & ((1 << 4) - 1)) as u64;
             }
 
-            Ok(literal)
+            Ok(())
         }
 
-        fn read_operator(&mut self) -> AocResult<Vec<Packet>> {
-            let length_type_id = self
-                .read_up_to_8(1)
-                .into_aoc_result_msg("missing 1-bit length type id")?;
+        fn read_operator(&mut self, subpackets: &mut Vec<Packet>) -> Result<(), DecodeError> {
+            let length_type_id =
+                self.read_field("1-bit length type id", |reader| reader.read_up_to_8(1))?;
 
-            let mut subpackets = Vec::new();
+            // Keeps whatever was decoded of a truncated subpacket.
+            let mut read_subpacket = |reader: &mut Self| match reader.read_packet() {
+                Ok(subpacket) => {
+                    subpackets.push(subpacket);
+                    Ok(())
+                }
+                Err(Truncated { packet, error }) => {
+                    subpackets.extend(packet);
+                    Err(error)
+                }
+            };
 
             if length_type_id == 0 {
                 let total_subpacket_length = self
-                    .read_up_to_64(15)
-                    .into_aoc_result_msg("missing 15-bit total subpacket length")?;
+                    .read_field("15-bit total subpacket length", |reader| {
+                        reader.read_up_to_64(15)
+                    })?;
 
                 let end_index = self.global_bit_index() + total_subpacket_length as usize;
                 while self.global_bit_index() < end_index {
-                    subpackets.push(self.read_packet()?);
+                    read_subpacket(self)?;
                 }
             } else {
-                let num_subpackets = self
-                    .read_up_to_64(11)
-                    .into_aoc_result_msg("missing 11-bit subpacket number")?;
+                let num_subpackets =
+                    self.read_field("11-bit subpacket number", |reader| reader.read_up_to_64(11))?;
 
                 for _ in 0..num_subpackets {
-                    subpackets.push(self.read_packet()?);
+                    read_subpacket(self)?;
                 }
             }
 
-            Ok(subpackets)
+            Ok(())
+        }
+
+        /// Reads as much of the outermost packet as possible.
+        ///
+        /// If the transmission is truncated or corrupted, the packets decoded
+        /// before the failure are returned alongside where it happened.
+        pub fn read_partial(&mut self) -> (Option<Packet>, Option<DecodeError>) {
+            match self.read_packet() {
+                Ok(packet) => (Some(packet), None),
+                Err(Truncated { packet, error }) => (packet, Some(error)),
+            }
+        }
+    }
+
+    /// Where decoding a transmission failed.
+    pub struct DecodeError {
+        /// Offset of the field that could not be read, from the start of the
+        /// transmission.
+        pub bit_offset: usize,
+        /// The field that was expected.
+        pub expected: &'static str,
+    }
+
+    impl Display for DecodeError {
+        fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+            write!(f, "missing {} at bit {}", self.expected, self.bit_offset)
         }
+    }
 
-        pub fn read(&mut self) -> AocResult<Packet> {
-            self.read_packet()
+    impl From<DecodeError> for AocError {
+        fn from(error: DecodeError) -> Self {
+            AocError::new(error.to_string())
+        }
+    }
+
+    /// A packet that failed to decode, with whatever part of it was decoded.
+    struct Truncated {
+        packet: Option<Packet>,
+        error: DecodeError,
+    }
+
+    /// Writes packets bit by bit, for generating transmissions.
+    #[derive(Default)]
+    pub struct Writer {
+        bits: Vec<bool>,
+    }
+
+    impl Writer {
+        fn write(&mut self, value: u64, num_bits: usize) {
+            self.bits
+                .extend((0..num_bits).rev().map(|bit| value & (1 << bit) != 0));
+        }
+
+        /// Writes a random packet, returning the sum of its versions.
+        ///
+        /// Packets nest at most `depth` levels deep, and some literals are
+        /// longer than 64 bits.
+        pub fn write_random_packet(&mut self, rng: &mut Rng, depth: usize) -> u64 {
+            const OPERATORS: [TypeId; 7] = [
+                TypeId::Sum,
+                TypeId::Product,
+                TypeId::Minimum,
+                TypeId::Maximum,
+                TypeId::GreaterThan,
+                TypeId::LessThan,
+                TypeId::EqualTo,
+            ];
+            let version = rng.below(8) as u64;
+            self.write(version, 3);
+            if depth == 0 || rng.below(3) == 0 {
+                self.write(TypeId::Literal as u64, 3);
+                let chunks = 1 + rng.below(20);
+                for chunk in 0..chunks {
+                    let more = if chunk + 1 < chunks { 1 << 4 } else { 0 };
+                    self.write(more | rng.below(16) as u64, 5);
+                }
+                return version;
+            }
+
+            let type_id = OPERATORS[rng.below(OPERATORS.len())];
+            self.write(type_id as u64, 3);
+            let subpackets = match type_id {
+                TypeId::GreaterThan | TypeId::LessThan | TypeId::EqualTo => 2,
+                _ => 1 + rng.below(4),
+            };
+            let mut sum = version;
+            if rng.below(2) == 0 {
+                // The length is only known once the subpackets are written.
+                self.write(0, 1);
+                let length_index = self.bits.len();
+                self.write(0, 15);
+                for _ in 0..subpackets {
+                    sum += self.write_random_packet(rng, depth - 1);
+                }
+                let length = self.bits.len() - length_index - 15;
+                let mut length_field = Writer::default();
+                length_field.write(length as u64, 15);
+                self.bits[length_index..length_index + 15].copy_from_slice(&length_field.bits);
+            } else {
+                self.write(1, 1);
+                self.write(subpackets as u64, 11);
+                for _ in 0..subpackets {
+                    sum += self.write_random_packet(rng, depth - 1);
+                }
+            }
+            sum
+        }
+
+        /// Corrupts the transmission by flipping a few bits or cutting it
+        /// short.
+        pub fn mutate(&mut self, rng: &mut Rng) {
+            if rng.below(2) == 0 {
+                for _ in 0..1 + rng.below(3) {
+                    let index = rng.below(self.bits.len());
+                    self.bits[index] = !self.bits[index];
+                }
+            } else {
+                self.bits.truncate(rng.below(self.bits.len()));
+            }
+        }
+
+        /// Packs the bits into bytes, padding the last byte with zeros.
+        pub fn into_input(self) -> Input {
+            self.bits
+                .chunks(8)
+                .map(|chunk| {
+                    chunk
+                        .iter()
+                        .enumerate()
+                        .fold(0, |byte, (i, bit)| byte | ((*bit as u8) << (7 - i)))
+                })
+                .collect()
         }
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
+/// Decodes the transmission.
+///
+/// A truncated or corrupted transmission fails to decode, unless `partial` is
+/// set, in which case it is decoded as far as possible, which is tolerated
+/// unless parsing is strict.
+fn decode(input: &str, partial: bool, options: &ParseOptions) -> AocResult<bits::Packet> {
     let input = bits::parse_input(input)?;
     let mut reader = bits::Reader::new(input);
-    let packet = reader.read()?;
-    let result = packet.sum_versions();
-    Ok(result as iAoc)
+    let (packet, error) = reader.read_partial();
+    if let Some(error) = error {
+        if !partial {
+            return Err(error.into());
+        }
+        options.tolerate(format!("transmission is truncated or corrupted: {}", error))?;
+    }
+    packet.into_aoc_result_msg("no packet could be decoded")
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let input = bits::parse_input(input)?;
-    let mut reader = bits::Reader::new(input);
-    let packet = reader.read()?;
-    let result = packet.value()?;
-    Ok(result as iAoc)
+/// Whether the `partial` parameter allows decoding part of a transmission.
+fn partial_param(context: &SolverContext) -> AocResult<bool> {
+    match context.param("partial") {
+        None => Ok(false),
+        Some(partial) => partial
+            .parse()
+            .into_aoc_result_msg("partial must be true or false"),
+    }
+}
+
+/// How decoding a fuzzed transmission turned out.
+#[derive(PartialEq)]
+enum FuzzOutcome {
+    /// The whole transmission decoded, with the given sum of versions.
+    Decoded(u64),
+    /// The transmission could not be decoded completely.
+    Rejected,
+    Panicked,
+}
+
+thread_local! {
+    /// Whether a panic on this thread is expected, so its message is hidden.
+    static PANIC_EXPECTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs a panic hook that hides the messages of expected panics and
+/// passes every other panic on to the hook it replaces.
+///
+/// The hook is only installed once and never removed, so solvers fuzzing on
+/// other threads cannot restore a hook that hides panics for everyone.
+fn hide_expected_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !PANIC_EXPECTED.get() {
+                hook(info);
+            }
+        }));
+    });
+}
+
+/// Decodes the transmission and computes everything the solvers and the
+/// `stats` parameter would from it.
+///
+/// Panics are expected while fuzzing, so their messages are hidden.
+fn fuzz_decode(input: &bits::Input, overflow: OverflowPolicy) -> FuzzOutcome {
+    hide_expected_panics();
+    let input = input.clone();
+    PANIC_EXPECTED.set(true);
+    let result = panic::catch_unwind(move || {
+        let (packet, error) = bits::Reader::new(input).read_partial();
+        let packet = packet?;
+        // Values can overflow, which is reported as an error rather than
+        // counted as a failure to decode.
+        let _ = packet.value(overflow);
+        packet.stats().to_string();
+        match error {
+            None => Some(packet.sum_versions()),
+            Some(_) => None,
+        }
+    });
+    PANIC_EXPECTED.set(false);
+    match result {
+        Ok(Some(versions)) => FuzzOutcome::Decoded(versions),
+        Ok(None) => FuzzOutcome::Rejected,
+        Err(_) => FuzzOutcome::Panicked,
+    }
+}
+
+/// Shrinks a transmission the decoder panics on by removing runs of bytes and
+/// clearing single bytes for as long as it still panics.
+fn shrink(mut input: bits::Input, overflow: OverflowPolicy) -> bits::Input {
+    loop {
+        let length = input.len();
+        let removed = successors(Some(length / 2), |run| Some(run / 2))
+            .take_while(|run| *run > 0)
+            .flat_map(|run| (0..=length - run).map(move |start| (start, run)))
+            .map(|(start, run)| {
+                let mut candidate = input.clone();
+                candidate.drain(start..start + run);
+                candidate
+            });
+        let cleared = (0..length).filter(|i| input[*i] != 0).map(|i| {
+            let mut candidate = input.clone();
+            candidate[i] = 0;
+            candidate
+        });
+        let smaller = removed
+            .chain(cleared)
+            .find(|candidate| fuzz_decode(candidate, overflow) == FuzzOutcome::Panicked);
+        match smaller {
+            Some(smaller) => input = smaller,
+            None => return input,
+        }
+    }
+}
+
+fn to_hex(input: &bits::Input) -> String {
+    input.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Decodes generated transmissions, failing if the decoder panics on one or
+/// decodes a valid one wrongly.
+///
+/// A quarter of the transmissions are random bytes. The rest are random
+/// packets, two thirds of which are corrupted, since random bytes rarely get
+/// past the first packet header. A transmission that panics is shrunk before
+/// it is reported, so it can be checked in as an example.
+fn fuzz(iterations: usize, overflow: OverflowPolicy) -> AocResult<()> {
+    let mut rng = Rng::new(16);
+    for _ in 0..iterations {
+        let (input, expected) = match rng.below(4) {
+            0 => {
+                let length = rng.below(64);
+                let input = (0..length).map(|_| rng.next_u64() as u8).collect();
+                (input, None)
+            }
+            kind => {
+                let mut writer = bits::Writer::default();
+                let versions = writer.write_random_packet(&mut rng, 6);
+                if kind == 1 {
+                    (writer.into_input(), Some(versions))
+                } else {
+                    writer.mutate(&mut rng);
+                    (writer.into_input(), None)
+                }
+            }
+        };
+        let result = match (fuzz_decode(&input, overflow), expected) {
+            (FuzzOutcome::Panicked, _) => Err(AocError::new(format!(
+                "decoder panicked on {}",
+                to_hex(&shrink(input, overflow))
+            ))),
+            (FuzzOutcome::Decoded(versions), Some(expected)) if versions != expected => {
+                Err(AocError::new(format!(
+                    "decoded {} with a version sum of {} instead of {}",
+                    to_hex(&input),
+                    versions,
+                    expected
+                )))
+            }
+            (FuzzOutcome::Rejected, Some(_)) => Err(AocError::new(format!(
+                "failed to decode valid transmission {}",
+                to_hex(&input)
+            ))),
+            _ => Ok(()),
+        };
+        result?;
+    }
+    Ok(())
+}
+
+/// Fuzzes the decoder first if the `fuzz` parameter is set.
+fn fuzz_param(context: &SolverContext) -> AocResult<()> {
+    match context.param("fuzz") {
+        None => Ok(()),
+        Some(iterations) => fuzz(
+            iterations
+                .parse()
+                .into_aoc_result_msg("fuzz must be a number of transmissions")?,
+            context.overflow(),
+        ),
+    }
+}
+
+/// Writes statistics about the decoded packets if the `stats` parameter is set.
+fn write_stats(context: &SolverContext, part: &str, packet: &bits::Packet) -> AocResult<()> {
+    let enabled = match context.param("stats") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("stats must be true or false")?,
+    };
+    if !enabled {
+        return Ok(());
+    }
+    let name = format!("16.{}.stats.txt", part);
+    context
+        .output()
+        .write(&name, packet.stats().to_string().as_bytes())
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[
+        ParamInfo {
+            name: "stats",
+            values: "true|false",
+            description: "Writes packet counts by type, version, and depth, a histogram of literal values, and the bits used to output/16.<part>.stats.txt.",
+            default: "false",
+        },
+        ParamInfo {
+            name: "fuzz",
+            values: "n",
+            description: "Decodes n generated transmissions before solving, failing with the shrunk transmission if the decoder panics on one.",
+            default: "0",
+        },
+        ParamInfo {
+            name: "partial",
+            values: "true|false",
+            description: "Decodes a truncated or corrupted transmission as far as possible instead of failing, unless parsing is strict.",
+            default: "false",
+        },
+    ],
+    animated: false,
+};
+
+pub struct PacketDecoder;
+
+impl Solver for PacketDecoder {
+    type Parsed = bits::Packet;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<bits::Packet> {
+        decode(input, partial_param(context)?, context.parse_options())
+    }
+
+    fn part_a(packet: &bits::Packet, context: &SolverContext) -> AocResult<SolutionValue> {
+        fuzz_param(context)?;
+        write_stats(context, "A", packet)?;
+        let result = packet.sum_versions();
+        Ok(SolutionValue::from(result as iAoc))
+    }
+
+    fn part_b(packet: &bits::Packet, context: &SolverContext) -> AocResult<SolutionValue> {
+        fuzz_param(context)?;
+        write_stats(context, "B", packet)?;
+        let result = packet.value(context.overflow())?;
+        Ok(SolutionValue::from(result as iAoc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::{self, assert_solves};
+
+    /// A transmission the decoder once panicked on, found by fuzzing.
+    const FUZZ_REGRESSION: &str = include_str!("../../input/test/16.13.txt");
+
+    #[test]
+    fn rejects_fuzz_regression_without_panicking() {
+        let input = bits::parse_input(FUZZ_REGRESSION).unwrap();
+        assert!(fuzz_decode(&input, OverflowPolicy::Check) == FuzzOutcome::Rejected);
+    }
+
+    #[test]
+    fn truncated_transmission_fails_to_decode() {
+        let context = testing::context(None, &[]);
+        let error = PacketDecoder::parse(FUZZ_REGRESSION, &context)
+            .err()
+            .unwrap();
+        assert!(error.message().starts_with("missing "));
+    }
+
+    #[test]
+    fn partial_decodes_truncated_transmission() {
+        // The transmission is truncated, so only part of it decodes.
+        assert_solves!(
+            PacketDecoder::part_a,
+            FUZZ_REGRESSION,
+            14,
+            params: [("partial", "true")]
+        );
+    }
+
+    #[test]
+    fn fuzzing_finds_no_panics() {
+        fuzz(500, OverflowPolicy::Check).unwrap();
+    }
+
+    #[test]
+    fn panics_are_only_expected_inside_fuzz_decode() {
+        hide_expected_panics();
+        assert!(!PANIC_EXPECTED.get());
+        let input = bits::parse_input(FUZZ_REGRESSION).unwrap();
+        fuzz_decode(&input, OverflowPolicy::Check);
+        assert!(!PANIC_EXPECTED.get());
+    }
 }
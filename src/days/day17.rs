@@ -1,4 +1,9 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+#[cfg(feature = "visualize")]
+use crate::common::svg::Document;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, SolutionValue, Solver,
+    SolverContext,
+};
 use itertools::Itertools;
 use num::integer::Roots;
 use num::Integer;
@@ -7,7 +12,7 @@ use std::str::FromStr;
 
 type Point = (i32, i32);
 
-struct TargetArea {
+pub struct TargetArea {
     min: Point,
     max: Point,
 }
@@ -60,8 +65,19 @@ impl FromStr for TargetArea {
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let target = TargetArea::from_str(input)?;
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[ParamInfo {
+        name: "trajectory",
+        values: "apex|vx,vy",
+        description: "Plots the trajectory of the highest probe, or of the given velocity, to output/17.<part>.trajectory.svg.",
+        default: "no plot",
+    }],
+    animated: false,
+};
+
+fn highest_peak(target: &TargetArea, context: &SolverContext) -> AocResult<iAoc> {
+    write_trajectory(context, "A", target)?;
 
     /*
         To get the largest maximum height, we want the largest initial Y velocity that
@@ -88,7 +104,7 @@ pub fn solve_a(input: &str) -> AocResult<iAoc> {
 
     let min_y = target.min.1;
     let v_y = -min_y - 1;
-    let peak = ((v_y + 1) * v_y).div_floor(&2);
+    let peak = Integer::div_floor(&((v_y + 1) * v_y), &2);
     Ok(peak as iAoc)
 }
 
@@ -119,8 +135,113 @@ impl Iterator for TrajectoryIterator {
     }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let target = TargetArea::from_str(input)?;
+/// The velocity that reaches the highest point and still hits the target.
+///
+/// The Y velocity is described in part A. The X velocity is the smallest one
+/// that reaches the target, which stops moving above it.
+#[cfg(feature = "visualize")]
+fn apex_velocity(target: &TargetArea) -> (i32, i32) {
+    let v_x = Integer::div_ceil(&(-1 + (8 * target.min.0 + 1).sqrt()), &2);
+    (v_x, -target.min.1 - 1)
+}
+
+/// Plots a trajectory and the target area if the `trajectory` parameter is set.
+#[cfg(feature = "visualize")]
+fn write_trajectory(context: &SolverContext, part: &str, target: &TargetArea) -> AocResult<()> {
+    let (v_x, v_y) = match context.param("trajectory") {
+        None => return Ok(()),
+        Some("apex") => apex_velocity(target),
+        Some(velocity) => {
+            let (v_x, v_y) = velocity
+                .split_once(',')
+                .into_aoc_result_msg("trajectory must be apex or vx,vy")?;
+            (
+                v_x.parse::<i32>().into_aoc_result()?,
+                v_y.parse::<i32>().into_aoc_result()?,
+            )
+        }
+    };
+
+    // Follow the probe until it hits the target or can no longer reach it.
+    let mut points = vec![(0, 0)];
+    for pos in TrajectoryIterator::new((0, 0), v_x, v_y) {
+        points.push(pos);
+        if target.in_area(&pos) || pos.0 > target.max.0 || pos.1 < target.min.1 {
+            break;
+        }
+    }
+
+    // Each axis is scaled to fill the plot on its own, since the probe can go
+    // much higher than it goes forward. SVG coordinates grow downward, so Y is
+    // flipped.
+    const SIZE: f64 = 800.0;
+    const MARGIN: f64 = 40.0;
+    let min_x = points
+        .iter()
+        .map(|pos| pos.0)
+        .chain([target.min.0])
+        .min()
+        .unwrap_or(0);
+    let max_x = points
+        .iter()
+        .map(|pos| pos.0)
+        .chain([target.max.0])
+        .max()
+        .unwrap_or(0);
+    let min_y = points
+        .iter()
+        .map(|pos| pos.1)
+        .chain([target.min.1])
+        .min()
+        .unwrap_or(0);
+    let max_y = points
+        .iter()
+        .map(|pos| pos.1)
+        .chain([target.max.1])
+        .max()
+        .unwrap_or(0);
+    let scale = |min: i32, max: i32| (SIZE - 2.0 * MARGIN) / (max - min).max(1) as f64;
+    let (scale_x, scale_y) = (scale(min_x, max_x), scale(min_y, max_y));
+    let to_plot = |(x, y): Point| {
+        (
+            MARGIN + (x - min_x) as f64 * scale_x,
+            MARGIN + (max_y - y) as f64 * scale_y,
+        )
+    };
+
+    let mut document = Document::new(SIZE, SIZE);
+    let (left, top) = to_plot((target.min.0, target.max.1));
+    let (right, bottom) = to_plot((target.max.0, target.min.1));
+    document.rect(left, top, right - left, bottom - top, "lightblue");
+    let plotted = points.iter().map(|pos| to_plot(*pos)).collect::<Vec<_>>();
+    document.polyline(&plotted, "black");
+    for (x, y) in &plotted {
+        document.circle(*x, *y, 3.0, "black");
+    }
+    let hit = points.last().is_some_and(|pos| target.in_area(pos));
+    document.text(
+        MARGIN,
+        MARGIN / 2.0,
+        16.0,
+        &format!(
+            "velocity {},{}: {}",
+            v_x,
+            v_y,
+            if hit { "hit" } else { "miss" }
+        ),
+    );
+
+    let name = format!("17.{}.trajectory.svg", part);
+    document.write(context.output(), &name)
+}
+
+#[cfg(not(feature = "visualize"))]
+fn write_trajectory(context: &SolverContext, _: &str, _: &TargetArea) -> AocResult<()> {
+    context.reject_param("trajectory", "visualize")
+}
+
+fn count_velocities(target: &TargetArea, context: &SolverContext) -> AocResult<iAoc> {
+    write_trajectory(context, "B", target)?;
     // The minimum initial Y velocity goes directly to the bottom of the target area
     // in the first step.
     let min_v_y = target.min.1;
@@ -156,7 +277,7 @@ pub fn solve_b(input: &str) -> AocResult<iAoc> {
 
             min_v_x = (-1 + sqrt(8 * min_x + 1)) / 2
     */
-    let min_v_x = (-1 + (8 * target.min.0 + 1).sqrt()).div_ceil(&2);
+    let min_v_x = Integer::div_ceil(&(-1 + (8 * target.min.0 + 1).sqrt()), &2);
     let max_v_x = target.max.0;
 
     // Now count all valid velocity pairs.
@@ -178,3 +299,21 @@ pub fn solve_b(input: &str) -> AocResult<iAoc> {
         .count();
     Ok(result as iAoc)
 }
+
+pub struct TrickShot;
+
+impl Solver for TrickShot {
+    type Parsed = TargetArea;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<TargetArea> {
+        TargetArea::from_str(input)
+    }
+
+    fn part_a(target: &TargetArea, context: &SolverContext) -> AocResult<SolutionValue> {
+        highest_peak(target, context).map(SolutionValue::from)
+    }
+
+    fn part_b(target: &TargetArea, context: &SolverContext) -> AocResult<SolutionValue> {
+        count_velocities(target, context).map(SolutionValue::from)
+    }
+}
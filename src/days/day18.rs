@@ -1,4 +1,8 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::math::OverflowPolicy;
+use crate::common::{
+    parse_lines, AocError, AocResult, DayInfo, IntoAocResult, SolutionValue, Solver, SolverContext,
+    StrategyInfo,
+};
 use itertools::Itertools;
 use num::Integer;
 use std::str::FromStr;
@@ -6,8 +10,8 @@ use std::str::FromStr;
 /// Stores each node value and its depth rather than the entire tree structure.
 /// Makes finding neighbors extremely easy, but tree operations are a bit more
 /// difficult to implement.
-#[derive(Clone)]
-struct SnailfishNumber {
+#[derive(Clone, Default)]
+pub struct SnailfishNumber {
     values: Vec<u64>,
     depths: Vec<u8>,
 }
@@ -15,12 +19,33 @@ struct SnailfishNumber {
 impl SnailfishNumber {
     pub fn add(&self, other: &SnailfishNumber) -> Self {
         let mut sum = self.clone();
-        sum.values.extend(other.values.iter());
-        sum.depths.extend(other.depths.iter());
-        for depth in sum.depths.iter_mut() {
+        sum.add_in_place(other);
+        sum
+    }
+
+    /// Adds the other number to this one without reducing, keeping this
+    /// number's storage, so a running sum grows without being copied.
+    pub fn add_in_place(&mut self, other: &SnailfishNumber) {
+        self.values.extend(other.values.iter());
+        self.depths.extend(other.depths.iter());
+        for depth in self.depths.iter_mut() {
             *depth += 1;
         }
-        sum
+    }
+
+    /// Overwrites this number with the unreduced sum of the two numbers,
+    /// reusing its storage instead of allocating a new number.
+    pub fn set_sum(&mut self, left: &SnailfishNumber, right: &SnailfishNumber) {
+        self.values.clear();
+        self.depths.clear();
+        self.values
+            .extend(left.values.iter().chain(right.values.iter()));
+        self.depths.extend(
+            left.depths
+                .iter()
+                .chain(right.depths.iter())
+                .map(|depth| depth + 1),
+        );
     }
 
     fn is_pair(&self, i: usize) -> bool {
@@ -30,7 +55,7 @@ impl SnailfishNumber {
         // of each node relative to its parent is not stored.
         self.depths
             .get(i + 1)
-            .and_then(|right| Some(*right == self.depths[i]))
+            .map(|right| *right == self.depths[i])
             .unwrap_or(false)
     }
 
@@ -73,7 +98,7 @@ impl SnailfishNumber {
 
     fn split(&mut self, i: usize) {
         let value = self.values[i];
-        let left = value.div_floor(&2);
+        let left = Integer::div_floor(&value, &2);
         let right = value - left;
 
         self.values[i] = left;
@@ -83,13 +108,40 @@ impl SnailfishNumber {
         self.depths.insert(i + 1, self.depths[i]);
     }
 
-    pub fn magnitude(mut self) -> u64 {
+    /// Calculates the magnitude without modifying the number.
+    ///
+    /// Nodes are pushed onto a stack with their depth, where the outermost
+    /// pair is at depth -1. Whenever the top two entries share a depth, they
+    /// are the two sides of a pair, so they collapse into its magnitude one
+    /// level up.
+    pub fn magnitude(&self, overflow: OverflowPolicy) -> AocResult<u64> {
+        let mut stack: Vec<(u64, i16)> = Vec::with_capacity(self.values.len());
+        for (value, depth) in self.values.iter().zip(self.depths.iter()) {
+            stack.push((*value, *depth as i16));
+            while let [.., (left, left_depth), (right, right_depth)] = stack[..] {
+                if left_depth != right_depth {
+                    break;
+                }
+                stack.truncate(stack.len() - 2);
+                let magnitude = overflow.add(overflow.mul(3, left)?, overflow.mul(2, right)?)?;
+                stack.push((magnitude, left_depth - 1));
+            }
+        }
+        Ok(stack.first().map_or(0, |(magnitude, _)| *magnitude))
+    }
+
+    /// Calculates the magnitude by collapsing the leftmost pair until one
+    /// value remains, consuming the number.
+    pub fn collapse_magnitude(mut self, overflow: OverflowPolicy) -> AocResult<u64> {
         // Reduce the first pair from left to right until there is only one
         // value remaining.
         while self.values.len() > 1 {
             for i in 0..self.values.len() {
                 if self.is_pair(i) {
-                    self.values[i] = 3 * self.values[i] + 2 * self.values[i + 1];
+                    self.values[i] = overflow.add(
+                        overflow.mul(3, self.values[i])?,
+                        overflow.mul(2, self.values[i + 1])?,
+                    )?;
                     if self.depths[i] > 0 {
                         self.depths[i] -= 1;
                     }
@@ -101,7 +153,77 @@ impl SnailfishNumber {
                 }
             }
         }
-        self.values[0]
+        Ok(self.values[0])
+    }
+}
+
+/// Recursive descent parser that checks the structure of a snailfish number
+/// while flattening it.
+struct SnailfishParser<'a> {
+    input: &'a [u8],
+    /// Whitespace trimmed from the start of the line, so columns match it.
+    offset: usize,
+    pos: usize,
+    number: SnailfishNumber,
+}
+
+impl<'a> SnailfishParser<'a> {
+    /// Pairs nested inside this many pairs explode, so they cannot appear in
+    /// a reduced number.
+    const MAX_DEPTH: u8 = 4;
+
+    fn error(&self, expected: &str) -> AocError {
+        let error = match self.input.get(self.pos) {
+            None => AocError::new(format!(
+                "malformed snailfish number: expected {}, found end of input",
+                expected
+            )),
+            Some(found) => AocError::new(format!(
+                "malformed snailfish number: expected {}, found '{}'",
+                expected, *found as char
+            )),
+        };
+        error.at_column(self.offset + self.pos + 1)
+    }
+
+    fn expect(&mut self, ch: u8) -> AocResult<()> {
+        if self.input.get(self.pos) == Some(&ch) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", ch as char)))
+        }
+    }
+
+    /// Parses a pair whose opening bracket is at the given depth, starting
+    /// from zero for the outermost pair.
+    fn pair(&mut self, depth: u8) -> AocResult<()> {
+        if depth >= Self::MAX_DEPTH {
+            return Err(AocError::new(format!(
+                "malformed snailfish number: pair is nested inside {} pairs, so the number is not reduced",
+                Self::MAX_DEPTH
+            ))
+            .at_column(self.offset + self.pos + 1));
+        }
+        self.expect(b'[')?;
+        self.element(depth)?;
+        self.expect(b',')?;
+        self.element(depth)?;
+        self.expect(b']')
+    }
+
+    /// Parses either side of a pair, which is a regular number or another pair.
+    fn element(&mut self, depth: u8) -> AocResult<()> {
+        match self.input.get(self.pos) {
+            Some(b'[') => self.pair(depth + 1),
+            Some(digit) if digit.is_ascii_digit() => {
+                self.number.values.push((digit - b'0') as u64);
+                self.number.depths.push(depth);
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error("'[' or a digit")),
+        }
     }
 }
 
@@ -109,68 +231,238 @@ impl FromStr for SnailfishNumber {
     type Err = AocError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut result = SnailfishNumber {
-            values: Vec::new(),
-            depths: Vec::new(),
+        let mut parser = SnailfishParser {
+            input: input.trim().as_bytes(),
+            offset: input.len() - input.trim_start().len(),
+            pos: 0,
+            number: SnailfishNumber {
+                values: Vec::new(),
+                depths: Vec::new(),
+            },
         };
-        let mut depth = 0;
-        for ch in input.trim().chars() {
-            match ch {
-                '[' => depth += 1,
-                ']' => {
-                    if depth == 0 {
-                        return Err(AocError::new("malformed snailfish number"));
-                    }
-                    depth -= 1;
-                }
-                ',' => (),
-                digit => {
-                    result
-                        .values
-                        .push(digit.to_digit(10).into_aoc_result()? as u64);
-                    result.depths.push(depth - 1);
-                }
-            }
+        parser.pair(0)?;
+        if parser.pos < parser.input.len() {
+            return Err(parser.error("end of input"));
+        }
+        Ok(parser.number)
+    }
+}
+
+/// Strategy used to add and measure the pairs of numbers in part B.
+#[derive(Clone, Copy)]
+enum SumStrategy {
+    /// Reduces every sum in one reused number and measures it in place.
+    Reuse,
+    /// Clones a new number for every sum and collapses it to measure it.
+    Clone,
+    /// Runs both for every pair and fails if they disagree.
+    CrossCheck,
+}
+
+impl FromStr for SumStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "reuse" => Ok(Self::Reuse),
+            "clone" => Ok(Self::Clone),
+            "cross-check" => Ok(Self::CrossCheck),
+            _ => Err(AocError::new(
+                "strategy must be reuse, clone, or cross-check",
+            )),
         }
-        Ok(result)
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let numbers: Vec<SnailfishNumber> = input
-        .lines()
-        .map(|line| SnailfishNumber::from_str(line))
-        .collect::<Result<_, _>>()?;
-
-    let mut numbers_iter = numbers.into_iter();
-    let mut sum = numbers_iter.next().into_aoc_result()?;
-    numbers_iter.fold((), |_, b| {
-        sum = sum.add(&b);
-        sum.reduce();
-    });
-    Ok(sum.magnitude())
+/// Finds the magnitude of the reduced sum by cloning, like the solution
+/// originally did.
+fn cloned_magnitude(
+    a: &SnailfishNumber,
+    b: &SnailfishNumber,
+    overflow: OverflowPolicy,
+) -> AocResult<u64> {
+    let mut sum = a.add(b);
+    sum.reduce();
+    sum.collapse_magnitude(overflow)
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "reuse",
+            description: "Reduces every sum in one reused number and measures it in place.",
+        },
+        StrategyInfo {
+            name: "clone",
+            description: "Clones a new number for every sum and collapses it to measure it.",
+        },
+        StrategyInfo {
+            name: "cross-check",
+            description: "Runs both for every pair of numbers and fails if they disagree.",
+        },
+    ],
+    params: &[],
+    animated: false,
+};
+
+pub struct Snailfish;
+
+impl Solver for Snailfish {
+    type Parsed = Vec<SnailfishNumber>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Vec<SnailfishNumber>> {
+        parse_lines(input, SnailfishNumber::from_str)
+    }
+
+    fn part_a(numbers: &Vec<SnailfishNumber>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let mut numbers_iter = numbers.iter();
+        let mut sum = numbers_iter.next().into_aoc_result()?.clone();
+        for b in numbers_iter {
+            sum.add_in_place(b);
+            sum.reduce();
+        }
+        sum.magnitude(context.overflow()).map(SolutionValue::from)
+    }
+
+    fn part_b(numbers: &Vec<SnailfishNumber>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let strategy = context.strategy_or(SumStrategy::Reuse)?;
+        let overflow = context.overflow();
+        let mut sum = SnailfishNumber::default();
+        let magnitudes = numbers
+            .iter()
+            .enumerate()
+            .cartesian_product(numbers.iter().enumerate())
+            .filter(|((i, _), (j, _))| i != j)
+            .map(|((i, a), (j, b))| {
+                if let SumStrategy::Clone = strategy {
+                    return cloned_magnitude(a, b, overflow);
+                }
+                sum.set_sum(a, b);
+                sum.reduce();
+                let magnitude = sum.magnitude(overflow)?;
+                if let SumStrategy::CrossCheck = strategy {
+                    let cloned = cloned_magnitude(a, b, overflow)?;
+                    if cloned != magnitude {
+                        return Err(AocError::new(format!(
+                            "sum of numbers {} and {} has magnitude {} when reused but {} when cloned",
+                            i + 1,
+                            j + 1,
+                            magnitude,
+                            cloned
+                        )));
+                    }
+                }
+                Ok(magnitude)
+            });
+        let result = itertools::process_results(magnitudes, |magnitudes| magnitudes.max())?
+            .into_aoc_result()?;
+        Ok(SolutionValue::from(result))
+    }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let numbers: Vec<SnailfishNumber> = input
-        .lines()
-        .map(|line| SnailfishNumber::from_str(line))
-        .collect::<Result<_, _>>()?;
-
-    let result = numbers
-        .iter()
-        .enumerate()
-        .cartesian_product(numbers.iter().enumerate())
-        .filter_map(|((i, a), (j, b))| {
-            if i == j {
-                None
-            } else {
-                let mut sum = a.add(b);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    fn parse_error(input: &str) -> String {
+        match SnailfishNumber::from_str(input) {
+            Ok(_) => panic!("{} should not parse", input),
+            Err(err) => err.message(),
+        }
+    }
+
+    #[test]
+    fn rejects_pair_with_three_elements() {
+        assert_eq!(
+            parse_error("[1,2,3]"),
+            "column 5: malformed snailfish number: expected ']', found ','"
+        );
+    }
+
+    #[test]
+    fn rejects_missing_comma() {
+        assert_eq!(
+            parse_error("[1 2]"),
+            "column 3: malformed snailfish number: expected ',', found ' '"
+        );
+        assert_eq!(
+            parse_error("[[1,2][3,4]]"),
+            "column 7: malformed snailfish number: expected ',', found '['"
+        );
+    }
+
+    #[test]
+    fn rejects_unbalanced_brackets() {
+        assert_eq!(
+            parse_error("[[1,2],3"),
+            "column 9: malformed snailfish number: expected ']', found end of input"
+        );
+        assert_eq!(
+            parse_error("[1,2]]"),
+            "column 6: malformed snailfish number: expected end of input, found ']'"
+        );
+    }
+
+    #[test]
+    fn rejects_pairs_nested_too_deeply() {
+        assert_eq!(
+            parse_error("[[[[[1,2],3],4],5],6]"),
+            "column 5: malformed snailfish number: pair is nested inside 4 pairs, so the number is not reduced"
+        );
+    }
+
+    #[test]
+    fn columns_count_leading_whitespace() {
+        assert_eq!(
+            parse_error("  [1,x]"),
+            "column 6: malformed snailfish number: expected '[' or a digit, found 'x'"
+        );
+    }
+
+    const HOMEWORK: &str = include_str!("../../input/test/18.5.txt");
+
+    #[test]
+    fn reused_sums_match_cloned_sums_on_the_homework() {
+        let numbers = parse_lines(HOMEWORK, SnailfishNumber::from_str).unwrap();
+        let overflow = OverflowPolicy::Check;
+        let mut sum = SnailfishNumber::default();
+        for (i, a) in numbers.iter().enumerate() {
+            for (j, b) in numbers.iter().enumerate().filter(|(j, _)| *j != i) {
+                sum.set_sum(a, b);
                 sum.reduce();
-                Some(sum.magnitude())
+                assert_eq!(
+                    sum.magnitude(overflow).unwrap(),
+                    cloned_magnitude(a, b, overflow).unwrap(),
+                    "sum of numbers {} and {}",
+                    i + 1,
+                    j + 1
+                );
             }
-        })
-        .max()
-        .into_aoc_result()?;
-    Ok(result)
+        }
+    }
+
+    #[test]
+    fn in_place_sum_matches_cloned_sum_on_the_homework() {
+        let numbers = parse_lines(HOMEWORK, SnailfishNumber::from_str).unwrap();
+        let overflow = OverflowPolicy::Check;
+        let mut in_place = numbers[0].clone();
+        let mut cloned = numbers[0].clone();
+        for number in &numbers[1..] {
+            in_place.add_in_place(number);
+            in_place.reduce();
+            cloned = cloned.add(number);
+            cloned.reduce();
+        }
+        assert_eq!(in_place.magnitude(overflow).unwrap(), 4140);
+        assert_eq!(cloned.collapse_magnitude(overflow).unwrap(), 4140);
+    }
+
+    #[test]
+    fn every_strategy_solves_the_homework() {
+        assert_solves!(Snailfish::part_a, HOMEWORK, 4140);
+        for strategy in ["reuse", "clone", "cross-check"] {
+            assert_solves!(Snailfish::part_b, HOMEWORK, 3993, strategy: strategy);
+        }
+    }
 }
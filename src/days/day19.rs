@@ -1,9 +1,19 @@
-use crate::common::{iAoc, AocResult, IntoAocResult};
+use crate::common::math;
+use crate::common::profile::{CountingMap, CountingSet};
+#[cfg(feature = "visualize")]
+use crate::common::svg::Document;
+use crate::common::{cancel, log};
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, ParseOptions, ProgressSink,
+    SolutionValue, Solver, SolverContext, StrategyInfo,
+};
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use num::{Integer, Unsigned};
-use rustc_hash::{FxHashMap, FxHashSet};
-use std::ops::{Add, Index, Mul, MulAssign, Sub};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use std::hash::BuildHasherDefault;
+use std::ops::{Add, Index, Mul, MulAssign, Neg, Sub};
+use std::str::FromStr;
 
 /// A single point, which can represent a beacon or scanner.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -42,6 +52,28 @@ impl Sub<&Point> for &Point {
     }
 }
 
+impl Neg for &Point {
+    type Output = Point;
+
+    fn neg(self) -> Self::Output {
+        Point((-self.0 .0, -self.0 .1, -self.0 .2))
+    }
+}
+
+/// Orientation-invariant signature of the line between two beacons, which is
+/// the absolute difference along each axis, sorted.
+///
+/// Rotating a scanner only permutes and negates the differences, so the same
+/// two beacons have the same signature in every scanner that sees them.
+type Signature = [i32; 3];
+
+fn signature(a: &Point, b: &Point) -> Signature {
+    let delta = b - a;
+    let mut signature = [delta.0 .0.abs(), delta.0 .1.abs(), delta.0 .2.abs()];
+    signature.sort_unstable();
+    signature
+}
+
 /// A single axis in a 3D plane.
 #[derive(Copy, Clone, Debug)]
 #[repr(u8)]
@@ -130,60 +162,139 @@ impl Mul<&Point> for &TransformationMatrix {
     }
 }
 
+type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+/// Number of beacons two scanners must both see to be merged.
+const DESIRED_OVERLAPS: usize = 12;
+
+/// A set of beacons, counted so that merging can be profiled.
+type BeaconSet = CountingSet<Point, FxBuildHasher>;
+
 /// A single scanner and its collection of known beacons.
-struct Scanner {
-    beacons: FxHashSet<Point>,
+#[derive(Clone)]
+pub struct Scanner {
+    beacons: BeaconSet,
 }
 
 /// A scanner, its collection of known beacons, and a set of the distances
 /// between those beacons.
-struct ScannerWithDistancesToBeacons {
-    beacons: FxHashSet<Point>,
+pub struct ScannerWithDistancesToBeacons {
+    beacons: BeaconSet,
     // Maps a distance to a vector of beacons that have another beacon that
     // distance away from it.
-    distances: FxHashMap<usize, Vec<Point>>,
+    distances: CountingMap<usize, Vec<Point>, FxBuildHasher>,
 }
 
 /// A global map of known scanners and their corresponding beacon data.
 /// Scanner data is translated and oriented properly before inserted into the global map.
-struct GlobalMap {
+pub struct GlobalMap {
     scanners: FxHashMap<Point, ScannerWithDistancesToBeacons>,
 }
 
 impl GlobalMap {
-    pub fn new() -> Self {
+    fn new() -> Self {
         Self {
             scanners: FxHashMap::default(),
         }
     }
 
-    pub fn from_scanners(scanners: Vec<Scanner>) -> Self {
+    /// Merges every scanner into a map relative to the first scanner, passing
+    /// over the scanners left until all of them are merged, and reporting
+    /// how many are merged to `progress` along the way.
+    fn from_scanners(
+        scanners: Vec<Scanner>,
+        strategy: AlignStrategy,
+        progress: &ProgressSink,
+    ) -> AocResult<Self> {
+        // Scanners are numbered in input order.
         let mut scanners = scanners
             .into_iter()
             .map(|scan| scan.into_distances())
+            .enumerate()
             .collect::<Vec<_>>();
 
+        let total = scanners.len();
         let mut global_map = GlobalMap::new();
 
         // Use the first scanner as the origin. Everything will be relative to
         // the first scanner's orientation.
         global_map
             .scanners
-            .insert(Point((0, 0, 0)), scanners.remove(0));
+            .insert(Point((0, 0, 0)), scanners.remove(0).1);
 
+        let mut pass = 0;
         while !scanners.is_empty() {
+            cancel::check()?;
+            pass += 1;
+            let remaining = scanners.len();
+            log::trace!("pass {}: {} scanners left to merge", pass, remaining);
             for i in (0..scanners.len()).rev() {
-                if global_map.merge_scanner(&scanners[i]) {
-                    scanners.swap_remove(i);
+                let merged = match strategy {
+                    AlignStrategy::Signature => {
+                        global_map.merge_scanner_by_signature(&scanners[i].1)
+                    }
+                    _ => global_map.merge_scanner(&scanners[i].1),
+                };
+                if merged {
+                    let (number, _) = scanners.swap_remove(i);
+                    log::debug!(
+                        "merged scanner {} in pass {}, {} beacons known",
+                        number,
+                        pass,
+                        global_map.beacons().len()
+                    );
                 }
+                progress.report(|| {
+                    format!(
+                        "merged {}/{} scanners in pass {}",
+                        total - scanners.len(),
+                        total,
+                        pass
+                    )
+                });
+            }
+            if scanners.len() == remaining {
+                return Err(AocError::new(format!(
+                    "{} scanners do not overlap with any known scanner",
+                    remaining
+                )));
             }
         }
-        global_map
+        log::debug!("placed {} scanners in {} passes", total, pass);
+        Ok(global_map)
     }
 
-    pub fn merge_scanner(&mut self, scanner: &ScannerWithDistancesToBeacons) -> bool {
-        // 12 overlaps are needed between beacons in two beacon sets to be valid for merging.
-        const DESIRED_OVERLAPS: usize = 12;
+    /// Checks if the scanner's beacons, oriented and translated, overlap with
+    /// enough known beacons, returning the moved beacons if they do.
+    fn align(
+        known_scanner: &ScannerWithDistancesToBeacons,
+        scanner: &ScannerWithDistancesToBeacons,
+        transformation_matrix: &TransformationMatrix,
+        delta: &Point,
+    ) -> Option<BeaconSet> {
+        // Go ahead and perform all of the transformations now.
+        // You really only need to check for points that correspond to overlapping
+        // distances, but each scanner does not have that many points, so it does
+        // not cost much to go ahead and translate them all.
+        let all_oriented_beacons = scanner
+            .beacons
+            .iter()
+            .map(|beacon| &(transformation_matrix * beacon) + delta)
+            .collect::<BeaconSet>();
+
+        if all_oriented_beacons
+            .iter()
+            .filter(|beacon| known_scanner.beacons.contains(beacon))
+            .count()
+            >= DESIRED_OVERLAPS
+        {
+            Some(all_oriented_beacons)
+        } else {
+            None
+        }
+    }
+
+    fn merge_scanner(&mut self, scanner: &ScannerWithDistancesToBeacons) -> bool {
         lazy_static! {
             // To detect if 12 beacons will overlap with the global map, C(12,2) lines between
             // all of those beacons must have identical length with distances in the global map.
@@ -194,7 +305,7 @@ impl GlobalMap {
         let scanned_distances = scanner.distances.keys().copied().collect::<FxHashSet<_>>();
 
         // Find one known scanner that this scanner can be merged with.
-        for (_, known_scanner) in &self.scanners {
+        for known_scanner in self.scanners.values() {
             // Distances we know and have properly oriented for this known scanner.
             let known_distances = known_scanner
                 .distances
@@ -224,7 +335,7 @@ impl GlobalMap {
                                 *dist,
                                 scanner.distances[dist]
                                     .iter()
-                                    .map(|beacon| &transformation_matrix * &beacon)
+                                    .map(|beacon| &transformation_matrix * beacon)
                                     .collect::<Vec<_>>(),
                             )
                         })
@@ -243,25 +354,12 @@ impl GlobalMap {
                                 overlapping_distance_to_transformed_beacons[dist].iter(),
                             )
                         })
-                        .map(|(known_beacon, unknown_beacon)| known_beacon - &unknown_beacon)
+                        .map(|(known_beacon, unknown_beacon)| known_beacon - unknown_beacon)
                         .collect::<Vec<_>>();
 
                     for delta in potential_translations {
-                        // Go ahead and perform all of the transformations now.
-                        // You really only need to check for points that correspond to overlapping
-                        // distances, but each scanner does not have that many points, so it does
-                        // not cost much to go ahead and translate them all.
-                        let all_oriented_beacons = scanner
-                            .beacons
-                            .iter()
-                            .map(|beacon| &(&transformation_matrix * &beacon) + &delta)
-                            .collect::<FxHashSet<_>>();
-
-                        if all_oriented_beacons
-                            .iter()
-                            .filter(|beacon| known_scanner.beacons.contains(beacon))
-                            .count()
-                            >= DESIRED_OVERLAPS
+                        if let Some(all_oriented_beacons) =
+                            Self::align(known_scanner, scanner, &transformation_matrix, &delta)
                         {
                             // Insert the scanner's beacons with the proper orientation.
                             let scanner = Scanner {
@@ -279,22 +377,107 @@ impl GlobalMap {
         false
     }
 
-    pub fn beacons(&self) -> FxHashSet<&Point> {
+    /// Merges the scanner by pairing its beacons with known beacons directly.
+    ///
+    /// Two beacons seen by both scanners have the same signature in each, so
+    /// a signature shared by exactly one pair on each side pairs up two
+    /// beacons. When the differences along each axis are distinct and not
+    /// zero, only one orientation lines up the two pairs, which also gives
+    /// the translation. The other approach instead tries every orientation
+    /// with every translation it could imply.
+    fn merge_scanner_by_signature(&mut self, scanner: &ScannerWithDistancesToBeacons) -> bool {
+        lazy_static! {
+            static ref SIGNATURE_OVERLAPS: usize = combinations(DESIRED_OVERLAPS, 2);
+        }
+
+        let scanned_signatures = scanner.signatures();
+        for known_scanner in self.scanners.values() {
+            let known_signatures = known_scanner.signatures();
+            let overlapping_signatures = scanned_signatures
+                .iter()
+                .filter_map(|(signature, pairs)| {
+                    known_signatures
+                        .get(signature)
+                        .map(|known_pairs| (signature, pairs, known_pairs))
+                })
+                .collect::<Vec<_>>();
+            let overlaps = overlapping_signatures
+                .iter()
+                .map(|(_, pairs, known_pairs)| pairs.len().min(known_pairs.len()))
+                .sum::<usize>();
+            if overlaps < *SIGNATURE_OVERLAPS {
+                continue;
+            }
+
+            for (signature, pairs, known_pairs) in overlapping_signatures {
+                let unambiguous =
+                    signature[0] > 0 && signature[0] < signature[1] && signature[1] < signature[2];
+                if !unambiguous || pairs.len() != 1 || known_pairs.len() != 1 {
+                    continue;
+                }
+                let (a, b) = pairs[0];
+                let (known_a, known_b) = known_pairs[0];
+                let delta = &b - &a;
+                let known_delta = &known_b - &known_a;
+                for transformation_matrix in BeaconOrientationIterator::new() {
+                    let oriented_delta = &transformation_matrix * &delta;
+                    // The pair may be listed in either order on each side.
+                    let known_start = if oriented_delta == known_delta {
+                        known_a
+                    } else if oriented_delta == -&known_delta {
+                        known_b
+                    } else {
+                        continue;
+                    };
+                    let translation = &known_start - &(&transformation_matrix * &a);
+                    if let Some(beacons) =
+                        Self::align(known_scanner, scanner, &transformation_matrix, &translation)
+                    {
+                        let scanner = Scanner { beacons }.into_distances();
+                        self.scanners.insert(translation, scanner);
+                        return true;
+                    }
+                    // Only one orientation lines up the pair, so the pairing
+                    // was a coincidence.
+                    break;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn beacons(&self) -> FxHashSet<&Point> {
         self.scanners
             .values()
             .flat_map(|scanner| scanner.beacons.iter())
             .collect()
     }
 
-    pub fn scanners(&self) -> FxHashSet<&Point> {
+    fn scanners(&self) -> FxHashSet<&Point> {
         self.scanners.keys().collect()
     }
 }
 
+impl ScannerWithDistancesToBeacons {
+    /// Maps the signature of the line between every two beacons to the pairs
+    /// of beacons with that signature.
+    fn signatures(&self) -> FxHashMap<Signature, Vec<(Point, Point)>> {
+        let mut signatures = FxHashMap::<_, Vec<_>>::default();
+        for (a, b) in self.beacons.iter().tuple_combinations() {
+            signatures
+                .entry(signature(a, b))
+                .or_default()
+                .push((*a, *b));
+        }
+        signatures
+    }
+}
+
 impl Scanner {
     pub fn into_distances(self) -> ScannerWithDistancesToBeacons {
         let pairs = self.beacons.iter().tuple_combinations();
-        let mut distances = FxHashMap::default();
+        let mut distances = CountingMap::default();
         for (a, b) in pairs {
             let entry = distances.entry(a.distance(b)).or_insert(Vec::new());
             entry.push(*a);
@@ -307,22 +490,32 @@ impl Scanner {
     }
 }
 
-fn parse_input(input: &str) -> AocResult<Vec<Scanner>> {
+/// Parses the scanner reports.
+///
+/// A beacon listed twice by the same scanner is only kept once, but it
+/// usually means the input is corrupted, so it is reported.
+fn parse_input(input: &str, options: &ParseOptions) -> AocResult<Vec<Scanner>> {
     let mut scans = Vec::new();
     for line in input.lines() {
         if line.starts_with("---") {
             scans.push(Scanner {
-                beacons: FxHashSet::default(),
+                beacons: BeaconSet::default(),
             });
         } else if !line.is_empty() {
             let mut nums = line
                 .split(',')
                 .map(|num| num.parse::<i32>().into_aoc_result());
-            scans.last_mut().into_aoc_result()?.beacons.insert(Point((
+            let beacon = Point((
                 nums.next().into_aoc_result()??,
                 nums.next().into_aoc_result()??,
                 nums.next().into_aoc_result()??,
-            )));
+            ));
+            options.expect_end(nums, "beacon coordinates")?;
+            let scanner = scans.len().checked_sub(1).into_aoc_result()?;
+            options.check(
+                scans[scanner].beacons.insert(beacon),
+                format!("scanner {} lists beacon {} more than once", scanner, line),
+            )?;
         }
     }
     Ok(scans)
@@ -386,8 +579,8 @@ impl Iterator for BeaconOrientationIterator {
         let mut second = Self::IDENTITY[self.j];
 
         let mut third = {
-            let next_i = (self.i + 1).mod_floor(&3);
-            let next_j = (self.j + 1).mod_floor(&3);
+            let next_i = math::modadd(self.i, 1, 3);
+            let next_j = math::modadd(self.j, 1, 3);
             if next_i == self.j {
                 Self::IDENTITY[next_j]
             } else {
@@ -431,22 +624,227 @@ fn combinations<I: Integer + Unsigned + Clone + Copy + num::ToPrimitive + std::i
         .div_floor(&factorial(r))
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let scanners = parse_input(input)?;
-    let global_map = GlobalMap::from_scanners(scanners);
-    Ok(global_map.beacons().len() as iAoc)
+/// Strategy used to find how each scanner is oriented and positioned.
+#[derive(Clone, Copy)]
+enum AlignStrategy {
+    /// Matches distances between beacons, then tries every orientation.
+    Distance,
+    /// Pairs beacons by orientation-invariant signatures, which gives the
+    /// orientation directly.
+    Signature,
+    /// Runs both strategies and checks that they build the same map.
+    CrossCheck,
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let scanners = parse_input(input)?;
-    let global_map = GlobalMap::from_scanners(scanners);
+impl FromStr for AlignStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "distance" => Ok(Self::Distance),
+            "signature" => Ok(Self::Signature),
+            "cross-check" => Ok(Self::CrossCheck),
+            _ => Err(AocError::new(
+                "strategy must be distance, signature, or cross-check",
+            )),
+        }
+    }
+}
 
-    let result = global_map
-        .scanners()
-        .iter()
-        .tuple_combinations()
-        .map(|(from, to)| from.distance(to))
+/// Builds the global map with the requested strategy.
+fn build_map(context: &SolverContext, scanners: Vec<Scanner>) -> AocResult<GlobalMap> {
+    let strategy = context.strategy_or(AlignStrategy::Distance)?;
+    if let AlignStrategy::CrossCheck = strategy {
+        let by_signature = GlobalMap::from_scanners(
+            scanners.clone(),
+            AlignStrategy::Signature,
+            context.progress(),
+        )?;
+        let global_map =
+            GlobalMap::from_scanners(scanners, AlignStrategy::Distance, context.progress())?;
+        if by_signature.scanners() != global_map.scanners() {
+            return Err(AocError::new(
+                "signature strategy placed scanners differently",
+            ));
+        }
+        if by_signature.beacons() != global_map.beacons() {
+            return Err(AocError::new("signature strategy found different beacons"));
+        }
+        return Ok(global_map);
+    }
+    GlobalMap::from_scanners(scanners, strategy, context.progress())
+}
+
+/// Projects a point onto a plane.
+#[cfg(feature = "visualize")]
+type Projection = fn(&Point) -> (i32, i32);
+
+/// Writes the XY, XZ, and YZ projections of the beacons and scanners side by
+/// side if the `projections` parameter is set.
+#[cfg(feature = "visualize")]
+fn write_projections(context: &SolverContext, part: &str, global_map: &GlobalMap) -> AocResult<()> {
+    let enabled = match context.param("projections") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("projections must be true or false")?,
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    const PANEL: f64 = 400.0;
+    const MARGIN: f64 = 30.0;
+    let beacons = global_map.beacons();
+    let scanners = global_map.scanners();
+    let points = || beacons.iter().chain(scanners.iter());
+    let min = points()
+        .flat_map(|point| [point.0 .0, point.0 .1, point.0 .2])
+        .min()
+        .unwrap_or(0);
+    let max = points()
+        .flat_map(|point| [point.0 .0, point.0 .1, point.0 .2])
         .max()
-        .into_aoc_result()?;
-    Ok(result as iAoc)
+        .unwrap_or(0);
+    // Every panel uses the same scale, so distances can be compared across them.
+    let scale = (PANEL - 2.0 * MARGIN) / (max - min).max(1) as f64;
+
+    let mut document = Document::new(3.0 * PANEL, PANEL);
+    let projections: [(&str, Projection); 3] = [
+        ("XY", |point| (point.0 .0, point.0 .1)),
+        ("XZ", |point| (point.0 .0, point.0 .2)),
+        ("YZ", |point| (point.0 .1, point.0 .2)),
+    ];
+    for (i, (label, project)) in projections.iter().enumerate() {
+        let left = i as f64 * PANEL;
+        // SVG coordinates grow downward, so the vertical axis is flipped.
+        let to_plot = |point: &Point| {
+            let (u, v) = project(point);
+            (
+                left + MARGIN + (u - min) as f64 * scale,
+                MARGIN + (max - v) as f64 * scale,
+            )
+        };
+        document.rect(
+            left,
+            0.0,
+            PANEL,
+            PANEL,
+            if i % 2 == 0 { "white" } else { "whitesmoke" },
+        );
+        document.text(left + MARGIN, MARGIN / 2.0, 16.0, label);
+        for beacon in &beacons {
+            let (x, y) = to_plot(beacon);
+            document.circle(x, y, 1.5, "gray");
+        }
+        for scanner in &scanners {
+            let (x, y) = to_plot(scanner);
+            document.circle(x, y, 4.0, "red");
+        }
+    }
+    let name = format!("19.{}.projections.svg", part);
+    document.write(context.output(), &name)
+}
+
+#[cfg(not(feature = "visualize"))]
+fn write_projections(context: &SolverContext, _: &str, _: &GlobalMap) -> AocResult<()> {
+    context.reject_param("projections", "visualize")
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "distance",
+            description: "Matches distances between beacons, then tries every orientation and translation they imply.",
+        },
+        StrategyInfo {
+            name: "signature",
+            description: "Pairs beacons by the sorted absolute differences between them, which gives the orientation directly.",
+        },
+        StrategyInfo {
+            name: "cross-check",
+            description: "Builds the map with both strategies and checks that they agree.",
+        },
+    ],
+    params: &[ParamInfo {
+        name: "projections",
+        values: "true|false",
+        description: "Plots the XY, XZ, and YZ projections of the beacons and scanners to output/19.<part>.projections.svg.",
+        default: "false",
+    }],
+    animated: false,
+};
+
+pub struct BeaconScanner;
+
+impl Solver for BeaconScanner {
+    type Parsed = Vec<Scanner>;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<Vec<Scanner>> {
+        parse_input(input, context.parse_options())
+    }
+
+    fn part_a(scanners: &Vec<Scanner>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let global_map = build_map(context, scanners.clone())?;
+        write_projections(context, "A", &global_map)?;
+        Ok(SolutionValue::from(global_map.beacons().len() as iAoc))
+    }
+
+    fn part_b(scanners: &Vec<Scanner>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let global_map = build_map(context, scanners.clone())?;
+        write_projections(context, "B", &global_map)?;
+
+        let result = global_map
+            .scanners()
+            .iter()
+            .tuple_combinations()
+            .map(|(from, to)| from.distance(to))
+            .max()
+            .into_aoc_result()?;
+        Ok(SolutionValue::from(result as iAoc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{Warning, Warnings};
+    use std::sync::Arc;
+
+    const DUPLICATED_BEACON: &str = "--- scanner 0 ---\n1,2,3\n4,5,6\n1,2,3\n";
+    const EXAMPLE: &str = include_str!("../../input/test/19.1.txt");
+
+    #[test]
+    fn distance_and_signature_build_the_same_map() {
+        let scanners = parse_input(EXAMPLE, &ParseOptions::default()).unwrap();
+        let progress = ProgressSink::new(None);
+        let by_distance =
+            GlobalMap::from_scanners(scanners.clone(), AlignStrategy::Distance, &progress).unwrap();
+        let by_signature =
+            GlobalMap::from_scanners(scanners, AlignStrategy::Signature, &progress).unwrap();
+        assert_eq!(by_distance.scanners().len(), 5);
+        assert_eq!(by_distance.beacons().len(), 79);
+        assert_eq!(by_signature.scanners(), by_distance.scanners());
+        assert_eq!(by_signature.beacons(), by_distance.beacons());
+    }
+
+    #[test]
+    fn lenient_parsing_keeps_one_copy_of_a_duplicated_beacon() {
+        let warnings = Arc::new(Warnings::default());
+        let options = ParseOptions::new(false, Arc::clone(&warnings));
+        let scanners = parse_input(DUPLICATED_BEACON, &options).unwrap();
+        assert_eq!(scanners.len(), 1);
+        assert_eq!(scanners[0].beacons.len(), 2);
+        assert_eq!(
+            warnings.take(),
+            vec![Warning::new("scanner 0 lists beacon 1,2,3 more than once")]
+        );
+    }
+
+    #[test]
+    fn strict_parsing_rejects_a_duplicated_beacon() {
+        let options = ParseOptions::new(true, Arc::default());
+        let err = parse_input(DUPLICATED_BEACON, &options).err().unwrap();
+        assert_eq!(err.message(), "scanner 0 lists beacon 1,2,3 more than once");
+    }
 }
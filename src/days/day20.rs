@@ -1,5 +1,12 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
-use itertools::Itertools;
+use crate::common::animate::Animator;
+use crate::common::grid::DoubleBuffer;
+use crate::common::parallel;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, SolutionValue, Solver, SolverContext,
+    StrategyInfo,
+};
+use rustc_hash::FxHashSet;
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 use std::str::FromStr;
 
 type Point = (usize, usize);
@@ -26,6 +33,7 @@ const SQUARE: [(isize, isize); 9] = [
 /// to one position in the vector, and its boolean value represents if it is lit or
 /// not. If the image is not inverted, a true value represents a lit pixel. If the
 /// image is inverted, a true value represents an dark pixel.
+#[derive(Clone)]
 struct Image {
     pixels: Vec<bool>,
     /// Height of the image.
@@ -46,12 +54,17 @@ impl Image {
         }
     }
 
-    pub fn is_inverted(&self) -> bool {
-        self.inverted
+    /// Resizes the image, reusing its pixel buffer. Every pixel is unset.
+    pub fn reset(&mut self, height: usize, width: usize, inverted: bool) {
+        self.pixels.clear();
+        self.pixels.resize(height * width, false);
+        self.height = height;
+        self.width = width;
+        self.inverted = inverted;
     }
 
-    pub fn pixels(&self) -> impl Iterator<Item = Point> {
-        (0..self.width).cartesian_product(0..self.height)
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
     }
 
     /// Maps a two-dimensional point to a flat index.
@@ -82,7 +95,7 @@ impl Image {
     pub fn is_lit(&self, pixel: Point) -> bool {
         self.pixels
             .get(self.get_index(pixel))
-            .and_then(|&b| Some(b != self.inverted))
+            .map(|&b| b != self.inverted)
             .unwrap_or(self.inverted)
     }
 
@@ -93,6 +106,86 @@ impl Image {
             self.pixels.iter().filter(|&b| *b).count()
         }
     }
+
+    /// Fraction of pixels that differ from the infinite background.
+    pub fn density(&self) -> f64 {
+        let set = self.pixels.iter().filter(|&b| *b).count();
+        set as f64 / self.pixels.len().max(1) as f64
+    }
+}
+
+type SparsePoint = (i32, i32);
+
+/// An image stored as the set of pixels that differ from its background.
+///
+/// This is the original representation, which pays off when few pixels
+/// differ from the background. Only pixels near a stored pixel can change
+/// when the image is enhanced, so each step costs time proportional to the
+/// number of stored pixels rather than to the area of the image.
+struct SparseImage {
+    /// Pixels whose value is the opposite of the background.
+    pixels: FxHashSet<SparsePoint>,
+    /// Is every pixel outside of `pixels` lit?
+    background: bool,
+    /// Smallest corner of the region the image has been enhanced over.
+    min: SparsePoint,
+    /// Largest corner of the region the image has been enhanced over.
+    max: SparsePoint,
+}
+
+impl SparseImage {
+    pub fn from_image(image: &Image) -> Self {
+        let mut pixels = FxHashSet::default();
+        for y in 0..image.height {
+            for x in 0..image.width {
+                if image.is_lit((x, y)) != image.is_inverted() {
+                    pixels.insert((x as i32, y as i32));
+                }
+            }
+        }
+        SparseImage {
+            pixels,
+            background: image.is_inverted(),
+            min: (0, 0),
+            max: (image.width as i32 - 1, image.height as i32 - 1),
+        }
+    }
+
+    pub fn is_lit(&self, pixel: &SparsePoint) -> bool {
+        self.pixels.contains(pixel) != self.background
+    }
+
+    pub fn lit_pixels(&self) -> usize {
+        if self.background {
+            usize::MAX
+        } else {
+            self.pixels.len()
+        }
+    }
+}
+
+impl Display for SparseImage {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        for y in self.min.1..=self.max.1 {
+            for x in self.min.0..=self.max.0 {
+                write!(f, "{}", if self.is_lit(&(x, y)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Image {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                write!(f, "{}", if self.is_lit((x, y)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
 
 struct ImageEnhancementAlgorithm {
@@ -113,9 +206,34 @@ impl ImageEnhancementAlgorithm {
         self.bits[bit >> 6] |= 1 << (bit & ((1 << 6) - 1));
     }
 
-    pub fn enhance_once(&self, image: Image) -> Image {
+    /// Calculates the index into the algorithm for a single pixel of the enhanced image.
+    fn algorithm_index(image: &Image, center: Point) -> usize {
+        // A pixel in the expanded image is (-1, -1) off from the same pixel
+        // in the original image.
+        //
+        // So when appliyng the transformation, also subtract an additional unit.
+        SQUARE
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(dx, dy))| {
+                let pixel = (
+                    center.0.overflowing_add((dx - 1) as usize).0,
+                    center.1.overflowing_add((dy - 1) as usize).0,
+                );
+                if image.is_lit(pixel) {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .fold(0usize, |acc, bit| acc | (1 << (8 - bit)))
+    }
+
+    /// Enhances the image once into `new_image`, splitting rows of the
+    /// enhanced image across the given number of threads.
+    pub fn enhance_once(&self, image: &Image, new_image: &mut Image, threads: usize) {
         // Enhanced image extends one unit in all four directions.
-        let mut new_image = Image::new(
+        new_image.reset(
             image.height + 2,
             image.width + 2,
             if image.is_inverted() {
@@ -125,49 +243,200 @@ impl ImageEnhancementAlgorithm {
             },
         );
 
-        // Check all pixels in the expanded image.
-        for center in new_image.pixels() {
-            // A pixel in the expanded image is (-1, -1) off from the same pixel
-            // in the original image.
-            //
-            // So when appliyng the transformation, also subtract an additional unit.
-            let algorithm_index = SQUARE
-                .iter()
-                .enumerate()
-                .filter_map(|(i, &(dx, dy))| {
-                    let pixel = (
-                        center.0.overflowing_add((dx - 1) as usize).0,
-                        center.1.overflowing_add((dy - 1) as usize).0,
-                    );
-                    if image.is_lit(pixel) {
-                        Some(i)
-                    } else {
-                        None
-                    }
-                })
-                .fold(0usize, |acc, bit| acc | (1 << (8 - bit)));
-            if self.get(algorithm_index) != new_image.is_inverted() {
-                new_image.set(center);
+        // Check all pixels in the expanded image. Every pixel only depends on
+        // the original image, so chunks of pixels can be enhanced independently.
+        let width = new_image.width;
+        let inverted = new_image.is_inverted();
+        parallel::for_each_chunk_mut(&mut new_image.pixels, threads, |start, chunk| {
+            for (offset, pixel) in chunk.iter_mut().enumerate() {
+                let index = start + offset;
+                let center = (index % width, index / width);
+                *pixel = self.get(Self::algorithm_index(image, center)) != inverted;
             }
-        }
+        });
+    }
 
-        new_image
+    /// Enhances a sparse image once.
+    pub fn enhance_sparse_once(&self, image: SparseImage) -> SparseImage {
+        let background = self.get(if image.background { 0b111111111 } else { 0 });
+
+        // A pixel with no stored pixel around it only sees the background, so
+        // it becomes the new background. Only pixels around stored pixels
+        // need to be checked.
+        let candidates = image
+            .pixels
+            .iter()
+            .flat_map(|&(x, y)| {
+                SQUARE
+                    .iter()
+                    .map(move |&(dx, dy)| (x + dx as i32, y + dy as i32))
+            })
+            .collect::<FxHashSet<_>>();
+        let pixels = candidates
+            .into_iter()
+            .filter(|&(x, y)| {
+                let index = SQUARE.iter().fold(0, |acc, &(dx, dy)| {
+                    (acc << 1) | image.is_lit(&(x + dx as i32, y + dy as i32)) as usize
+                });
+                self.get(index) != background
+            })
+            .collect();
+
+        SparseImage {
+            pixels,
+            background,
+            min: (image.min.0 - 1, image.min.1 - 1),
+            max: (image.max.0 + 1, image.max.1 + 1),
+        }
     }
 
-    pub fn enhance(&self, mut image: Image, times: usize) -> Image {
-        if times == 0 {
-            return image;
+    /// Enhances a sparse image the given number of times, animating each step.
+    pub fn enhance_sparse(
+        &self,
+        mut image: SparseImage,
+        times: usize,
+        animator: &mut Animator,
+    ) -> AocResult<SparseImage> {
+        animator.frame(|| image.to_string())?;
+        for _ in 0..times {
+            image = self.enhance_sparse_once(image);
+            animator.frame(|| image.to_string())?;
         }
 
+        Ok(image)
+    }
+
+    /// Enhances the image the given number of times, animating each step.
+    ///
+    /// Each step writes into the image from two steps before, so the pixel
+    /// buffers are reused rather than allocated for every step.
+    pub fn enhance(
+        &self,
+        image: Image,
+        times: usize,
+        threads: usize,
+        animator: &mut Animator,
+    ) -> AocResult<Image> {
+        let mut images = DoubleBuffer::new(image, Image::new(0, 0, false));
+        animator.frame(|| images.read().to_string())?;
         for _ in 0..times {
-            image = self.enhance_once(image);
+            let (image, new_image) = images.write();
+            self.enhance_once(image, new_image, threads);
+            images.swap();
+            animator.frame(|| images.read().to_string())?;
+        }
+
+        Ok(images.into_inner())
+    }
+}
+
+/// Two enhancement steps composed into one, which finds the value of a pixel
+/// two steps later from its 5x5 neighborhood.
+///
+/// A lookup table over every 5x5 neighborhood would have 2^25 entries, and
+/// nearly every neighborhood in an input image is different, so filling one
+/// in costs more than it saves. Instead, each of the nine pixels in between
+/// is looked up in the algorithm directly from the bits of the neighborhood.
+struct TwoStepAlgorithm<'a> {
+    algorithm: &'a ImageEnhancementAlgorithm,
+}
+
+impl<'a> TwoStepAlgorithm<'a> {
+    pub fn new(algorithm: &'a ImageEnhancementAlgorithm) -> Self {
+        TwoStepAlgorithm { algorithm }
+    }
+
+    /// Enhances a 5x5 neighborhood twice, where the neighborhood is given as
+    /// five rows of five bits from top to bottom, with the leftmost pixel of
+    /// each row as its highest bit.
+    pub fn get(&self, rows: &[usize; 5]) -> bool {
+        let mut index = 0;
+        for top in 0..3 {
+            for shift in (0..3).rev() {
+                let inner = rows[top..top + 3]
+                    .iter()
+                    .fold(0, |acc, row| (acc << 3) | ((row >> shift) & 0b111));
+                index = (index << 1) | self.algorithm.get(inner) as usize;
+            }
         }
+        self.algorithm.get(index)
+    }
+
+    /// Enhances the image twice into `new_image` in a single pass, splitting
+    /// rows of the enhanced image across the given number of threads.
+    ///
+    /// Each pixel's neighborhood is kept as five rows of five bits, which
+    /// slide one column to the right from one pixel to the next, so every
+    /// pixel only reads the five new pixels of its neighborhood.
+    pub fn enhance_twice(&self, image: &Image, new_image: &mut Image, threads: usize) {
+        let background =
+            |inverted: bool| self.algorithm.get(if inverted { 0b111111111 } else { 0 });
+        new_image.reset(
+            image.height + 4,
+            image.width + 4,
+            background(background(image.is_inverted())),
+        );
 
-        image
+        // A pixel in the twice-expanded image is (-2, -2) off from the same
+        // pixel in the original image, so its neighborhood ends at its own
+        // position in the original image.
+        let width = new_image.width;
+        let inverted = new_image.is_inverted();
+        let column = |x: usize, y: usize| {
+            (0..5).map(move |row| image.is_lit((x, y.wrapping_add(row).wrapping_sub(4))) as usize)
+        };
+        parallel::for_each_chunk_mut(&mut new_image.pixels, threads, |start, chunk| {
+            let mut rows = [0usize; 5];
+            for (offset, pixel) in chunk.iter_mut().enumerate() {
+                let index = start + offset;
+                let (x, y) = (index % width, index / width);
+                if offset == 0 || x == 0 {
+                    rows = [0; 5];
+                    for dx in (1..5).rev() {
+                        for (row, lit) in rows.iter_mut().zip(column(x.wrapping_sub(dx), y)) {
+                            *row = (*row << 1) | lit;
+                        }
+                    }
+                }
+                for (row, lit) in rows.iter_mut().zip(column(x, y)) {
+                    *row = ((*row << 1) | lit) & 0b11111;
+                }
+                *pixel = self.get(&rows) != inverted;
+            }
+        });
+    }
+
+    /// Enhances the image the given number of times, two steps per pass,
+    /// animating each pass.
+    ///
+    /// An odd number of steps finishes with a single step.
+    pub fn enhance(
+        &self,
+        image: Image,
+        times: usize,
+        threads: usize,
+        animator: &mut Animator,
+    ) -> AocResult<Image> {
+        let mut images = DoubleBuffer::new(image, Image::new(0, 0, false));
+        animator.frame(|| images.read().to_string())?;
+        for _ in 0..times / 2 {
+            let (image, new_image) = images.write();
+            self.enhance_twice(image, new_image, threads);
+            images.swap();
+            animator.frame(|| images.read().to_string())?;
+        }
+        if times % 2 == 1 {
+            let (image, new_image) = images.write();
+            self.algorithm.enhance_once(image, new_image, threads);
+            images.swap();
+            animator.frame(|| images.read().to_string())?;
+        }
+
+        Ok(images.into_inner())
     }
 }
 
-struct ImageEnhancement {
+pub struct ImageEnhancement {
     algorithm: ImageEnhancementAlgorithm,
     image: Image,
 }
@@ -202,14 +471,201 @@ impl FromStr for ImageEnhancement {
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let ImageEnhancement { algorithm, image } = ImageEnhancement::from_str(input)?;
-    let enhanced_image = algorithm.enhance(image, 2);
-    Ok(enhanced_image.lit_pixels() as iAoc)
+/// How the image is stored while it is enhanced.
+#[derive(Clone, Copy)]
+enum ImageStrategy {
+    /// Picks sparse or dense based on the density of the input image.
+    Auto,
+    /// A flat vector covering every pixel.
+    Dense,
+    /// A set of the pixels that differ from the background.
+    Sparse,
+    /// A flat vector, enhanced two steps at a time with a composed lookup.
+    TwoStep,
+    /// Runs dense and two-step and fails if they disagree.
+    CrossCheck,
+}
+
+impl FromStr for ImageStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "auto" => Ok(Self::Auto),
+            "dense" => Ok(Self::Dense),
+            "sparse" => Ok(Self::Sparse),
+            "two-step" => Ok(Self::TwoStep),
+            "cross-check" => Ok(Self::CrossCheck),
+            _ => Err(AocError::new(
+                "strategy must be auto, dense, sparse, two-step, or cross-check",
+            )),
+        }
+    }
+}
+
+/// Below this fraction of set pixels, the auto strategy stores images
+/// sparsely.
+const SPARSE_DENSITY: f64 = 0.05;
+
+/// Enhances the input image the given number of times and counts the lit
+/// pixels.
+fn count_lit_pixels(
+    enhancement: &ImageEnhancement,
+    context: &SolverContext,
+    part: &str,
+    times: usize,
+) -> AocResult<SolutionValue> {
+    let algorithm = &enhancement.algorithm;
+    let image = enhancement.image.clone();
+    let strategy = match context.strategy_or(ImageStrategy::Auto)? {
+        ImageStrategy::Auto if image.density() < SPARSE_DENSITY => ImageStrategy::Sparse,
+        ImageStrategy::Auto => ImageStrategy::Dense,
+        strategy => strategy,
+    };
+    let mut animator = Animator::new(context, &format!("20.{}", part))?;
+    let lit_pixels = match strategy {
+        ImageStrategy::Sparse => algorithm
+            .enhance_sparse(SparseImage::from_image(&image), times, &mut animator)?
+            .lit_pixels(),
+        ImageStrategy::TwoStep => TwoStepAlgorithm::new(algorithm)
+            .enhance(image, times, context.threads(), &mut animator)?
+            .lit_pixels(),
+        ImageStrategy::CrossCheck => {
+            let dense =
+                algorithm.enhance(image.clone(), times, context.threads(), &mut animator)?;
+            let two_step = TwoStepAlgorithm::new(algorithm).enhance(
+                image,
+                times,
+                context.threads(),
+                &mut animator,
+            )?;
+            if dense.pixels != two_step.pixels || dense.is_inverted() != two_step.is_inverted() {
+                return Err(AocError::new(format!(
+                    "enhancing two steps at a time gave a different image than one step at a time, with {} lit pixels instead of {}",
+                    two_step.lit_pixels(),
+                    dense.lit_pixels()
+                )));
+            }
+            dense.lit_pixels()
+        }
+        _ => algorithm
+            .enhance(image, times, context.threads(), &mut animator)?
+            .lit_pixels(),
+    };
+    Ok(SolutionValue::from(lit_pixels as iAoc))
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "auto",
+            description: "Uses the sparse image if less than 5% of the input image is lit, and the dense image otherwise.",
+        },
+        StrategyInfo {
+            name: "dense",
+            description: "Stores every pixel in a flat vector, enhanced across threads.",
+        },
+        StrategyInfo {
+            name: "sparse",
+            description: "Stores only the pixels that differ from the infinite background.",
+        },
+        StrategyInfo {
+            name: "two-step",
+            description: "Stores every pixel in a flat vector and enhances it two steps per pass, looking each pixel's 5x5 neighborhood up in the composition of two steps.",
+        },
+        StrategyInfo {
+            name: "cross-check",
+            description: "Runs dense and two-step and fails if their images differ.",
+        },
+    ],
+    params: &[],
+    animated: true,
+};
+
+pub struct TrenchMap;
+
+impl Solver for TrenchMap {
+    type Parsed = ImageEnhancement;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<ImageEnhancement> {
+        ImageEnhancement::from_str(input)
+    }
+
+    fn part_a(enhancement: &ImageEnhancement, context: &SolverContext) -> AocResult<SolutionValue> {
+        count_lit_pixels(enhancement, context, "A", 2)
+    }
+
+    fn part_b(enhancement: &ImageEnhancement, context: &SolverContext) -> AocResult<SolutionValue> {
+        count_lit_pixels(enhancement, context, "B", 50)
+    }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let ImageEnhancement { algorithm, image } = ImageEnhancement::from_str(input)?;
-    let enhanced_image = algorithm.enhance(image, 50);
-    Ok(enhanced_image.lit_pixels() as iAoc)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    const EXAMPLE: &str = include_str!("../../input/test/20.1.txt");
+    /// The real input, whose algorithm lights every pixel of an empty
+    /// neighborhood, so the background flips on every step.
+    const INPUT: &str = include_str!("../../input/20.txt");
+
+    /// Enhances the image with every strategy, checking that they light the
+    /// same pixels, and returns how many are lit.
+    fn lit_pixels_agree(input: &str, times: usize, threads: usize) -> usize {
+        let ImageEnhancement { algorithm, image } = ImageEnhancement::from_str(input).unwrap();
+        let context = SolverContext::default();
+        let mut animator = Animator::new(&context, "20.test").unwrap();
+        let dense = algorithm
+            .enhance(image.clone(), times, threads, &mut animator)
+            .unwrap();
+        let two_step = TwoStepAlgorithm::new(&algorithm)
+            .enhance(image.clone(), times, threads, &mut animator)
+            .unwrap();
+        let sparse = algorithm
+            .enhance_sparse(SparseImage::from_image(&image), times, &mut animator)
+            .unwrap();
+        assert!(
+            dense.pixels == two_step.pixels && dense.is_inverted() == two_step.is_inverted(),
+            "dense and two-step differ after {} steps on {} threads",
+            times,
+            threads
+        );
+        assert_eq!(
+            sparse.lit_pixels(),
+            dense.lit_pixels(),
+            "sparse and dense differ after {} steps on {} threads",
+            times,
+            threads
+        );
+        dense.lit_pixels()
+    }
+
+    #[test]
+    fn examples() {
+        for strategy in ["auto", "dense", "sparse", "two-step", "cross-check"] {
+            assert_solves!(TrenchMap::part_a, EXAMPLE, 35, strategy: strategy);
+            assert_solves!(TrenchMap::part_b, EXAMPLE, 3351, strategy: strategy);
+        }
+    }
+
+    #[test]
+    fn strategies_agree_on_the_example() {
+        for threads in [1, 4] {
+            assert_eq!(lit_pixels_agree(EXAMPLE, 2, threads), 35);
+            assert_eq!(lit_pixels_agree(EXAMPLE, 50, threads), 3351);
+            for times in [0, 1, 3, 7] {
+                lit_pixels_agree(EXAMPLE, times, threads);
+            }
+        }
+    }
+
+    #[test]
+    fn strategies_agree_with_a_flipping_background() {
+        for threads in [1, 4] {
+            for times in [1, 2, 3] {
+                lit_pixels_agree(INPUT, times, threads);
+            }
+        }
+    }
 }
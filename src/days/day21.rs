@@ -1,6 +1,11 @@
-use crate::common::{iAoc, AocResult, IntoAocResult};
+use crate::common::math::OverflowPolicy;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, ParseOptions, SolutionValue,
+    Solver, SolverContext,
+};
 use itertools::Itertools;
 use std::collections::HashMap;
+use std::str::FromStr;
 
 #[derive(Clone, Copy)]
 struct PlayerState {
@@ -14,23 +19,62 @@ impl PlayerState {
     }
 }
 
+/// A single turn of the practice game.
+struct Turn {
+    player: u8,
+    rolls: [usize; PracticeDiracDie::ROLLS_PER_TURN],
+    /// Space the player moved to, from 1 to 10.
+    space: u8,
+    score: usize,
+}
+
+/// A die that rolls 1, 2, 3, and so on up to its number of sides, then
+/// starts over at 1.
+struct DeterministicDie {
+    sides: usize,
+    /// Face shown by the next roll, from 1 to `sides`.
+    next_face: usize,
+    times_rolled: usize,
+}
+
+impl DeterministicDie {
+    pub fn new(sides: usize) -> Self {
+        DeterministicDie {
+            sides,
+            next_face: 1,
+            times_rolled: 0,
+        }
+    }
+
+    pub fn roll(&mut self) -> usize {
+        let face = self.next_face;
+        self.next_face = if face == self.sides { 1 } else { face + 1 };
+        self.times_rolled += 1;
+        face
+    }
+
+    pub fn times_rolled(&self) -> usize {
+        self.times_rolled
+    }
+}
+
 struct PracticeDiracDie {
     p1: PlayerState,
     p2: PlayerState,
-    rolls: usize,
+    die: DeterministicDie,
     p1_turn: bool,
 }
 
 impl PracticeDiracDie {
     const SPACES: u8 = 10;
     const ROLLS_PER_TURN: usize = 3;
-    const MAX_ROLL: usize = 100;
+    const DIE_SIDES: usize = 100;
 
     pub fn new(p1_pos: u8, p2_pos: u8) -> Self {
         PracticeDiracDie {
             p1: PlayerState::new((p1_pos - 1) % Self::SPACES),
             p2: PlayerState::new((p2_pos - 1) % Self::SPACES),
-            rolls: 0,
+            die: DeterministicDie::new(Self::DIE_SIDES),
             p1_turn: true,
         }
     }
@@ -50,7 +94,7 @@ impl PracticeDiracDie {
     }
 
     pub fn times_rolled(&self) -> usize {
-        self.rolls
+        self.die.times_rolled()
     }
 
     fn next_player(&mut self) -> &mut PlayerState {
@@ -61,27 +105,36 @@ impl PracticeDiracDie {
         }
     }
 
-    fn roll(&mut self) -> usize {
-        let total = Self::ROLLS_PER_TURN
-            + (0..Self::ROLLS_PER_TURN)
-                .map(|i| (self.rolls + i) % Self::MAX_ROLL)
-                .sum::<usize>();
-        self.rolls += Self::ROLLS_PER_TURN;
-        total
+    fn roll(&mut self) -> [usize; Self::ROLLS_PER_TURN] {
+        let die = &mut self.die;
+        [(); Self::ROLLS_PER_TURN].map(|_| die.roll())
     }
 
     pub fn play(&mut self) {
+        self.play_with(|_| ())
+    }
+
+    /// Plays the game, calling `on_turn` after every turn.
+    pub fn play_with<F: FnMut(Turn)>(&mut self, mut on_turn: F) {
         while !self.done() {
-            let roll = self.roll();
+            let rolls = self.roll();
+            let player_number = if self.p1_turn { 1 } else { 2 };
             let player = self.next_player();
-            player.pos = ((player.pos as usize + roll) % (Self::SPACES as usize)) as u8;
+            let moves = rolls.iter().sum::<usize>();
+            player.pos = ((player.pos as usize + moves) % (Self::SPACES as usize)) as u8;
             player.points += (player.pos + 1) as usize;
+            on_turn(Turn {
+                player: player_number,
+                rolls,
+                space: player.pos + 1,
+                score: player.points,
+            });
             self.p1_turn = !self.p1_turn;
         }
     }
 }
 
-fn parse_positions(input: &str) -> AocResult<(u8, u8)> {
+fn parse_positions(input: &str, options: &ParseOptions) -> AocResult<(u8, u8)> {
     let mut lines = input.lines();
     let first = lines
         .next()
@@ -99,16 +152,60 @@ fn parse_positions(input: &str) -> AocResult<(u8, u8)> {
         .1
         .parse::<u8>()
         .into_aoc_result()?;
-    Ok((first, second))
+    options.expect_end(lines.filter(|line| !line.is_empty()), "starting positions")?;
+    // Positions off the board are wrapped around it, just like moves are.
+    let wrap = |position: u8| -> AocResult<u8> {
+        options.check(
+            (1..=10).contains(&position),
+            format!("starting position {} is not on the board", position),
+        )?;
+        Ok(((position as u16 + 9) % 10 + 1) as u8)
+    };
+    Ok((wrap(first)?, wrap(second)?))
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let (p1, p2) = parse_positions(input)?;
-    let mut game = PracticeDiracDie::new(p1, p2);
-    game.play();
-    let losing_score =
-        game.loser().into_aoc_result_msg("no losing player")?.points * game.times_rolled();
-    Ok(losing_score as iAoc)
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[
+        ParamInfo {
+            name: "replay",
+            values: "true|false",
+            description: "Writes every turn of the part A practice game to output/21.A.replay.txt.",
+            default: "false",
+        },
+        ParamInfo {
+            name: "histogram",
+            values: "lengths|margins",
+            description: "Writes a histogram of part B universes by game length or win margin to output/21.B.<kind>.txt.",
+            default: "no histogram",
+        },
+    ],
+    animated: false,
+};
+
+/// Plays the practice game, writing a log of every turn in the style of the
+/// puzzle's worked example if the `replay` parameter is set.
+fn play_practice_game(context: &SolverContext, game: &mut PracticeDiracDie) -> AocResult<()> {
+    let enabled = match context.param("replay") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("replay must be true or false")?,
+    };
+    if !enabled {
+        game.play();
+        return Ok(());
+    }
+
+    let mut text = String::new();
+    game.play_with(|turn| {
+        let rolls = turn.rolls.iter().map(|roll| roll.to_string()).join("+");
+        text += &format!(
+            "Player {} rolls {} and moves to space {} for a total score of {}.\n",
+            turn.player, rolls, turn.space, turn.score
+        );
+    });
+    context.output().write("21.A.replay.txt", text.as_bytes())
 }
 
 /// A bitwise representation of the game state.
@@ -142,6 +239,7 @@ enum Player {
     Player2 = 1,
 }
 
+#[allow(clippy::unusual_byte_groupings)]
 impl GameState {
     const P1_POINTS: u32 = 0b11111_00000_0000_0000_0;
     const P2_POINTS: u32 = 0b00000_11111_0000_0000_0;
@@ -259,11 +357,12 @@ impl DiracDie {
 
     fn possible_rolls(&self) -> impl Iterator<Item = Vec<u32>> {
         (0..Self::ROLLS_PER_TURN)
-            .map(|_| (Self::MIN_ROLL..=Self::MAX_ROLL))
+            .map(|_| Self::MIN_ROLL..=Self::MAX_ROLL)
             .multi_cartesian_product()
     }
 
-    pub fn play(&mut self) {
+    /// Counts how many ways each sum of a turn's rolls can be achieved.
+    fn possible_roll_sums(&self) -> HashMap<u32, usize> {
         // A lot of the rolls produce the same sum, so count how many of each
         // possible roll can be achieved.
         let mut possible_roll_sums = HashMap::new();
@@ -272,6 +371,11 @@ impl DiracDie {
                 .entry(roll.into_iter().sum::<u32>())
                 .or_insert(0) += 1;
         }
+        possible_roll_sums
+    }
+
+    pub fn play(&mut self, overflow: OverflowPolicy) -> AocResult<()> {
+        let possible_roll_sums = self.possible_roll_sums();
 
         let mut done = false;
         while !done {
@@ -289,23 +393,78 @@ impl DiracDie {
                     // Split off on all possible dice rolls.
                     done = false;
                     for (roll, sum_count) in &possible_roll_sums {
-                        let mut state = state.clone();
+                        let mut state = state;
 
                         let player = state.next_player();
                         let new_pos = state.move_player(player, *roll);
                         state.increase_points(player, new_pos + 1);
                         state.flip_turn();
 
-                        self.games[state.0 as usize] += sum_count * universe_count;
+                        let games = &mut self.games[state.0 as usize];
+                        *games = overflow.add(*games, overflow.mul(*sum_count, universe_count)?)?;
                     }
 
                     self.games[game] = 0;
                 }
             }
         }
+        Ok(())
+    }
+
+    /// Plays the games like `play`, but one turn at a time, so that the
+    /// universes can be recorded by when and how they were won.
+    pub fn play_by_turn(&mut self, overflow: OverflowPolicy) -> AocResult<WinDistribution> {
+        let possible_roll_sums = self.possible_roll_sums();
+        let mut distribution = WinDistribution::default();
+
+        let mut turn = 0;
+        let mut done = false;
+        while !done {
+            done = true;
+            turn += 1;
+            let mut next_games = vec![0; self.games.len()];
+            for game in 0..self.games.len() {
+                let universe_count = self.games[game];
+                if universe_count == 0 {
+                    continue;
+                }
+                let state = GameState(game as u32);
+
+                // Finished games carry over to the next turn unchanged.
+                if state.get_winner().is_some() {
+                    next_games[game] = overflow.add(next_games[game], universe_count)?;
+                    continue;
+                }
+
+                done = false;
+                for (roll, sum_count) in &possible_roll_sums {
+                    let mut state = state;
+
+                    let player = state.next_player();
+                    let new_pos = state.move_player(player, *roll);
+                    state.increase_points(player, new_pos + 1);
+                    state.flip_turn();
+
+                    let universes = overflow.mul(*sum_count, universe_count)?;
+                    if state.get_winner().is_some() {
+                        let other = match player {
+                            Player::Player1 => Player::Player2,
+                            Player::Player2 => Player::Player1,
+                        };
+                        let margin = state.get_points(player) - state.get_points(other);
+                        distribution.record(turn, margin as usize, universes, overflow)?;
+                    }
+
+                    let games = &mut next_games[state.0 as usize];
+                    *games = overflow.add(*games, universes)?;
+                }
+            }
+            self.games = next_games;
+        }
+        Ok(distribution)
     }
 
-    pub fn win_counts(&self) -> (usize, usize) {
+    pub fn win_counts(&self, overflow: OverflowPolicy) -> AocResult<(usize, usize)> {
         let mut p1_count = 0;
         let mut p2_count = 0;
         for game in 0..self.games.len() {
@@ -314,21 +473,184 @@ impl DiracDie {
                 let state = GameState(game as u32);
 
                 match state.get_winner() {
-                    Some(Player::Player1) => p1_count += universe_count,
-                    Some(Player::Player2) => p2_count += universe_count,
+                    Some(Player::Player1) => p1_count = overflow.add(p1_count, universe_count)?,
+                    Some(Player::Player2) => p2_count = overflow.add(p2_count, universe_count)?,
                     None => (),
                 }
             }
         }
-        (p1_count, p2_count)
+        Ok((p1_count, p2_count))
     }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let (p1, p2) = parse_positions(input)?;
-    let mut game = DiracDie::new(p1, p2);
-    game.play();
-    let (p1_count, p2_count) = game.win_counts();
-    let result = p1_count.max(p2_count);
-    Ok(result as iAoc)
+/// Universes that ended in a win, counted by game length and by win margin.
+#[derive(Default)]
+struct WinDistribution {
+    /// Universes won on each turn, counting both players' turns.
+    lengths: Vec<usize>,
+    /// Universes won by each margin. The winner's score is capped at the
+    /// winning score, so this is how far the loser was from winning.
+    margins: Vec<usize>,
+}
+
+impl WinDistribution {
+    fn record(
+        &mut self,
+        turn: usize,
+        margin: usize,
+        universes: usize,
+        overflow: OverflowPolicy,
+    ) -> AocResult<()> {
+        for (buckets, bucket) in [(&mut self.lengths, turn), (&mut self.margins, margin)] {
+            if buckets.len() <= bucket {
+                buckets.resize(bucket + 1, 0);
+            }
+            buckets[bucket] = overflow.add(buckets[bucket], universes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which win distribution to write as a histogram.
+#[derive(Clone, Copy)]
+enum HistogramKind {
+    /// Universes by the number of turns taken to win.
+    Lengths,
+    /// Universes by win margin.
+    Margins,
+}
+
+impl FromStr for HistogramKind {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "lengths" => Ok(Self::Lengths),
+            "margins" => Ok(Self::Margins),
+            _ => Err(AocError::new("histogram must be lengths or margins")),
+        }
+    }
+}
+
+/// Renders a text histogram, with bars scaled to the largest bucket.
+fn render_histogram(label: &str, buckets: &[usize]) -> String {
+    const BAR_WIDTH: usize = 60;
+    let max = buckets.iter().copied().max().unwrap_or(0).max(1);
+    let first = buckets.iter().position(|&count| count != 0).unwrap_or(0);
+    let count_width = max.to_string().len();
+
+    let mut text = format!(
+        "{:>6} {:>width$}\n",
+        label,
+        "universes",
+        width = count_width
+    );
+    for (bucket, &count) in buckets.iter().enumerate().skip(first) {
+        let bar = (count as u128 * BAR_WIDTH as u128).div_ceil(max as u128) as usize;
+        text += &format!(
+            "{:>6} {:>width$} {}\n",
+            bucket,
+            count,
+            "#".repeat(bar),
+            width = count_width
+        );
+    }
+    text
+}
+
+fn write_histogram(
+    context: &SolverContext,
+    distribution: &WinDistribution,
+    kind: HistogramKind,
+) -> AocResult<()> {
+    let (name, text) = match kind {
+        HistogramKind::Lengths => (
+            "21.B.lengths.txt",
+            render_histogram("turns", &distribution.lengths),
+        ),
+        HistogramKind::Margins => (
+            "21.B.margins.txt",
+            render_histogram("margin", &distribution.margins),
+        ),
+    };
+    context.output().write(name, text.as_bytes())
+}
+
+pub struct DiracDice;
+
+impl Solver for DiracDice {
+    /// The starting position of each player.
+    type Parsed = (u8, u8);
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<(u8, u8)> {
+        parse_positions(input, context.parse_options())
+    }
+
+    fn part_a(&(p1, p2): &(u8, u8), context: &SolverContext) -> AocResult<SolutionValue> {
+        let mut game = PracticeDiracDie::new(p1, p2);
+        play_practice_game(context, &mut game)?;
+        let losing_score =
+            game.loser().into_aoc_result_msg("no losing player")?.points * game.times_rolled();
+        Ok(SolutionValue::from(losing_score as iAoc))
+    }
+
+    fn part_b(&(p1, p2): &(u8, u8), context: &SolverContext) -> AocResult<SolutionValue> {
+        let mut game = DiracDie::new(p1, p2);
+        match context.param("histogram") {
+            None => game.play(context.overflow())?,
+            Some(kind) => {
+                let kind = HistogramKind::from_str(kind)?;
+                let distribution = game.play_by_turn(context.overflow())?;
+                write_histogram(context, &distribution, kind)?;
+            }
+        }
+        let (p1_count, p2_count) = game.win_counts(context.overflow())?;
+        let result = p1_count.max(p2_count);
+        Ok(SolutionValue::from(result as iAoc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+
+    const EXAMPLE: &str = include_str!("../../input/test/21.1.txt");
+
+    #[test]
+    fn deterministic_die_wraps_from_its_last_side_to_one() {
+        let mut die = DeterministicDie::new(100);
+        let faces = (0..103).map(|_| die.roll()).collect::<Vec<_>>();
+        assert_eq!(faces[..3], [1, 2, 3]);
+        assert_eq!(faces[97..], [98, 99, 100, 1, 2, 3]);
+    }
+
+    #[test]
+    fn deterministic_die_counts_every_roll_across_wraps() {
+        let mut die = DeterministicDie::new(100);
+        for _ in 0..302 {
+            die.roll();
+        }
+        assert_eq!(die.times_rolled(), 302);
+        // 302 rolls go around three times and land on 2, so 3 is next.
+        assert_eq!(die.roll(), 3);
+        assert_eq!(die.times_rolled(), 303);
+    }
+
+    #[test]
+    fn practice_game_turns_roll_across_the_wrap() {
+        let mut game = PracticeDiracDie::new(4, 8);
+        let mut turns = Vec::new();
+        game.play_with(|turn| turns.push(turn.rolls));
+        // The 34th turn rolls the last side and then starts over.
+        assert_eq!(turns[33], [100, 1, 2]);
+        assert_eq!(game.times_rolled(), 993);
+        assert_eq!(game.loser().unwrap().points, 745);
+    }
+
+    #[test]
+    fn example() {
+        assert_solves!(DiracDice::part_a, EXAMPLE, 739785);
+        assert_solves!(DiracDice::part_b, EXAMPLE, 444356092776315);
+    }
 }
@@ -1,14 +1,22 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
-use std::str::FromStr;
+use crate::common::math::OverflowPolicy;
+use crate::common::{cache, log, SolutionValue, Solver};
+use crate::common::{
+    iAoc, parse_lines, AocError, AocResult, DayInfo, InputHash, IntoAocResult, ParamInfo,
+    ParseOptions, SolverContext,
+};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 
 type Range = (i32, i32);
 
-fn intersects_range((a_left, a_right): &Range, (b_left, b_right): &Range) -> bool {
-    !(a_right < b_left || a_left > b_right)
-}
-
-fn range_intersection((a_left, a_right): Range, (b_left, b_right): Range) -> Range {
-    (a_left.max(b_left), a_right.min(b_right))
+/// Clips a range to the given bounds, returning `None` if nothing is left.
+fn clip_range((left, right): Range, (min, max): Range) -> Option<Range> {
+    let clipped = (left.max(min), right.min(max));
+    if clipped.0 > clipped.1 {
+        None
+    } else {
+        Some(clipped)
+    }
 }
 
 /// Original partitioning code.
@@ -51,7 +59,7 @@ fn partition_range(
     (outer_left, inner, outer_right)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Cuboid {
     x: Range,
     y: Range,
@@ -62,32 +70,50 @@ impl Cuboid {
     pub fn new(x: Range, y: Range, z: Range) -> Self {
         Cuboid { x, y, z }
     }
-    pub fn cubes(&self) -> u64 {
-        (self.x.1 - self.x.0 + 1) as u64
-            * (self.y.1 - self.y.0 + 1) as u64
-            * (self.z.1 - self.z.0 + 1) as u64
+    /// Number of cubes in the cuboid.
+    ///
+    /// Steps are checked to run forwards when parsed, and splitting a cuboid
+    /// should never produce a backwards range, so one is reported as an
+    /// error rather than counted as a wrapped-around length.
+    pub fn cubes(&self, overflow: OverflowPolicy) -> AocResult<u64> {
+        let length = |(left, right): Range| -> AocResult<u64> {
+            if left > right {
+                return Err(AocError::new(format!(
+                    "cuboid {:?} has a backwards range",
+                    self
+                )));
+            }
+            Ok((right as i64 - left as i64 + 1) as u64)
+        };
+        overflow.mul(
+            overflow.mul(length(self.x)?, length(self.y)?)?,
+            length(self.z)?,
+        )
     }
 
-    pub fn intersects(&self, other: &Cuboid) -> bool {
-        intersects_range(&self.x, &other.x)
-            && intersects_range(&self.y, &other.y)
-            && intersects_range(&self.z, &other.z)
+    /// Clips the cuboid to the given region, which gives the intersection of
+    /// the two cuboids, or `None` if they do not intersect.
+    pub fn clipped_to(&self, region: &Cuboid) -> Option<Cuboid> {
+        Some(Cuboid::new(
+            clip_range(self.x, region.x)?,
+            clip_range(self.y, region.y)?,
+            clip_range(self.z, region.z)?,
+        ))
     }
+}
 
-    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
-        if !self.intersects(other) {
-            None
-        } else {
-            Some(Cuboid::new(
-                range_intersection(self.x, other.x),
-                range_intersection(self.y, other.y),
-                range_intersection(self.z, other.z),
-            ))
-        }
+/// Formats the cuboid the way steps give it, such as `x=-20..26,y=-36..17,z=-47..7`.
+impl Display for Cuboid {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        write!(
+            f,
+            "x={}..{},y={}..{},z={}..{}",
+            self.x.0, self.x.1, self.y.0, self.y.1, self.z.0, self.z.1
+        )
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 enum CuboidState {
     Off,
@@ -95,15 +121,13 @@ enum CuboidState {
 }
 
 #[derive(Debug)]
-struct RebootStep {
+pub struct RebootStep {
     state: CuboidState,
     cuboid: Cuboid,
 }
 
-impl FromStr for RebootStep {
-    type Err = AocError;
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
+impl RebootStep {
+    pub fn parse(input: &str, options: &ParseOptions) -> AocResult<Self> {
         let (state, ranges) = input.split_once(' ').into_aoc_result()?;
         let state = match state {
             "off" => CuboidState::Off,
@@ -112,45 +136,64 @@ impl FromStr for RebootStep {
         };
 
         let mut ranges = ranges.split(',');
-
-        let (x1, x2) = ranges.next().into_aoc_result()?[2..]
-            .split_once("..")
-            .into_aoc_result()?;
-        let x = (
-            x1.parse::<i32>().into_aoc_result()?,
-            x2.parse::<i32>().into_aoc_result()?,
-        );
-        let (y1, y2) = ranges.next().into_aoc_result()?[2..]
-            .split_once("..")
-            .into_aoc_result()?;
-        let y = (
-            y1.parse::<i32>().into_aoc_result()?,
-            y2.parse::<i32>().into_aoc_result()?,
-        );
-        let (z1, z2) = ranges.next().into_aoc_result()?[2..]
-            .split_once("..")
-            .into_aoc_result()?;
-        let z = (
-            z1.parse::<i32>().into_aoc_result()?,
-            z2.parse::<i32>().into_aoc_result()?,
-        );
-        let cuboid = Cuboid::new(x, y, z);
+        let mut next_range = |axis: &str| -> AocResult<Range> {
+            let range = ranges.next().into_aoc_result()?;
+            let (min, max) = range
+                .strip_prefix(axis)
+                .and_then(|range| range.strip_prefix('='))
+                .into_aoc_result_msg("invalid cuboid range")?
+                .split_once("..")
+                .into_aoc_result()?;
+            let (min, max) = (
+                min.parse::<i32>().into_aoc_result()?,
+                max.parse::<i32>().into_aoc_result()?,
+            );
+            if min > max {
+                // A backwards range still describes the same cubes.
+                options.tolerate(format!("range {} is backwards", range))?;
+                return Ok((max, min));
+            }
+            Ok((min, max))
+        };
+        let cuboid = Cuboid::new(next_range("x")?, next_range("y")?, next_range("z")?);
+        options.expect_end(ranges, "cuboid ranges")?;
 
         Ok(RebootStep { state, cuboid })
     }
 }
 
-fn parse_input(input: &str) -> AocResult<Vec<RebootStep>> {
-    input
-        .lines()
-        .map(|line| RebootStep::from_str(line))
-        .collect::<Result<_, _>>()
+impl Display for RebootStep {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        let state = match self.state {
+            CuboidState::Off => "off",
+            CuboidState::On => "on",
+        };
+        write!(f, "{} {}", state, self.cuboid)
+    }
 }
 
-fn count_cubes(steps: Vec<RebootStep>) -> iAoc {
-    let mut cuboids: Vec<Cuboid> = Vec::new();
+/// Parses the reboot steps and checks that they make sense together.
+///
+/// Backwards ranges are already flipped or rejected by `RebootStep::parse`.
+/// Steps that repeat an earlier step exactly are valid but suspicious, so
+/// they are reported.
+fn parse_input(input: &str, options: &ParseOptions) -> AocResult<Vec<RebootStep>> {
+    let steps = parse_lines(input, |line| RebootStep::parse(line, options))?;
+
+    let mut first_steps = HashMap::new();
+    for (i, step) in steps.iter().enumerate() {
+        let first = *first_steps.entry((step.state, step.cuboid)).or_insert(i);
+        if first != i {
+            options.warn(format!("step {} repeats step {}", i + 1, first + 1));
+        }
+    }
+    Ok(steps)
+}
 
-    for RebootStep {
+/// Applies the steps to a set of disjoint cuboids that are on, returning the
+/// disjoint cuboids that are on afterwards.
+fn apply_steps(mut cuboids: Vec<Cuboid>, steps: &[RebootStep]) -> Vec<Cuboid> {
+    for &RebootStep {
         state,
         cuboid: new_cuboid,
     } in steps
@@ -161,7 +204,7 @@ fn count_cubes(steps: Vec<RebootStep>) -> iAoc {
         // on the new cuboid being added.
         // This process removes overlapping ranges, replacing it with the new cuboid.
         for old_cuboid in cuboids {
-            match old_cuboid.intersection(&new_cuboid) {
+            match old_cuboid.clipped_to(&new_cuboid) {
                 // No intersection, old cuboid is unchanged.
                 None => new_cuboids.push(old_cuboid),
                 Some(intersection) => {
@@ -254,27 +297,278 @@ fn count_cubes(steps: Vec<RebootStep>) -> iAoc {
 
         cuboids = new_cuboids;
     }
-
     cuboids
-        .into_iter()
-        .fold(0 as iAoc, |acc, cuboid| acc + cuboid.cubes())
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let steps = parse_input(input)?;
-    let init_area = Cuboid::new((-50, 50), (-50, 50), (-50, 50));
-    let steps = steps
-        .into_iter()
-        .filter(|RebootStep { cuboid, .. }| {
-            return cuboid.intersects(&init_area);
-        })
-        .collect::<Vec<_>>();
-    let result = count_cubes(steps);
-    Ok(result)
+fn count_cubes(cuboids: &[Cuboid], overflow: OverflowPolicy) -> AocResult<iAoc> {
+    cuboids.iter().try_fold(0 as iAoc, |acc, cuboid| {
+        overflow.add(acc, cuboid.cubes(overflow)?)
+    })
+}
+
+/// Hashes of the first `k` steps for every `k`, from none of them to all of
+/// them, which key the snapshot taken after those steps.
+fn prefix_hashes(steps: &[RebootStep]) -> Vec<InputHash> {
+    let mut text = String::new();
+    let mut hashes = vec![InputHash::new(&text)];
+    for step in steps {
+        text += &format!("{}\n", step);
+        hashes.push(InputHash::new(&text));
+    }
+    hashes
+}
+
+/// Reads a snapshot of disjoint cuboids, one per line.
+fn read_snapshot(snapshot: &str) -> AocResult<Vec<Cuboid>> {
+    parse_lines(snapshot, parse_region)
+        .map_err(|err| AocError::new(format!("snapshot is corrupted: {}", err.message())))
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let steps = parse_input(input)?;
-    let result = count_cubes(steps);
-    Ok(result)
+/// Applies the steps and counts the cubes that are on.
+///
+/// If the `snapshots` parameter is set, the cuboids left after all of the
+/// steps are saved in the cache, keyed by the hash of the steps. A later run
+/// whose steps start with the same steps picks up from that snapshot and
+/// only applies the steps after it.
+fn run_steps(context: &SolverContext, part: &str, steps: &[RebootStep]) -> AocResult<iAoc> {
+    let enabled = match context.param("snapshots") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("snapshots must be true or false")?,
+    };
+    if !enabled {
+        return count_cubes(&apply_steps(Vec::new(), steps), context.overflow());
+    }
+
+    let name = format!("22.{}", part);
+    let hashes = prefix_hashes(steps);
+    let mut start = (Vec::new(), 0);
+    for (applied, hash) in hashes.iter().enumerate().skip(1).rev() {
+        if let Some(snapshot) = cache::load(context.output(), &name, *hash)? {
+            start = (read_snapshot(&snapshot)?, applied);
+            break;
+        }
+    }
+    let (cuboids, applied) = start;
+    if applied == 0 {
+        log::debug!("no snapshot of any of the {} steps", steps.len());
+    } else {
+        log::debug!(
+            "resuming from a snapshot of {} cuboids after {} of {} steps",
+            cuboids.len(),
+            applied,
+            steps.len()
+        );
+    }
+
+    let cuboids = apply_steps(cuboids, &steps[applied..]);
+    if applied < steps.len() {
+        let snapshot = cuboids
+            .iter()
+            .map(|cuboid| format!("{}\n", cuboid))
+            .collect::<String>();
+        cache::store(context.output(), &name, hashes[steps.len()], &snapshot)?;
+    }
+    count_cubes(&cuboids, context.overflow())
+}
+
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[
+        ParamInfo {
+            name: "region",
+            values: "min..max|x=min..max,y=min..max,z=min..max",
+            description: "Region that part A counts cubes in.",
+            default: "-50..50 on every axis",
+        },
+        ParamInfo {
+            name: "analysis",
+            values: "true|false",
+            description: "Writes the cubes each part A step has inside and outside of the region to output/22.A.analysis.txt.",
+            default: "false",
+        },
+        ParamInfo {
+            name: "snapshots",
+            values: "true|false",
+            description: "Saves the cuboids left after all of the steps to output/cache, and starts from the snapshot of the longest saved prefix of the steps, so only new steps are applied.",
+            default: "false",
+        },
+    ],
+    animated: false,
+};
+
+/// Parses the region part A is limited to, either as `min..max` on every axis
+/// or as `x=min..max,y=min..max,z=min..max`.
+fn parse_region(input: &str) -> AocResult<Cuboid> {
+    let parse_range = |range: &str| -> AocResult<Range> {
+        let (min, max) = range
+            .split_once("..")
+            .into_aoc_result_msg("region range must be of the form min..max")?;
+        let range = (
+            min.parse::<i32>().into_aoc_result()?,
+            max.parse::<i32>().into_aoc_result()?,
+        );
+        if range.0 > range.1 {
+            return Err(AocError::new("region range must not be backwards"));
+        }
+        Ok(range)
+    };
+
+    if !input.contains('=') {
+        let range = parse_range(input)?;
+        return Ok(Cuboid::new(range, range, range));
+    }
+    let mut ranges = input.split(',');
+    let mut next_range = |axis: &str| -> AocResult<Range> {
+        let range = ranges
+            .next()
+            .and_then(|range| range.strip_prefix(axis))
+            .and_then(|range| range.strip_prefix('='))
+            .into_aoc_result_msg("region must be of the form x=min..max,y=min..max,z=min..max")?;
+        parse_range(range)
+    };
+    let region = Cuboid::new(next_range("x")?, next_range("y")?, next_range("z")?);
+    if ranges.next().is_some() {
+        return Err(AocError::new("region has too many ranges"));
+    }
+    Ok(region)
+}
+
+/// Writes how much of each step is inside and outside of the region if the
+/// `analysis` parameter is set.
+fn write_analysis(context: &SolverContext, steps: &[RebootStep], region: &Cuboid) -> AocResult<()> {
+    let enabled = match context.param("analysis") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("analysis must be true or false")?,
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    let overflow = context.overflow();
+    let mut text = String::from("step state inside outside\n");
+    for (i, RebootStep { state, cuboid }) in steps.iter().enumerate() {
+        let inside = match cuboid.clipped_to(region) {
+            None => 0,
+            Some(clipped) => clipped.cubes(overflow)?,
+        };
+        let outside = cuboid.cubes(overflow)? - inside;
+        let state = match state {
+            CuboidState::Off => "off",
+            CuboidState::On => "on",
+        };
+        text += &format!("{} {} {} {}\n", i + 1, state, inside, outside);
+    }
+    context.output().write("22.A.analysis.txt", text.as_bytes())
+}
+
+pub struct ReactorReboot;
+
+impl Solver for ReactorReboot {
+    type Parsed = Vec<RebootStep>;
+
+    fn parse(input: &str, context: &SolverContext) -> AocResult<Vec<RebootStep>> {
+        parse_input(input, context.parse_options())
+    }
+
+    fn part_a(steps: &Vec<RebootStep>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let region = match context.param("region") {
+            None => Cuboid::new((-50, 50), (-50, 50), (-50, 50)),
+            Some(region) => parse_region(region)?,
+        };
+
+        write_analysis(context, steps, &region)?;
+
+        // Steps that reach outside of the region only count the cubes inside it.
+        let steps = steps
+            .iter()
+            .filter_map(|step| {
+                step.cuboid.clipped_to(&region).map(|cuboid| RebootStep {
+                    state: step.state,
+                    cuboid,
+                })
+            })
+            .collect::<Vec<_>>();
+        run_steps(context, "A", &steps).map(SolutionValue::from)
+    }
+
+    fn part_b(steps: &Vec<RebootStep>, context: &SolverContext) -> AocResult<SolutionValue> {
+        run_steps(context, "B", steps).map(SolutionValue::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn solve_a_for(input: &str) -> iAoc {
+        let context = SolverContext::default();
+        let steps = ReactorReboot::parse(input, &context).unwrap();
+        match ReactorReboot::part_a(&steps, &context).unwrap() {
+            SolutionValue::Number(cubes) => cubes,
+            other => panic!("expected a number of cubes, got {}", other),
+        }
+    }
+
+    #[test]
+    fn clip_range_keeps_the_overlap() {
+        assert_eq!(clip_range((45, 55), (-50, 50)), Some((45, 50)));
+        assert_eq!(clip_range((-60, -49), (-50, 50)), Some((-50, -49)));
+        assert_eq!(clip_range((-60, 60), (-50, 50)), Some((-50, 50)));
+        assert_eq!(clip_range((51, 60), (-50, 50)), None);
+    }
+
+    #[test]
+    fn cuboid_straddling_the_region_only_counts_cubes_inside() {
+        // x=45..50 is inside, x=51..55 is not.
+        assert_eq!(solve_a_for("on x=45..55,y=0..0,z=0..0"), 6);
+        // x=-50..-48 on 3 of 5, y=-50..50 on 101 of 121, z=0..1 on both.
+        assert_eq!(solve_a_for("on x=-52..-48,y=-60..60,z=0..1"), 3 * 101 * 2);
+        // Straddling both ends of every axis leaves the whole region.
+        assert_eq!(
+            solve_a_for("on x=-60..60,y=-60..60,z=-60..60"),
+            101 * 101 * 101
+        );
+    }
+
+    #[test]
+    fn cuboid_outside_the_region_counts_nothing() {
+        assert_eq!(solve_a_for("on x=51..60,y=0..0,z=0..0"), 0);
+        assert_eq!(
+            solve_a_for("on x=0..1,y=0..1,z=0..1\non x=-100..-51,y=-100..100,z=0..0"),
+            8
+        );
+    }
+
+    #[test]
+    fn partial_off_step_only_turns_off_cubes_inside() {
+        let input = "on x=40..50,y=0..0,z=0..0\noff x=48..60,y=0..0,z=0..0";
+        // x=40..47 stays on.
+        assert_eq!(solve_a_for(input), 8);
+    }
+
+    #[test]
+    fn inverted_cuboid_has_no_cube_count() {
+        let cuboid = Cuboid::new((0, 2), (5, 3), (0, 0));
+        let err = cuboid.cubes(OverflowPolicy::Check).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "cuboid Cuboid { x: (0, 2), y: (5, 3), z: (0, 0) } has a backwards range"
+        );
+    }
+
+    #[test]
+    fn backwards_step_range_is_flipped_or_rejected() {
+        let lenient = ParseOptions::new(false, Arc::default());
+        let step = RebootStep::parse("on x=2..0,y=0..0,z=0..0", &lenient).unwrap();
+        assert_eq!(step.cuboid, Cuboid::new((0, 2), (0, 0), (0, 0)));
+
+        let strict = ParseOptions::new(true, Arc::default());
+        let err = RebootStep::parse("on x=2..0,y=0..0,z=0..0", &strict).unwrap_err();
+        assert_eq!(err.message(), "range x=2..0 is backwards");
+    }
 }
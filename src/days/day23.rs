@@ -1,12 +1,20 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::math::OverflowPolicy;
+use crate::common::profile::CountingMap;
+use crate::common::search::check_admissible;
+use crate::common::{cancel, log, SolutionValue, Solver};
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, Rng, SolverContext, StrategyInfo,
+};
 use num_traits::FromPrimitive;
 use std::cmp::{Ordering, Reverse};
 use std::collections::{BinaryHeap, HashMap};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 use std::str::FromStr;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive)]
 #[repr(u8)]
-enum Amphipod {
+pub enum Amphipod {
     Amber = 0,
     Bronze = 1,
     Copper = 2,
@@ -24,8 +32,48 @@ impl Amphipod {
         }
     }
 
-    pub fn energy(&self) -> usize {
-        10usize.pow(*self as u32)
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Amber => 'A',
+            Self::Bronze => 'B',
+            Self::Copper => 'C',
+            Self::Desert => 'D',
+        }
+    }
+}
+
+/// Energy each type of amphipod uses to move one step.
+#[derive(Clone, Copy, Debug)]
+pub struct EnergyCosts([usize; 4]);
+
+impl EnergyCosts {
+    pub fn energy(&self, amp: Amphipod) -> usize {
+        self.0[amp as usize]
+    }
+}
+
+impl Default for EnergyCosts {
+    /// The puzzle's costs, which grow tenfold with each type.
+    fn default() -> Self {
+        EnergyCosts([1, 10, 100, 1000])
+    }
+}
+
+impl FromStr for EnergyCosts {
+    type Err = AocError;
+
+    /// Parses four comma-separated costs, in the order A, B, C, D.
+    ///
+    /// Costs must be positive, since finding every optimal solution relies on
+    /// every move costing energy.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let costs = input
+            .split(',')
+            .map(|cost| cost.trim().parse::<usize>().ok().filter(|cost| *cost > 0))
+            .collect::<Option<Vec<_>>>()
+            .and_then(|costs| <[usize; 4]>::try_from(costs).ok())
+            .into_aoc_result_msg("costs must be four positive integers, such as 1,10,100,1000")?;
+        Ok(EnergyCosts(costs))
     }
 }
 
@@ -41,7 +89,7 @@ impl Amphipod {
 ///
 /// Thus, 64 bits can be used to represent every unique state of this system.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct AmphipodState<const R: usize> {
+pub struct AmphipodState<const R: usize> {
     hallway: [Option<Amphipod>; 7],
     rooms: [[Option<Amphipod>; R]; 4],
 }
@@ -77,7 +125,7 @@ impl<const R: usize> AmphipodState<R> {
     }
 
     fn decode_space(space: u64) -> Option<Amphipod> {
-        Amphipod::from_u64(space - 1)
+        space.checked_sub(1).and_then(Amphipod::from_u64)
     }
 
     /// Encodes the state into a 64-bit integer.
@@ -132,8 +180,12 @@ impl<const R: usize> AmphipodState<R> {
     }
 
     /// Iterator over all of the next states of the current state.
-    pub fn next_states<'a>(&'a self) -> impl Iterator<Item = (Self, usize)> + 'a {
-        self.hallway_to_room().chain(self.room_to_hallway())
+    pub fn next_states<'a>(
+        &'a self,
+        costs: EnergyCosts,
+    ) -> impl Iterator<Item = (Self, usize)> + 'a {
+        self.hallway_to_room(costs)
+            .chain(self.room_to_hallway(costs))
     }
 
     /// Checks if an amphipod can enter this room by assuring that the only amphipods
@@ -169,15 +221,14 @@ impl<const R: usize> AmphipodState<R> {
 
     /// Calculates the distance between two X coordinates in the hallway.
     fn distance(a: usize, b: usize) -> usize {
-        if a < b {
-            b - a
-        } else {
-            a - b
-        }
+        b.abs_diff(a)
     }
 
     /// Generates all valid state changes for one amphipod in a hallway to its room.
-    fn hallway_to_room<'a>(&'a self) -> impl Iterator<Item = (Self, usize)> + 'a {
+    fn hallway_to_room<'a>(
+        &'a self,
+        costs: EnergyCosts,
+    ) -> impl Iterator<Item = (Self, usize)> + 'a {
         self.hallway
             .iter()
             .enumerate()
@@ -209,7 +260,7 @@ impl<const R: usize> AmphipodState<R> {
                 let steps = Self::distance(hallway_x, target_room_x) + target_room_y + 1;
 
                 // The cost for the move.
-                let energy = steps * amp.energy();
+                let energy = steps * costs.energy(amp);
 
                 // Create the new state by copying the current one and swapping
                 // the current position with the target position in the room.
@@ -229,17 +280,22 @@ impl<const R: usize> AmphipodState<R> {
         hallway_index: usize,
     ) -> impl Iterator<Item = usize> + 'a {
         // Move left and right, starting at the current hallway index, until
-        // a non-empty space is hit.
-        (0..hallway_index)
+        // a non-empty space is hit. The index is the space to the left of the
+        // room, so it is the first space to the left.
+        (0..=hallway_index)
             .rev()
             .take_while(move |x| self.hallway[*x].is_none())
             .chain(
-                (hallway_index..self.hallway.len()).take_while(move |x| self.hallway[*x].is_none()),
+                (hallway_index + 1..self.hallway.len())
+                    .take_while(move |x| self.hallway[*x].is_none()),
             )
     }
 
     /// Generates all valid state changes for one amphipod in a wrong room to the hallway.
-    fn room_to_hallway<'a>(&'a self) -> impl Iterator<Item = (Self, usize)> + 'a {
+    fn room_to_hallway<'a>(
+        &'a self,
+        costs: EnergyCosts,
+    ) -> impl Iterator<Item = (Self, usize)> + 'a {
         self.rooms
             .iter()
             .enumerate()
@@ -258,7 +314,7 @@ impl<const R: usize> AmphipodState<R> {
                     .map(move |hallway_index| {
                         let hallway_x = Self::hallway_x(hallway_index);
                         let steps = room_y + 1 + Self::distance(room_x, hallway_x);
-                        let energy = steps * amp.energy();
+                        let energy = steps * costs.energy(amp);
 
                         let mut new_state = *self;
                         std::mem::swap(
@@ -278,7 +334,10 @@ impl<const R: usize> AmphipodState<R> {
     ///
     /// Calculates the energy required for all amphipods in invalid positions
     /// to move directly to their goal position, regardless of obstacles.
-    fn heuristic(&self) -> usize {
+    ///
+    /// Every step is charged at the same cost the moves use, so the bound
+    /// holds for any cost table.
+    fn heuristic(&self, costs: EnergyCosts) -> usize {
         // Cost of moving amphipods in the hallway to the space above their room.
         let hallway_to_above_room = self
             .hallway
@@ -290,8 +349,8 @@ impl<const R: usize> AmphipodState<R> {
                 let hallway_x = Self::hallway_x(hallway_index);
                 let target_room_x = Self::room_x(target_room);
                 let steps = 1 + Self::distance(hallway_x, target_room_x);
-                let energy = steps * amp.energy();
-                energy
+
+                steps * costs.energy(amp)
             })
             .sum::<usize>();
         // Cost of moving amphipods in the wrong room to the space above their room.
@@ -308,11 +367,11 @@ impl<const R: usize> AmphipodState<R> {
                     .skip_while(move |(_, amp)| room_index == *amp as usize)
                     .map(move |(room_y, amp)| {
                         let target_room = amp as usize;
-                        let target_room_x = Self::hallway_x(target_room);
+                        let target_room_x = Self::room_x(target_room);
                         let hallway_steps = Self::distance(room_x, target_room_x).max(2);
                         let steps = room_y + 1 + hallway_steps;
-                        let energy = steps * amp.energy();
-                        energy
+
+                        steps * costs.energy(amp)
                     })
             })
             .sum::<usize>();
@@ -328,8 +387,8 @@ impl<const R: usize> AmphipodState<R> {
                     Some(first_open_y) => {
                         let steps = (first_open_y + 1) * first_open_y / 2;
                         let amp = Amphipod::from_usize(room_index).unwrap();
-                        let energy = amp.energy() * steps;
-                        energy
+
+                        costs.energy(amp) * steps
                     }
                 },
             )
@@ -338,25 +397,79 @@ impl<const R: usize> AmphipodState<R> {
         hallway_to_above_room + room_to_above_room + above_room_to_room
     }
 
+    /// Searches for the cheapest path from the start state to the goal state
+    /// using the given strategy.
+    pub fn solve(start: Self, strategy: SearchStrategy, costs: EnergyCosts) -> AocResult<usize> {
+        let heuristic = |state: &Self| state.heuristic(costs);
+        match strategy {
+            SearchStrategy::AStar => Self::search(start, costs, heuristic),
+            SearchStrategy::Dijkstra => Self::search(start, costs, |_| 0),
+            SearchStrategy::CheckHeuristic => {
+                Self::check_heuristic(&start, costs)?;
+                Self::search(start, costs, heuristic)
+            }
+            SearchStrategy::CrossCheck => {
+                let a_star = Self::search(start, costs, heuristic)?;
+                let dijkstra = Self::search(start, costs, |_| 0)?;
+                if a_star != dijkstra {
+                    Err(AocError::new(format!(
+                        "A* found cost {} but Dijkstra found cost {}",
+                        a_star, dijkstra
+                    )))
+                } else {
+                    Ok(a_star)
+                }
+            }
+        }
+    }
+
+    /// Checks that `heuristic()` never overestimates the remaining energy, using
+    /// states sampled by randomly moving amphipods from the start state.
+    fn check_heuristic(start: &Self, costs: EnergyCosts) -> AocResult<usize> {
+        const SAMPLES: usize = 8;
+        const MAX_WALK: usize = 8;
+        check_admissible(
+            start,
+            SAMPLES,
+            MAX_WALK,
+            &mut Rng::new(23),
+            |state| state.next_states(costs).map(|(state, _)| state).collect(),
+            |state| state.heuristic(costs),
+            |state| Self::search(*state, costs, |_| 0).ok(),
+        )
+    }
+
     /// Implements the A* algorithm, searching for the shortest path from the
     /// start state to the goal state.
-    pub fn solve(start: Self) -> AocResult<usize> {
+    ///
+    /// A heuristic that always returns 0 turns this into Dijkstra's algorithm.
+    fn search<H>(start: Self, costs: EnergyCosts, heuristic: H) -> AocResult<usize>
+    where
+        H: Fn(&Self) -> usize,
+    {
         let encoded_goal = Self::goal().encode();
         let encoded_start = start.encode();
 
-        let start_f_score = start.heuristic();
-        let mut f_scores = HashMap::new();
+        let start_f_score = heuristic(&start);
+        let mut f_scores = CountingMap::new();
         f_scores.insert(encoded_start, start_f_score);
 
-        let mut g_scores = HashMap::new();
+        let mut g_scores = CountingMap::new();
         g_scores.insert(encoded_start, 0);
 
         let mut open_set = BinaryHeap::new();
         open_set.push(Reverse((start_f_score, encoded_start)));
 
+        let mut expanded = 0;
         while let Some(Reverse((f_score, encoded_state))) = open_set.pop() {
+            cancel::check()?;
             let state = Self::decode(encoded_state);
             if encoded_state == encoded_goal {
+                log::debug!(
+                    "expanded {} states, discovered {}",
+                    expanded,
+                    g_scores.len()
+                );
                 return Ok(f_score);
             }
 
@@ -364,13 +477,16 @@ impl<const R: usize> AmphipodState<R> {
                 continue;
             }
 
+            expanded += 1;
+            log::trace!("expanding state {:#x} with f = {}", encoded_state, f_score);
+
             let g_score = g_scores.get(&encoded_state).copied().unwrap();
-            for (next_state, cost) in state.next_states() {
+            for (next_state, cost) in state.next_states(costs) {
                 let encoded_next_state = next_state.encode();
                 let tentative_g_score = g_score + cost;
                 let next_state_g_score = g_scores.entry(encoded_next_state).or_insert(usize::MAX);
                 if tentative_g_score < *next_state_g_score {
-                    let new_f_score = tentative_g_score + next_state.heuristic();
+                    let new_f_score = tentative_g_score + heuristic(&next_state);
                     *f_scores.entry(encoded_next_state).or_default() = new_f_score;
                     *next_state_g_score = tentative_g_score;
                     open_set.push(Reverse((new_f_score, encoded_next_state)));
@@ -380,6 +496,208 @@ impl<const R: usize> AmphipodState<R> {
 
         Err(AocError::new("no solution found"))
     }
+
+    /// Finds every distinct move ordering that reaches the goal state with the
+    /// minimum energy.
+    ///
+    /// Runs Dijkstra's algorithm to completion for every state no more
+    /// expensive than the goal, remembering all predecessors that reach a
+    /// state with the same minimum energy. Every move costs energy, so a
+    /// state's predecessors are all settled before it is, and the number of
+    /// optimal paths to a state can be summed as states are settled.
+    pub fn optimal_solutions(
+        start: Self,
+        costs: EnergyCosts,
+        overflow: OverflowPolicy,
+    ) -> AocResult<OptimalSolutions> {
+        let encoded_goal = Self::goal().encode();
+        let encoded_start = start.encode();
+
+        let mut g_scores = HashMap::new();
+        g_scores.insert(encoded_start, 0);
+        let mut counts = HashMap::new();
+        counts.insert(encoded_start, 1u64);
+        let mut predecessors: HashMap<u64, Vec<u64>> = HashMap::new();
+
+        let mut open_set = BinaryHeap::new();
+        open_set.push(Reverse((0, encoded_start)));
+
+        let mut cost = None;
+        while let Some(Reverse((g_score, encoded_state))) = open_set.pop() {
+            cancel::check()?;
+            if cost.is_some_and(|cost| g_score > cost) {
+                break;
+            }
+            if g_score > g_scores[&encoded_state] {
+                continue;
+            }
+            if encoded_state == encoded_goal {
+                cost = Some(g_score);
+                continue;
+            }
+
+            let count = counts[&encoded_state];
+            for (next_state, move_cost) in Self::decode(encoded_state).next_states(costs) {
+                let encoded_next_state = next_state.encode();
+                let tentative_g_score = g_score + move_cost;
+                let next_state_g_score = g_scores.entry(encoded_next_state).or_insert(usize::MAX);
+                match tentative_g_score.cmp(next_state_g_score) {
+                    Ordering::Less => {
+                        *next_state_g_score = tentative_g_score;
+                        counts.insert(encoded_next_state, count);
+                        predecessors.insert(encoded_next_state, vec![encoded_state]);
+                        open_set.push(Reverse((tentative_g_score, encoded_next_state)));
+                    }
+                    Ordering::Equal => {
+                        let next_count = counts.entry(encoded_next_state).or_insert(0);
+                        *next_count = overflow.add(*next_count, count)?;
+                        predecessors
+                            .entry(encoded_next_state)
+                            .or_default()
+                            .push(encoded_state);
+                    }
+                    Ordering::Greater => (),
+                }
+            }
+        }
+
+        let cost = cost.into_aoc_result_msg("no solution found")?;
+        Ok(OptimalSolutions {
+            cost,
+            count: counts[&encoded_goal],
+            start: encoded_start,
+            goal: encoded_goal,
+            g_scores,
+            predecessors,
+        })
+    }
+
+    /// Describes the move of a single amphipod from this state to the next.
+    fn describe_move(&self, next: &Self) -> String {
+        let spaces = |state: &Self| {
+            let hallway = state
+                .hallway
+                .iter()
+                .enumerate()
+                .map(|(i, space)| (format!("hallway {}", Self::hallway_x(i)), *space));
+            let rooms = state.rooms.iter().enumerate().flat_map(|(r, room)| {
+                room.iter()
+                    .enumerate()
+                    .map(move |(y, space)| (format!("room {} depth {}", r, y), *space))
+            });
+            hallway.chain(rooms).collect::<Vec<_>>()
+        };
+        let (before, after) = (spaces(self), spaces(next));
+        let mut from = None;
+        let mut to = None;
+        for ((name, before), (_, after)) in before.into_iter().zip(after) {
+            match (before, after) {
+                (Some(amp), None) => from = Some((name, amp)),
+                (None, Some(_)) => to = Some(name),
+                _ => (),
+            }
+        }
+        match (from, to) {
+            (Some((from, amp)), Some(to)) => format!("{} {} -> {}", amp.to_char(), from, to),
+            _ => String::from("?"),
+        }
+    }
+}
+
+impl<const R: usize> Display for AmphipodState<R> {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        let space = |space: Option<Amphipod>| space.map_or('.', Amphipod::to_char);
+        writeln!(f, "#############")?;
+        write!(f, "#")?;
+        for x in 0..11 {
+            let above_room = (2..=8).contains(&x) && x % 2 == 0;
+            let ch = if above_room {
+                '.'
+            } else {
+                space(self.hallway[Self::hallway_index(x)])
+            };
+            write!(f, "{}", ch)?;
+        }
+        writeln!(f, "#")?;
+        for y in 0..R {
+            let edge = if y == 0 { "###" } else { "  #" };
+            write!(f, "{}", edge)?;
+            for room in &self.rooms {
+                write!(f, "{}#", space(room[y]))?;
+            }
+            writeln!(f, "{}", if y == 0 { "##" } else { "" })?;
+        }
+        writeln!(f, "  #########")
+    }
+}
+
+/// Every move ordering that solves a burrow with the minimum energy.
+pub struct OptimalSolutions {
+    cost: usize,
+    /// Number of distinct optimal move orderings.
+    count: u64,
+    start: u64,
+    goal: u64,
+    g_scores: HashMap<u64, usize>,
+    /// Maps a state to every state that reaches it along an optimal path.
+    predecessors: HashMap<u64, Vec<u64>>,
+}
+
+impl OptimalSolutions {
+    /// Lists up to `limit` optimal move orderings as sequences of encoded
+    /// states, from the start state to the goal state.
+    pub fn paths(&self, limit: usize) -> Vec<Vec<u64>> {
+        let mut paths = Vec::new();
+        // Walks backwards from the goal, keeping the path from each state to
+        // the goal.
+        let mut stack = vec![vec![self.goal]];
+        while let Some(path) = stack.pop() {
+            if paths.len() >= limit {
+                break;
+            }
+            let state = *path.last().unwrap();
+            if state == self.start {
+                paths.push(path.into_iter().rev().collect());
+                continue;
+            }
+            for predecessor in self.predecessors.get(&state).into_iter().flatten() {
+                let mut path = path.clone();
+                path.push(*predecessor);
+                stack.push(path);
+            }
+        }
+        paths
+    }
+}
+
+/// Strategy used to search the state graph.
+#[derive(Clone, Copy)]
+pub enum SearchStrategy {
+    /// A* search, guided by `AmphipodState::heuristic`.
+    AStar,
+    /// Dijkstra's algorithm, which uses no heuristic at all.
+    Dijkstra,
+    /// Runs both searches and fails if their costs differ, which catches a
+    /// heuristic that is no longer admissible.
+    CrossCheck,
+    /// Verifies the A* heuristic on sampled states before searching.
+    CheckHeuristic,
+}
+
+impl FromStr for SearchStrategy {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "astar" => Ok(Self::AStar),
+            "dijkstra" => Ok(Self::Dijkstra),
+            "cross-check" => Ok(Self::CrossCheck),
+            "check-heuristic" => Ok(Self::CheckHeuristic),
+            _ => Err(AocError::new(
+                "strategy must be astar, dijkstra, cross-check, or check-heuristic",
+            )),
+        }
+    }
 }
 
 impl<const R: usize> FromStr for AmphipodState<R> {
@@ -417,30 +735,282 @@ impl<const R: usize> FromStr for AmphipodState<R> {
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let state = AmphipodState::<2>::from_str(input)?;
-    let result = AmphipodState::<2>::solve(state)?;
-    Ok(result as iAoc)
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[
+        StrategyInfo {
+            name: "astar",
+            description: "A* search, guided by the amphipod heuristic.",
+        },
+        StrategyInfo {
+            name: "dijkstra",
+            description: "Dijkstra's algorithm, which uses no heuristic at all.",
+        },
+        StrategyInfo {
+            name: "cross-check",
+            description: "Runs both searches and fails if their costs differ.",
+        },
+        StrategyInfo {
+            name: "check-heuristic",
+            description: "Verifies the A* heuristic on sampled states before searching.",
+        },
+    ],
+    params: &[
+        ParamInfo {
+            name: "optimal",
+            values: "count|list",
+            description: "Counts every distinct optimal move ordering, and optionally lists the first 100 of them, in output/23.<part>.optimal.txt.",
+            default: "no report",
+        },
+        ParamInfo {
+            name: "costs",
+            values: "a,b,c,d",
+            description: "Energy each type of amphipod uses per step, in the order A, B, C, D.",
+            default: "1,10,100,1000",
+        },
+    ],
+    animated: false,
+};
+
+/// Whether to report every optimal solution, not just the cost of one.
+#[derive(Clone, Copy)]
+enum OptimalReport {
+    Count,
+    List,
+}
+
+impl FromStr for OptimalReport {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "count" => Ok(Self::Count),
+            "list" => Ok(Self::List),
+            _ => Err(AocError::new("optimal must be count or list")),
+        }
+    }
+}
+
+/// Writes the number of optimal solutions, and optionally the solutions
+/// themselves, if the `optimal` parameter is set.
+fn write_optimal_solutions<const R: usize>(
+    context: &SolverContext,
+    part: &str,
+    start: AmphipodState<R>,
+    costs: EnergyCosts,
+) -> AocResult<()> {
+    const LISTED: usize = 100;
+    let report = match context.param("optimal") {
+        None => return Ok(()),
+        Some(report) => OptimalReport::from_str(report)?,
+    };
+
+    let solutions = AmphipodState::optimal_solutions(start, costs, context.overflow())?;
+    let mut text = format!(
+        "{}\n{} optimal solutions with energy {}\n",
+        start, solutions.count, solutions.cost
+    );
+    if let OptimalReport::List = report {
+        for (i, path) in solutions.paths(LISTED).iter().enumerate() {
+            text += &format!("\nSolution {}:\n", i + 1);
+            for (from, to) in path.iter().zip(path.iter().skip(1)) {
+                let (from, to) = (
+                    AmphipodState::<R>::decode(*from),
+                    AmphipodState::<R>::decode(*to),
+                );
+                let energy = solutions.g_scores[&to.encode()] - solutions.g_scores[&from.encode()];
+                text += &format!("  {} ({})\n", from.describe_move(&to), energy);
+            }
+        }
+        if solutions.count > LISTED as u64 {
+            text += &format!("\n{} more not listed\n", solutions.count - LISTED as u64);
+        }
+    }
+    let name = format!("23.{}.optimal.txt", part);
+    context.output().write(&name, text.as_bytes())
+}
+
+/// The energy costs from the `costs` parameter.
+fn energy_costs(context: &SolverContext) -> AocResult<EnergyCosts> {
+    match context.param("costs") {
+        None => Ok(EnergyCosts::default()),
+        Some(costs) => EnergyCosts::from_str(costs),
+    }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let folded_state = AmphipodState::<2>::from_str(input)?;
-    let mut unfolded_state = AmphipodState::<4>::new();
-
-    const UNFOLDED_INPUT: [[Option<Amphipod>; 2]; 4] = [
-        [Some(Amphipod::Desert), Some(Amphipod::Desert)],
-        [Some(Amphipod::Copper), Some(Amphipod::Bronze)],
-        [Some(Amphipod::Bronze), Some(Amphipod::Amber)],
-        [Some(Amphipod::Amber), Some(Amphipod::Copper)],
-    ];
-    for room_index in 0..4 {
-        let mut it = std::iter::once(folded_state.rooms[room_index][0])
-            .chain(UNFOLDED_INPUT[room_index].iter().copied())
-            .chain(std::iter::once(folded_state.rooms[room_index][1]));
-        unfolded_state.rooms[room_index] =
-            unfolded_state.rooms[room_index].map(|_| it.next().unwrap());
-    }
-
-    let result = AmphipodState::<4>::solve(unfolded_state)?;
-    Ok(result as iAoc)
+pub struct AmphipodBurrow;
+
+impl Solver for AmphipodBurrow {
+    type Parsed = AmphipodState<2>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<AmphipodState<2>> {
+        AmphipodState::<2>::from_str(input)
+    }
+
+    fn part_a(&state: &AmphipodState<2>, context: &SolverContext) -> AocResult<SolutionValue> {
+        let strategy = context.strategy_or(SearchStrategy::AStar)?;
+        let costs = energy_costs(context)?;
+        write_optimal_solutions(context, "A", state, costs)?;
+        let result = AmphipodState::<2>::solve(state, strategy, costs)?;
+        Ok(SolutionValue::from(result as iAoc))
+    }
+
+    fn part_b(
+        folded_state: &AmphipodState<2>,
+        context: &SolverContext,
+    ) -> AocResult<SolutionValue> {
+        let strategy = context.strategy_or(SearchStrategy::AStar)?;
+        let costs = energy_costs(context)?;
+        let mut unfolded_state = AmphipodState::<4>::new();
+
+        const UNFOLDED_INPUT: [[Option<Amphipod>; 2]; 4] = [
+            [Some(Amphipod::Desert), Some(Amphipod::Desert)],
+            [Some(Amphipod::Copper), Some(Amphipod::Bronze)],
+            [Some(Amphipod::Bronze), Some(Amphipod::Amber)],
+            [Some(Amphipod::Amber), Some(Amphipod::Copper)],
+        ];
+        for (room_index, unfolded_rows) in UNFOLDED_INPUT.iter().enumerate() {
+            let mut it = std::iter::once(folded_state.rooms[room_index][0])
+                .chain(unfolded_rows.iter().copied())
+                .chain(std::iter::once(folded_state.rooms[room_index][1]));
+            unfolded_state.rooms[room_index] =
+                unfolded_state.rooms[room_index].map(|_| it.next().unwrap());
+        }
+
+        write_optimal_solutions(context, "B", unfolded_state, costs)?;
+        let result = AmphipodState::<4>::solve(unfolded_state, strategy, costs)?;
+        Ok(SolutionValue::from(result as iAoc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::testing::assert_solves;
+    use itertools::Itertools;
+
+    const EXAMPLE: &str = include_str!("../../input/test/23.1.txt");
+
+    impl<const R: usize> AmphipodState<R> {
+        /// Generates a state with `R` amphipods of each type placed on random
+        /// spaces.
+        ///
+        /// Amphipods only ever leave a room from the top, so each room is
+        /// filled from the bottom with its empty spaces above its occupants.
+        fn random(rng: &mut Rng) -> Self {
+            let mut spaces = (0..4)
+                .flat_map(|amp| std::iter::repeat_n(Amphipod::from_usize(amp), R))
+                .chain(std::iter::repeat_n(None, 7))
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut spaces);
+            let mut spaces = spaces.into_iter();
+            let hallway = [(); 7].map(|_| spaces.next().unwrap());
+            let rooms = [(); 4].map(|_| {
+                let mut room = [(); R].map(|_| spaces.next().unwrap());
+                room.sort_by_key(Option::is_some);
+                room
+            });
+            Self { hallway, rooms }
+        }
+
+        /// Finds the minimum energy to reach the goal state by trying every
+        /// sequence of moves, or `None` if the goal cannot be reached.
+        ///
+        /// Every amphipod moves at most twice, once into the hallway and once into
+        /// its room, so no sequence of moves repeats a state. The minimum energy
+        /// from every state tried is kept in `memo`.
+        fn brute_force(
+            self,
+            costs: EnergyCosts,
+            memo: &mut HashMap<u64, Option<usize>>,
+        ) -> Option<usize> {
+            if self == Self::goal() {
+                return Some(0);
+            }
+            if let Some(energy) = memo.get(&self.encode()) {
+                return *energy;
+            }
+            let energy = self
+                .next_states(costs)
+                .filter_map(|(next_state, energy)| {
+                    Some(energy + next_state.brute_force(costs, memo)?)
+                })
+                .min();
+            memo.insert(self.encode(), energy);
+            energy
+        }
+    }
+
+    #[test]
+    fn states_survive_encoding_and_decoding() {
+        const SAMPLES: usize = 1000;
+        fn check_round_trips<const R: usize>(rng: &mut Rng) {
+            for _ in 0..SAMPLES {
+                let state = AmphipodState::<R>::random(rng);
+                let decoded = AmphipodState::<R>::decode(state.encode());
+                assert!(
+                    decoded == state,
+                    "state\n{}was decoded as\n{}",
+                    state,
+                    decoded
+                );
+            }
+        }
+
+        let mut rng = Rng::new(23);
+        check_round_trips::<2>(&mut rng);
+        check_round_trips::<3>(&mut rng);
+        check_round_trips::<4>(&mut rng);
+    }
+
+    /// Checks every burrow with one row, which is every placement of one
+    /// amphipod of each type.
+    #[test]
+    fn a_star_agrees_with_brute_force_on_one_row_burrows() {
+        let costs = EnergyCosts::default();
+        let mut memo = HashMap::new();
+        for spaces in (0..11).permutations(4) {
+            let mut state = AmphipodState::<1>::new();
+            for (amp, space) in spaces.into_iter().enumerate() {
+                let amp = Amphipod::from_usize(amp);
+                match space {
+                    0..=6 => state.hallway[space] = amp,
+                    _ => state.rooms[space - 7][0] = amp,
+                }
+            }
+            let brute_force = state.brute_force(costs, &mut memo);
+            let a_star = AmphipodState::search(state, costs, |state| state.heuristic(costs)).ok();
+            assert_eq!(a_star, brute_force, "energy for\n{}", state);
+        }
+    }
+
+    #[test]
+    fn example() {
+        assert_solves!(AmphipodBurrow::part_a, EXAMPLE, 12521);
+        assert_solves!(AmphipodBurrow::part_b, EXAMPLE, 44169);
+    }
+
+    #[test]
+    fn astar_and_dijkstra_agree_on_the_example() {
+        for strategy in ["astar", "dijkstra"] {
+            assert_solves!(AmphipodBurrow::part_a, EXAMPLE, 12521, strategy: strategy);
+            assert_solves!(AmphipodBurrow::part_b, EXAMPLE, 44169, strategy: strategy);
+        }
+    }
+
+    #[test]
+    fn heuristic_is_admissible_on_the_example() {
+        let costs = EnergyCosts::default();
+        let start = AmphipodState::<2>::from_str(EXAMPLE).unwrap();
+        let checked = check_admissible(
+            &start,
+            10,
+            8,
+            &mut Rng::new(23),
+            |state| state.next_states(costs).map(|(state, _)| state).collect(),
+            |state| state.heuristic(costs),
+            |state| AmphipodState::search(*state, costs, |_| 0).ok(),
+        )
+        .unwrap();
+        assert!(checked > 0);
+    }
 }
@@ -1,11 +1,15 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::{
+    iAoc, parse_lines, AocError, AocResult, DayInfo, IntoAocResult, ParamInfo, SolutionValue,
+    Solver, SolverContext,
+};
 use itertools::Itertools;
+use std::fmt::{Display, Formatter, Result as DisplayResult};
 use std::str::FromStr;
 
 /// The variables used by the MONAD.
 #[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
-enum Variable {
+pub enum Variable {
     W = 0,
     X = 1,
     Y = 2,
@@ -26,9 +30,21 @@ impl FromStr for Variable {
     }
 }
 
+impl Display for Variable {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        let name = match self {
+            Self::W => "w",
+            Self::X => "x",
+            Self::Y => "y",
+            Self::Z => "z",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// A parameter to an instruction.
 #[derive(Clone, Copy, PartialEq, Eq)]
-enum Parameter {
+pub enum Parameter {
     Variable(Variable),
     Literal(i64),
 }
@@ -48,9 +64,18 @@ impl FromStr for Parameter {
     }
 }
 
+impl Display for Parameter {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        match self {
+            Self::Variable(var) => write!(f, "{}", var),
+            Self::Literal(literal) => write!(f, "{}", literal),
+        }
+    }
+}
+
 /// A single instruction.
 #[derive(Clone, Copy, PartialEq, Eq)]
-enum Instruction {
+pub enum Instruction {
     Inp(Variable),
     Add(Variable, Parameter),
     Mul(Variable, Parameter),
@@ -59,40 +84,50 @@ enum Instruction {
     Eql(Variable, Parameter),
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        match self {
+            Self::Inp(var) => write!(f, "inp {}", var),
+            Self::Add(var, param) => write!(f, "add {} {}", var, param),
+            Self::Mul(var, param) => write!(f, "mul {} {}", var, param),
+            Self::Div(var, param) => write!(f, "div {} {}", var, param),
+            Self::Mod(var, param) => write!(f, "mod {} {}", var, param),
+            Self::Eql(var, param) => write!(f, "eql {} {}", var, param),
+        }
+    }
+}
+
 /// Parse all instructions from the input string.
 fn parse_instructions(input: &str) -> AocResult<Vec<Instruction>> {
-    input
-        .lines()
-        .map(|line| {
-            let mut split = line.split(' ');
-            match split.next().into_aoc_result()? {
-                "inp" => Ok(Instruction::Inp(Variable::from_str(
-                    split.next().into_aoc_result()?,
-                )?)),
-                "add" => Ok(Instruction::Add(
-                    Variable::from_str(split.next().into_aoc_result()?)?,
-                    Parameter::from_str(split.next().into_aoc_result()?)?,
-                )),
-                "mul" => Ok(Instruction::Mul(
-                    Variable::from_str(split.next().into_aoc_result()?)?,
-                    Parameter::from_str(split.next().into_aoc_result()?)?,
-                )),
-                "div" => Ok(Instruction::Div(
-                    Variable::from_str(split.next().into_aoc_result()?)?,
-                    Parameter::from_str(split.next().into_aoc_result()?)?,
-                )),
-                "mod" => Ok(Instruction::Mod(
-                    Variable::from_str(split.next().into_aoc_result()?)?,
-                    Parameter::from_str(split.next().into_aoc_result()?)?,
-                )),
-                "eql" => Ok(Instruction::Eql(
-                    Variable::from_str(split.next().into_aoc_result()?)?,
-                    Parameter::from_str(split.next().into_aoc_result()?)?,
-                )),
-                _ => Err(AocError::new("invalid instruction")),
-            }
-        })
-        .collect::<Result<_, _>>()
+    parse_lines(input, |line| {
+        let mut split = line.split(' ');
+        match split.next().into_aoc_result()? {
+            "inp" => Ok(Instruction::Inp(Variable::from_str(
+                split.next().into_aoc_result()?,
+            )?)),
+            "add" => Ok(Instruction::Add(
+                Variable::from_str(split.next().into_aoc_result()?)?,
+                Parameter::from_str(split.next().into_aoc_result()?)?,
+            )),
+            "mul" => Ok(Instruction::Mul(
+                Variable::from_str(split.next().into_aoc_result()?)?,
+                Parameter::from_str(split.next().into_aoc_result()?)?,
+            )),
+            "div" => Ok(Instruction::Div(
+                Variable::from_str(split.next().into_aoc_result()?)?,
+                Parameter::from_str(split.next().into_aoc_result()?)?,
+            )),
+            "mod" => Ok(Instruction::Mod(
+                Variable::from_str(split.next().into_aoc_result()?)?,
+                Parameter::from_str(split.next().into_aoc_result()?)?,
+            )),
+            "eql" => Ok(Instruction::Eql(
+                Variable::from_str(split.next().into_aoc_result()?)?,
+                Parameter::from_str(split.next().into_aoc_result()?)?,
+            )),
+            _ => Err(AocError::new("invalid instruction")),
+        }
+    })
 }
 
 /// Runs the MONAD program with the given digits as input.
@@ -122,16 +157,16 @@ fn run_monad(instructions: &Vec<Instruction>, input: &[u8; 14]) -> bool {
                 i += 1;
             }
             Instruction::Add(var, param) => {
-                vars[*var as usize] = vars[*var as usize] + param_value(param, &vars);
+                vars[*var as usize] += param_value(param, &vars);
             }
             Instruction::Mul(var, param) => {
-                vars[*var as usize] = vars[*var as usize] * param_value(param, &vars);
+                vars[*var as usize] *= param_value(param, &vars);
             }
             Instruction::Div(var, param) => {
-                vars[*var as usize] = vars[*var as usize] / param_value(param, &vars);
+                vars[*var as usize] /= param_value(param, &vars);
             }
             Instruction::Mod(var, param) => {
-                vars[*var as usize] = vars[*var as usize] % param_value(param, &vars);
+                vars[*var as usize] %= param_value(param, &vars);
             }
             Instruction::Eql(var, param) => {
                 vars[*var as usize] = if vars[*var as usize] == param_value(param, &vars) {
@@ -321,16 +356,23 @@ fn run_monad(instructions: &Vec<Instruction>, input: &[u8; 14]) -> bool {
 
 */
 
-/// A call to the 18-instruction subroutine in the MONAD, which takes two parameters.
+/// A call to the 18-instruction subroutine in the MONAD.
+///
+/// The `pop_stack` parameter is implied by the sign of `stack_pop_add`, so it
+/// is only kept for the disassembly.
 #[derive(Debug)]
 struct MonadSubroutineCall {
+    pop_stack: bool,
     stack_pop_add: i32,
     stack_push_add: i32,
 }
 
 impl MonadSubroutineCall {
-    pub fn new(stack_pop_add: i32, stack_push_add: i32) -> Self {
+    const INSTRUCTIONS: usize = 18;
+
+    pub fn new(pop_stack: bool, stack_pop_add: i32, stack_push_add: i32) -> Self {
         Self {
+            pop_stack,
             stack_pop_add,
             stack_push_add,
         }
@@ -339,13 +381,18 @@ impl MonadSubroutineCall {
 
 /// Parses the MONAD into even groups of subroutine calls, which make up the entire
 /// program.
-fn parse_monad_subroutines(monad: &Vec<Instruction>) -> AocResult<Vec<MonadSubroutineCall>> {
+fn parse_monad_subroutines(monad: &[Instruction]) -> AocResult<Vec<MonadSubroutineCall>> {
     monad
         .iter()
-        .chunks(18)
+        .chunks(MonadSubroutineCall::INSTRUCTIONS)
         .into_iter()
         .map(|subroutine| {
-            let mut subroutine = subroutine.skip(5);
+            let mut subroutine = subroutine.skip(4);
+            let pop_stack = match subroutine.next() {
+                Some(Instruction::Div(_, Parameter::Literal(1))) => false,
+                Some(Instruction::Div(_, Parameter::Literal(26))) => true,
+                _ => return Err(AocError::new("invalid stack pop division instruction")),
+            };
             let stack_pop_add = match subroutine.next() {
                 Some(Instruction::Add(_, Parameter::Literal(num))) => num,
                 _ => return Err(AocError::new("invalid stack peek addition instruction")),
@@ -356,6 +403,7 @@ fn parse_monad_subroutines(monad: &Vec<Instruction>) -> AocResult<Vec<MonadSubro
                 _ => return Err(AocError::new("invalid stack push addition instruction")),
             };
             Ok(MonadSubroutineCall::new(
+                pop_stack,
                 *stack_pop_add as i32,
                 *stack_push_add as i32,
             ))
@@ -363,8 +411,60 @@ fn parse_monad_subroutines(monad: &Vec<Instruction>) -> AocResult<Vec<MonadSubro
         .collect::<Result<_, _>>()
 }
 
+/// Formats the MONAD grouped into its subroutine calls, each annotated with
+/// the parameters extracted from it.
+fn disassemble(monad: &[Instruction], subroutine_calls: &[MonadSubroutineCall]) -> String {
+    let mut listing = String::new();
+    for (digit_index, (instructions, call)) in monad
+        .chunks(MonadSubroutineCall::INSTRUCTIONS)
+        .zip(subroutine_calls)
+        .enumerate()
+    {
+        if digit_index > 0 {
+            listing.push('\n');
+        }
+        listing += &format!(
+            "# digit {}: monad_subroutine(pop_stack = {}, stack_pop_add = {}, stack_push_add = {})\n",
+            digit_index + 1,
+            call.pop_stack,
+            call.stack_pop_add,
+            call.stack_push_add
+        );
+        for (i, instruction) in instructions.iter().enumerate() {
+            let parameter = match i {
+                4 => Some("pop_stack"),
+                5 => Some("stack_pop_add"),
+                15 => Some("stack_push_add"),
+                _ => None,
+            };
+            listing += &match parameter {
+                Some(parameter) => format!("{:<10} # {}\n", instruction.to_string(), parameter),
+                None => format!("{}\n", instruction),
+            };
+        }
+    }
+    listing
+}
+
+/// Writes the disassembled MONAD if the `disassemble` parameter is set.
+fn write_disassembly(context: &SolverContext, monad: &Monad) -> AocResult<()> {
+    let enabled = match context.param("disassemble") {
+        None => false,
+        Some(enabled) => enabled
+            .parse::<bool>()
+            .into_aoc_result_msg("disassemble must be true or false")?,
+    };
+    if enabled {
+        let listing = disassemble(&monad.instructions, &monad.subroutine_calls);
+        context
+            .output()
+            .write("24.disassembly.txt", listing.as_bytes())?;
+    }
+    Ok(())
+}
+
 /// Represents a relationship between two digits of the model number.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 struct DigitRelationship {
     a: usize,
     b: usize,
@@ -379,14 +479,14 @@ impl DigitRelationship {
 
 /// Parses MONAD subroutine calls into the corresponding digit relationships.
 fn parse_digit_relationships(
-    subroutine_calls: Vec<MonadSubroutineCall>,
+    subroutine_calls: &[MonadSubroutineCall],
 ) -> AocResult<Vec<DigitRelationship>> {
     // Emulate the stack of digits. Instead of storing an actual digit, we store
     // the digit index, which represents any digit that may be passed in at this
     // position.
     let mut stack = Vec::new();
     let mut relationships = Vec::new();
-    for (digit_index, subroutine_call) in subroutine_calls.into_iter().enumerate() {
+    for (digit_index, subroutine_call) in subroutine_calls.iter().enumerate() {
         if subroutine_call.stack_pop_add >= 0 || stack.is_empty() {
             // Always-pushing call.
             stack.push((digit_index, subroutine_call.stack_push_add));
@@ -410,6 +510,26 @@ fn parse_digit_relationships(
     }
 }
 
+/// The MONAD program, with the digit relationships it enforces.
+pub struct Monad {
+    instructions: Vec<Instruction>,
+    subroutine_calls: Vec<MonadSubroutineCall>,
+    digit_relationships: Vec<DigitRelationship>,
+}
+
+impl Monad {
+    /// Derives the digit relationships enforced by the MONAD.
+    fn analyze(instructions: Vec<Instruction>) -> AocResult<Self> {
+        let subroutine_calls = parse_monad_subroutines(&instructions)?;
+        let digit_relationships = parse_digit_relationships(&subroutine_calls)?;
+        Ok(Self {
+            instructions,
+            subroutine_calls,
+            digit_relationships,
+        })
+    }
+}
+
 /*
     At this point, a series of digit relationships (specifically 7) are known.
 
@@ -433,30 +553,89 @@ fn parse_digit_relationships(
         digit[a] - C = digit[b]
             digit[b] = 1
             digit[a] = digit[b] + C = 1 + C
+
+    Each digit belongs to exactly one relationship, so the pairs can be solved
+    independently. This also makes it easy to fix some digits ahead of time:
+    for each pair, try candidate values for digit[a] from best to worst, derive
+    digit[b], and take the first pair where both digits are in the range 1 to 9
+    and agree with any fixed digits. Without fixed digits, this finds exactly
+    the values above.
 */
 
-fn maximize_digits(digit_relationships: Vec<DigitRelationship>) -> [u8; 14] {
-    let mut digits = [9u8; 14];
-    for DigitRelationship { a, b, c } in digit_relationships {
-        if c > 0 {
-            digits[a] -= c as u8;
-        } else {
-            digits[b] -= (-c) as u8;
+/// Digits fixed ahead of time, indexed by digit position.
+type DigitConstraints = [Option<u8>; 14];
+
+/// Parses digit constraints of the form `position:digit,position:digit`, where
+/// positions are counted from 1 at the leftmost digit.
+fn parse_digit_constraints(input: &str) -> AocResult<DigitConstraints> {
+    let mut constraints = [None; 14];
+    for constraint in input.split(',') {
+        let (position, digit) = constraint
+            .split_once(':')
+            .into_aoc_result_msg("digit constraint must be of the form position:digit")?;
+        let position = position
+            .parse::<usize>()
+            .into_aoc_result_msg("invalid digit position")?;
+        let digit = digit.parse::<u8>().into_aoc_result_msg("invalid digit")?;
+        if !(1..=14).contains(&position) {
+            return Err(AocError::new("digit position must be between 1 and 14"));
         }
+        if !(1..=9).contains(&digit) {
+            return Err(AocError::new("fixed digit must be between 1 and 9"));
+        }
+        constraints[position - 1] = Some(digit);
     }
-    digits
+    Ok(constraints)
 }
 
-fn minimize_digits(digit_relationships: Vec<DigitRelationship>) -> [u8; 14] {
-    let mut digits = [1u8; 14];
-    for DigitRelationship { a, b, c } in digit_relationships {
-        if c > 0 {
-            digits[b] += c as u8;
-        } else {
-            digits[a] += (-c) as u8;
-        }
+/// Chooses the first valid digits for each relationship, trying candidate
+/// digits in the given order.
+fn choose_digits<I>(
+    digit_relationships: &[DigitRelationship],
+    constraints: &DigitConstraints,
+    candidates: I,
+) -> AocResult<[u8; 14]>
+where
+    I: Iterator<Item = u8> + Clone,
+{
+    let allowed = |index: usize, digit: i8| {
+        (1..=9).contains(&digit) && constraints[index].is_none_or(|fixed| fixed as i8 == digit)
+    };
+
+    let mut digits = [None; 14];
+    for &DigitRelationship { a, b, c } in digit_relationships {
+        let (digit_a, digit_b) = candidates
+            .clone()
+            .map(|digit_a| (digit_a as i8, digit_a as i8 + c))
+            .find(|(digit_a, digit_b)| allowed(a, *digit_a) && allowed(b, *digit_b))
+            .into_aoc_result_msg("no model number satisfies the fixed digits")?;
+        digits[a] = Some(digit_a as u8);
+        digits[b] = Some(digit_b as u8);
     }
-    digits
+
+    // Digits without a relationship can be anything.
+    let mut result = [0u8; 14];
+    for (i, digit) in digits.iter().enumerate() {
+        result[i] = match digit.or(constraints[i]) {
+            Some(digit) => digit,
+            None => candidates.clone().next().into_aoc_result()?,
+        };
+    }
+    Ok(result)
+}
+
+fn maximize_digits(
+    digit_relationships: &[DigitRelationship],
+    constraints: &DigitConstraints,
+) -> AocResult<[u8; 14]> {
+    choose_digits(digit_relationships, constraints, (1..=9).rev())
+}
+
+fn minimize_digits(
+    digit_relationships: &[DigitRelationship],
+    constraints: &DigitConstraints,
+) -> AocResult<[u8; 14]> {
+    choose_digits(digit_relationships, constraints, 1..=9)
 }
 
 /// Joins an array of digits back into the number it represents.
@@ -466,30 +645,302 @@ fn join_digits(digits: &[u8; 14]) -> u64 {
         .fold(0u64, |acc, digit| 10 * acc + *digit as u64)
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let monad = parse_instructions(input)?;
-    let subroutine_calls = parse_monad_subroutines(&monad)?;
-    let digit_relationships = parse_digit_relationships(subroutine_calls)?;
-    let digits = maximize_digits(digit_relationships);
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[
+        ParamInfo {
+            name: "fixed",
+            values: "position:digit,...",
+            description: "Fixes digits of the model number, counting positions from 1 on the left.",
+            default: "no fixed digits",
+        },
+        ParamInfo {
+            name: "disassemble",
+            values: "true|false",
+            description: "Writes the annotated MONAD to output/24.disassembly.txt.",
+            default: "false",
+        },
+    ],
+    animated: false,
+};
+
+pub struct ArithmeticLogicUnit;
+
+impl Solver for ArithmeticLogicUnit {
+    type Parsed = Monad;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Monad> {
+        Monad::analyze(parse_instructions(input)?)
+    }
 
-    if !run_monad(&monad, &digits) {
-        Err(AocError::new("maximized digits do not pass the program"))
-    } else {
-        let result = join_digits(&digits);
-        Ok(result as iAoc)
+    fn part_a(monad: &Monad, context: &SolverContext) -> AocResult<SolutionValue> {
+        let constraints = match context.param("fixed") {
+            None => [None; 14],
+            Some(fixed) => parse_digit_constraints(fixed)?,
+        };
+        write_disassembly(context, monad)?;
+        let digits = maximize_digits(&monad.digit_relationships, &constraints)?;
+
+        if !run_monad(&monad.instructions, &digits) {
+            Err(AocError::new("maximized digits do not pass the program"))
+        } else {
+            let result = join_digits(&digits);
+            Ok(SolutionValue::from(result as iAoc))
+        }
+    }
+
+    fn part_b(monad: &Monad, context: &SolverContext) -> AocResult<SolutionValue> {
+        let constraints = match context.param("fixed") {
+            None => [None; 14],
+            Some(fixed) => parse_digit_constraints(fixed)?,
+        };
+        write_disassembly(context, monad)?;
+        let digits = minimize_digits(&monad.digit_relationships, &constraints)?;
+
+        if !run_monad(&monad.instructions, &digits) {
+            Err(AocError::new("minimized digits do not pass the program"))
+        } else {
+            let result = join_digits(&digits);
+            Ok(SolutionValue::from(result as iAoc))
+        }
     }
 }
 
-pub fn solve_b(input: &str) -> AocResult<iAoc> {
-    let monad = parse_instructions(input)?;
-    let subroutine_calls = parse_monad_subroutines(&monad)?;
-    let digit_relationships = parse_digit_relationships(subroutine_calls)?;
-    let digits = minimize_digits(digit_relationships);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Rng;
+
+    /// Assembles the MONAD that makes the given subroutine calls.
+    fn assemble(subroutine_calls: &[MonadSubroutineCall]) -> Vec<Instruction> {
+        use Instruction::*;
+        use Parameter::Literal;
+        use Variable::*;
+        subroutine_calls
+            .iter()
+            .flat_map(|call| {
+                let divisor = if call.pop_stack { 26 } else { 1 };
+                [
+                    Inp(W),
+                    Mul(X, Literal(0)),
+                    Add(X, Parameter::Variable(Z)),
+                    Mod(X, Literal(26)),
+                    Div(Z, Literal(divisor)),
+                    Add(X, Literal(call.stack_pop_add as i64)),
+                    Eql(X, Parameter::Variable(W)),
+                    Eql(X, Literal(0)),
+                    Mul(Y, Literal(0)),
+                    Add(Y, Literal(25)),
+                    Mul(Y, Parameter::Variable(X)),
+                    Add(Y, Literal(1)),
+                    Mul(Z, Parameter::Variable(Y)),
+                    Mul(Y, Literal(0)),
+                    Add(Y, Parameter::Variable(W)),
+                    Add(Y, Literal(call.stack_push_add as i64)),
+                    Mul(Y, Parameter::Variable(X)),
+                    Add(Z, Parameter::Variable(Y)),
+                ]
+            })
+            .collect()
+    }
 
-    if !run_monad(&monad, &digits) {
-        Err(AocError::new("minimized digits do not pass the program"))
-    } else {
-        let result = join_digits(&digits);
-        Ok(result as iAoc)
+    /// Generates the source of a MONAD with random parameters that accepts at
+    /// least one model number.
+    ///
+    /// Like the puzzle inputs, pushing calls have a `stack_pop_add` of at least
+    /// 10, and each popping call is paired with an earlier pushing call so that
+    /// the two digits can be made to agree.
+    fn generate_monad(rng: &mut Rng) -> String {
+        let mut subroutine_calls = Vec::with_capacity(14);
+        let mut stack = Vec::new();
+        for digit_index in 0..14 {
+            let remaining = 14 - digit_index;
+            let push = stack.is_empty() || (stack.len() < remaining && rng.below(2) == 0);
+            if push {
+                let stack_push_add = rng.below(17) as i32;
+                stack.push(stack_push_add);
+                subroutine_calls.push(MonadSubroutineCall::new(
+                    false,
+                    10 + rng.below(7) as i32,
+                    stack_push_add,
+                ));
+            } else {
+                // A difference between the two digits, which must leave a
+                // negative stack_pop_add so that the call pops.
+                let pushed_add = stack.pop().unwrap();
+                let c = (rng.below(17) as i32 - 8).min(pushed_add - 1);
+                subroutine_calls.push(MonadSubroutineCall::new(
+                    true,
+                    c - pushed_add,
+                    rng.below(17) as i32,
+                ));
+            }
+        }
+        assemble(&subroutine_calls).iter().join("\n")
+    }
+
+    /// Generates digits that satisfy the relationships, with every other digit
+    /// random.
+    fn random_digits(rng: &mut Rng, digit_relationships: &[DigitRelationship]) -> [u8; 14] {
+        let mut digits = [0u8; 14];
+        for digit in &mut digits {
+            *digit = 1 + rng.below(9) as u8;
+        }
+        for DigitRelationship { a, b, c } in digit_relationships {
+            let low = 1.max(1 - c);
+            let high = 9.min(9 - c);
+            let digit_a = low + rng.below((high - low + 1) as usize) as i8;
+            digits[*a] = digit_a as u8;
+            digits[*b] = (digit_a + c) as u8;
+        }
+        digits
+    }
+
+    /// Checks the model numbers found from the digit relationships of a MONAD
+    /// against the interpreter.
+    ///
+    /// The largest and smallest model numbers must be accepted, as must other
+    /// digits that satisfy the relationships, which must lie between the two.
+    /// Breaking any one relationship must get the digits rejected.
+    fn check_monad(monad: &Monad, rng: &mut Rng) -> AocResult<()> {
+        const SAMPLES: usize = 20;
+        let no_constraints = [None; 14];
+        let digit_relationships = &monad.digit_relationships;
+        let max = maximize_digits(digit_relationships, &no_constraints)?;
+        let min = minimize_digits(digit_relationships, &no_constraints)?;
+        for (name, digits) in [("largest", max), ("smallest", min)] {
+            if !run_monad(&monad.instructions, &digits) {
+                return Err(AocError::new(format!(
+                    "{} model number {} does not pass the program",
+                    name,
+                    join_digits(&digits)
+                )));
+            }
+        }
+
+        let range = join_digits(&min)..=join_digits(&max);
+        for _ in 0..SAMPLES {
+            let digits = random_digits(rng, digit_relationships);
+            let number = join_digits(&digits);
+            if !run_monad(&monad.instructions, &digits) {
+                return Err(AocError::new(format!(
+                    "model number {} satisfies the digit relationships but does not pass the program",
+                    number
+                )));
+            }
+            if !range.contains(&number) {
+                return Err(AocError::new(format!(
+                    "model number {} passes the program but is outside {} to {}",
+                    number,
+                    range.start(),
+                    range.end()
+                )));
+            }
+
+            let DigitRelationship { b, .. } =
+                digit_relationships[rng.below(digit_relationships.len())];
+            let mut broken = digits;
+            broken[b] = (broken[b] + rng.below(8) as u8) % 9 + 1;
+            if run_monad(&monad.instructions, &broken) {
+                return Err(AocError::new(format!(
+                    "model number {} breaks a digit relationship but passes the program",
+                    join_digits(&broken)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn relationships_agree_with_the_interpreter_on_generated_monads() {
+        const MONADS: usize = 200;
+        let mut rng = Rng::new(24);
+        for _ in 0..MONADS {
+            let source = generate_monad(&mut rng);
+            let monad = Monad::analyze(parse_instructions(&source).unwrap()).unwrap();
+            if let Err(err) = check_monad(&monad, &mut rng) {
+                panic!("{} in generated MONAD:\n{}", err.message(), source);
+            }
+        }
+    }
+
+    /// Relationships that pair up neighboring digits, with differences that
+    /// rule out some digits on both sides.
+    fn paired_relationships() -> Vec<DigitRelationship> {
+        [2, -3, 0, 8, -8, 1, -1]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| DigitRelationship::new(2 * i, 2 * i + 1, *c))
+            .collect()
+    }
+
+    fn constraint_error(input: &str) -> String {
+        parse_digit_constraints(input).unwrap_err().message()
+    }
+
+    #[test]
+    fn parses_digit_constraints() {
+        let constraints = parse_digit_constraints("1:5,14:9").unwrap();
+        assert_eq!(constraints[0], Some(5));
+        assert_eq!(constraints[13], Some(9));
+        assert!(constraints[1..13].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn rejects_invalid_digit_constraints() {
+        assert_eq!(
+            constraint_error("1=5"),
+            "digit constraint must be of the form position:digit"
+        );
+        assert_eq!(constraint_error("x:5"), "invalid digit position");
+        assert_eq!(
+            constraint_error("0:5"),
+            "digit position must be between 1 and 14"
+        );
+        assert_eq!(
+            constraint_error("15:5"),
+            "digit position must be between 1 and 14"
+        );
+        assert_eq!(constraint_error("1:x"), "invalid digit");
+        assert_eq!(
+            constraint_error("1:0"),
+            "fixed digit must be between 1 and 9"
+        );
+        assert_eq!(
+            constraint_error("1:10"),
+            "fixed digit must be between 1 and 9"
+        );
+    }
+
+    #[test]
+    fn fixed_digits_change_the_largest_and_smallest_model_numbers() {
+        let none = [None; 14];
+        let max = maximize_digits(&paired_relationships(), &none).unwrap();
+        let min = minimize_digits(&paired_relationships(), &none).unwrap();
+        assert_eq!(join_digits(&max), 79969919918998);
+        assert_eq!(join_digits(&min), 13411119911221);
+
+        // Fixing the first digit moves the second digit with it, and fixing
+        // the fourth digit moves the third.
+        let fixed = parse_digit_constraints("1:5,4:2").unwrap();
+        let max = maximize_digits(&paired_relationships(), &fixed).unwrap();
+        let min = minimize_digits(&paired_relationships(), &fixed).unwrap();
+        assert_eq!(join_digits(&max), 57529919918998);
+        assert_eq!(join_digits(&min), 57521119911221);
+    }
+
+    #[test]
+    fn rejects_fixed_digits_no_model_number_satisfies() {
+        // The second digit is two more than the first, so the first cannot be 9.
+        let fixed = parse_digit_constraints("1:9").unwrap();
+        for digits in [
+            maximize_digits(&paired_relationships(), &fixed),
+            minimize_digits(&paired_relationships(), &fixed),
+        ] {
+            assert_eq!(
+                digits.unwrap_err().message(),
+                "no model number satisfies the fixed digits"
+            );
+        }
     }
 }
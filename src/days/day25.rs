@@ -1,167 +1,100 @@
-use crate::common::{iAoc, AocError, AocResult, IntoAocResult};
+use crate::common::animate::Animator;
+use crate::common::grid::DoubleBuffer;
+use crate::common::{
+    iAoc, AocError, AocResult, DayInfo, IntoAocResult, SolutionValue, Solver, SolverContext,
+};
 use std::fmt::{Display, Formatter, Result as DisplayResult};
 use std::str::FromStr;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 #[repr(u8)]
-enum SeaCucumber {
+pub enum SeaCucumber {
     East,
     South,
 }
 
-struct SeaCucumberHerds {
-    data: Vec<Option<SeaCucumber>>,
+#[derive(Clone)]
+pub struct SeaCucumberHerds {
+    /// Spaces of the grid, row by row. The grid is double buffered so that no
+    /// step allocates a new one.
+    data: DoubleBuffer<Vec<Option<SeaCucumber>>>,
     height: usize,
     width: usize,
+    /// Moves found for the herd being moved, reused across steps.
+    moves: Vec<(usize, usize)>,
 }
 
 impl SeaCucumberHerds {
-    pub fn new(height: usize, width: usize) -> Self {
+    pub fn new(height: usize, width: usize, data: Vec<Option<SeaCucumber>>) -> Self {
         Self {
-            data: vec![None; height * width],
+            data: DoubleBuffer::new(data.clone(), data),
             height,
             width,
+            moves: Vec::new(),
         }
     }
 
-    pub fn step(mut self) -> (Self, bool) {
-        let mut next = SeaCucumberHerds::new(self.height, self.width);
-        let mut changed = false;
-
-        // First move all east sea cucumbers.
+    /// Finds every sea cucumber in the herd that faces an empty space,
+    /// recording each move as the pair of its current and target index.
+    fn find_moves(&mut self, herd: SeaCucumber) {
+        let data = self.data.read();
+        self.moves.clear();
         for y in 0..self.height {
             let row_start = y * self.width;
-            let row = &mut self.data[row_start..(row_start + self.width)];
-
-            // Find the first empty space in the current row.
-            // If it does not exist, the row is in gridlock.
-            let first_empty_x = row.iter().position(|space| space.is_none());
-            if let Some(first_empty_x) = first_empty_x {
-                // Found an empty space, move left across the entire row and
-                // move sea cucumbers accordingly.
-
-                // Condition stating that the next east sea cucumber can move.
-                let mut can_move = true;
-                let mut prev = first_empty_x;
-                for x in (0..first_empty_x)
-                    .rev()
-                    .chain(((first_empty_x + 1)..self.width).rev())
-                {
-                    match row[x] {
-                        Some(SeaCucumber::East) => {
-                            if can_move {
-                                // East sea cucumber can move.
-
-                                changed = true;
-                                can_move = false;
-
-                                // Move sea cucumber.
-                                next.data[row_start + prev] = Some(SeaCucumber::East);
-
-                                // Also update this sea cucumber's location in the current
-                                // state, so that south sea cucumbers see its updated
-                                // position.
-                                //
-                                // Since we are iterating from right-to-left and do not
-                                // repeat any values, this sea cucumber will surely not
-                                // move again.
-                                row[x] = None;
-                                row[prev] = Some(SeaCucumber::East);
-                            } else {
-                                // East sea cucumber cannot move, keep it in same position.
-                                next.data[row_start + x] = Some(SeaCucumber::East);
-                            }
-                        }
-                        Some(_) => {
-                            can_move = false;
-                        }
-                        None => {
-                            can_move = true;
-                        }
-                    }
-                    prev = x;
+            for x in 0..self.width {
+                let index = row_start + x;
+                if data[index] != Some(herd) {
+                    continue;
                 }
-            } else {
-                // Gridlock, copy over sea cucumbers to new state.
-                for x in row.iter().enumerate().filter_map(|(x, space)| {
-                    if let Some(SeaCucumber::East) = space {
-                        Some(x)
-                    } else {
-                        None
-                    }
-                }) {
-                    next.data[row_start + x] = Some(SeaCucumber::East);
+                let target = match herd {
+                    SeaCucumber::East if x + 1 == self.width => row_start,
+                    SeaCucumber::East => index + 1,
+                    SeaCucumber::South if y + 1 == self.height => x,
+                    SeaCucumber::South => index + self.width,
+                };
+                if data[target].is_none() {
+                    self.moves.push((index, target));
                 }
             }
         }
+    }
 
-        // Now move all south sea cucumbers.
-        for x in 0..self.width {
-            // Find the first empty space in the current column.
-            // If it does not exist, the row is in gridlock.
-            let first_empty_y =
-                (0..self.height).position(|y| self.data[y * self.width + x].is_none());
-            if let Some(first_empty_y) = first_empty_y {
-                // Found an empty space, move up across the entire column and
-                // move sea cucumbers accordingly.
-
-                // Condition stating that the next south sea cucumber can move.
-                let mut can_move = true;
-                let mut prev_index = first_empty_y * self.width + x;
-                for y in (0..first_empty_y)
-                    .rev()
-                    .chain(((first_empty_y + 1)..self.height).rev())
-                {
-                    let current_index = y * self.width + x;
-                    match self.data[current_index] {
-                        Some(SeaCucumber::South) => {
-                            if can_move {
-                                // South sea cucumber can move.
-
-                                changed = true;
-                                can_move = false;
-
-                                // Move sea cucumber.
-                                next.data[prev_index] = Some(SeaCucumber::South);
-                            } else {
-                                // South sea cucumber cannot move, keep it in same position.
-                                next.data[current_index] = Some(SeaCucumber::South);
-                            }
-                        }
-                        Some(_) => {
-                            can_move = false;
-                        }
-                        None => {
-                            can_move = true;
-                        }
-                    }
-                    prev_index = current_index;
-                }
-            } else {
-                // Gridlock, copy over sea cucumbers to new state.
-                for index in (0..self.height).filter_map(|y| {
-                    let index = y * self.width + x;
-                    if let Some(SeaCucumber::South) = self.data[index] {
-                        Some(index)
-                    } else {
-                        None
-                    }
-                }) {
-                    next.data[index] = Some(SeaCucumber::South);
-                }
-            }
+    /// Moves every sea cucumber in the herd that faces an empty space,
+    /// returning if any moved.
+    ///
+    /// All moves are found before any are applied, so a herd that cannot move
+    /// leaves the grid untouched.
+    fn move_herd(&mut self, herd: SeaCucumber) -> bool {
+        self.find_moves(herd);
+        if self.moves.is_empty() {
+            return false;
         }
+        let (data, next) = self.data.write();
+        next.copy_from_slice(data);
+        for &(index, target) in &self.moves {
+            next[index] = None;
+            next[target] = Some(herd);
+        }
+        self.data.swap();
+        true
+    }
 
-        (next, changed)
+    /// Moves the east-facing herd and then the south-facing herd, returning
+    /// if any sea cucumber moved.
+    pub fn step(&mut self) -> bool {
+        let east_moved = self.move_herd(SeaCucumber::East);
+        let south_moved = self.move_herd(SeaCucumber::South);
+        east_moved || south_moved
     }
 }
 
 impl Display for SeaCucumberHerds {
     fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        let data = self.data.read();
         let mut index = 0;
         for _ in 0..self.height {
             for _ in 0..self.width {
-                let ch = match self.data[index] {
+                let ch = match data[index] {
                     Some(SeaCucumber::East) => '>',
                     Some(SeaCucumber::South) => 'v',
                     None => '.',
@@ -169,7 +102,7 @@ impl Display for SeaCucumberHerds {
                 write!(f, "{}", ch)?;
                 index += 1;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         Ok(())
@@ -184,11 +117,11 @@ impl FromStr for SeaCucumberHerds {
 
         let height = lines.clone().count();
         let width = lines.clone().next().into_aoc_result_msg("no rows")?.len();
-        let mut herds = Self::new(height, width);
+        let mut data = vec![None; height * width];
 
         for (y, line) in lines.enumerate() {
             for (x, ch) in line.chars().enumerate() {
-                herds.data[y * width + x] = match ch {
+                data[y * width + x] = match ch {
                     '>' => Some(SeaCucumber::East),
                     'v' => Some(SeaCucumber::South),
                     '.' => None,
@@ -197,24 +130,41 @@ impl FromStr for SeaCucumberHerds {
             }
         }
 
-        Ok(herds)
+        Ok(Self::new(height, width, data))
     }
 }
 
-pub fn solve_a(input: &str) -> AocResult<iAoc> {
-    let mut herds = SeaCucumberHerds::from_str(input)?;
-    let mut steps = 0;
-    loop {
-        steps += 1;
-        let (updated_herds, changed) = herds.step();
-        if !changed {
-            break;
+pub const INFO: DayInfo = DayInfo {
+    strategies: &[],
+    params: &[],
+    animated: true,
+};
+
+pub struct SeaCucumbers;
+
+impl Solver for SeaCucumbers {
+    type Parsed = SeaCucumberHerds;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<SeaCucumberHerds> {
+        SeaCucumberHerds::from_str(input)
+    }
+
+    fn part_a(herds: &SeaCucumberHerds, context: &SolverContext) -> AocResult<SolutionValue> {
+        let mut herds = herds.clone();
+        let mut animator = Animator::new(context, "25.A")?;
+        animator.frame(|| herds.to_string())?;
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            if !herds.step() {
+                break;
+            }
+            animator.frame(|| herds.to_string())?;
         }
-        herds = updated_herds;
+        Ok(SolutionValue::from(steps as iAoc))
     }
-    Ok(steps as iAoc)
-}
 
-pub fn solve_b(_: &str) -> AocResult<iAoc> {
-    Ok(0)
+    fn part_b(_: &SeaCucumberHerds, _: &SolverContext) -> AocResult<SolutionValue> {
+        Ok(SolutionValue::from(0))
+    }
 }
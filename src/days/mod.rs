@@ -25,4 +25,7 @@ mod day23;
 mod day24;
 mod day25;
 
-pub use all::{solve, Solution};
+pub use all::{
+    implemented_days, info, input_path, is_stub, parse, puzzle, solve, solve_day, solve_parts,
+    Puzzle, Solution,
+};
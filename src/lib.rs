@@ -0,0 +1,17 @@
+//! Solutions to the 2021 Advent of Code, for use from other programs without
+//! running the command line tool.
+//!
+//! `solve_day` solves one part of a day for the given input with default
+//! options. Everything the command line tool does with its options is
+//! available through `days` and `program`.
+
+#[macro_use]
+extern crate num_derive;
+
+pub mod common;
+pub mod days;
+pub mod program;
+
+pub use common::{iAoc, AocError, AocResult, SolutionValue};
+pub use days::{implemented_days, puzzle, solve_day, Puzzle};
+pub use program::SolutionPart;
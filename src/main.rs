@@ -1,37 +1,351 @@
-#[macro_use]
-extern crate num_derive;
+use advent_of_code_2021::common::profile::CountingAllocator;
+#[cfg(feature = "http")]
+use advent_of_code_2021::common::AocError;
+use advent_of_code_2021::common::{animate, AocResult, DayInfo};
+#[cfg(feature = "http")]
+use advent_of_code_2021::days::solve;
+use advent_of_code_2021::days::{
+    implemented_days, info, input_path, is_stub, parse, puzzle, solve_parts, Solution,
+};
+#[cfg(feature = "http")]
+use advent_of_code_2021::program::fetch::{self, Verdict};
+#[cfg(feature = "tui")]
+use advent_of_code_2021::program::tui;
+use advent_of_code_2021::program::{
+    batch, check, csv, examples, history, report, scaffold, summary, Colors, ExitStatus,
+    ProgramArgs, SolutionPart,
+};
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
 
-mod common;
-mod days;
-mod program;
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
-use crate::days::solve;
-use crate::program::ProgramArgs;
-use std::env;
+fn print_strategies(day: u8, info: &DayInfo) {
+    if info.strategies.is_empty() {
+        return println!("Day {} has no strategies", day);
+    }
+    println!("Day {} strategies:", day);
+    for (i, strategy) in info.strategies.iter().enumerate() {
+        let default = if i == 0 { " (default)" } else { "" };
+        println!("  {}{}", strategy.name, default);
+        println!("      {}", strategy.description);
+    }
+}
+
+fn print_params(day: u8, info: &DayInfo) {
+    let animation_params: &[_] = if info.animated { &animate::PARAMS } else { &[] };
+    if info.params.is_empty() && animation_params.is_empty() {
+        return println!("Day {} has no params", day);
+    }
+    println!("Day {} params:", day);
+    for param in info.params.iter().chain(animation_params) {
+        println!("  {}={}", param.name, param.values);
+        println!("      {}", param.description);
+        println!("      Default: {}", param.default);
+    }
+}
+
+fn print_days(args: &ProgramArgs) {
+    println!("Day  Part A  Part B  Input    Title");
+    for day in implemented_days() {
+        let part = |part| if is_stub(day, part) { "stub" } else { "yes" };
+        let path = input_path(&args.with_day(day));
+        let input = if Path::new(&path).exists() {
+            "present"
+        } else {
+            "missing"
+        };
+        let puzzle = puzzle(day);
+        println!(
+            "{:<4} {:<7} {:<7} {:<8} {}",
+            day,
+            part(SolutionPart::A),
+            part(SolutionPart::B),
+            input,
+            puzzle.title
+        );
+        if args.verbose() {
+            println!("     {}", puzzle.summary);
+            println!("     {}", puzzle.url());
+        }
+    }
+}
+
+/// Prints only the answer, leaving out everything `print_solution` would
+/// otherwise show on stdout.
+fn print_answer(args: &ProgramArgs, solution: &Solution) {
+    let colors = args.colors();
+    println!("{}", args.value_format().format(solution.solution()));
+    if let Err(err) = csv::append(args, solution) {
+        eprintln!("failed to write timings to CSV: {}", colors.error(err));
+    }
+    if let Err(err) = history::record(args, solution) {
+        eprintln!("failed to record answer history: {}", colors.error(err));
+    }
+    for warning in solution.warnings() {
+        eprintln!("{}", warning);
+    }
+}
+
+fn print_solution(args: &ProgramArgs, solution: &Solution) {
+    if args.quiet() {
+        return print_answer(args, solution);
+    }
+    let colors = args.colors();
+    let puzzle = puzzle(args.day());
+    println!("Day {}, Part {}: {}", args.day(), args.part(), puzzle.title);
+    let time = match solution.parse_time() {
+        None => format!("{} us", solution.time().as_micros()),
+        Some(parse_time) => format!(
+            "{} us: {} us parsing, {} us solving",
+            solution.time().as_micros(),
+            parse_time.as_micros(),
+            solution.time().saturating_sub(parse_time).as_micros()
+        ),
+    };
+    println!(
+        "Solution: {} ({})",
+        colors.answer(args.value_format().format(solution.solution())),
+        colors.time(time)
+    );
+    if solution.runs() > 1 {
+        println!(
+            "Bench: {} runs, {}",
+            solution.runs(),
+            colors.time(format!(
+                "{} us fastest, {} us slowest",
+                solution.fastest().as_micros(),
+                solution.slowest().as_micros()
+            ))
+        );
+    }
+    if let Some(cold_times) = solution.cold_times() {
+        println!(
+            "Cold: {}",
+            colors.time(format!(
+                "{} us mean, {} us reading input",
+                cold_times.time().as_micros(),
+                cold_times.read_time().as_micros()
+            ))
+        );
+    }
+    if args.verbose() {
+        println!("Puzzle: {}", puzzle.url());
+        println!("Approach: {}", puzzle.summary);
+        println!("Input: {}", solution.input_hash());
+    }
+    if let Err(err) = csv::append(args, solution) {
+        eprintln!("failed to write timings to CSV: {}", colors.error(err));
+    }
+    match history::record(args, solution) {
+        Err(err) => eprintln!("failed to record answer history: {}", colors.error(err)),
+        Ok(Some(change)) => println!("{}", change),
+        Ok(None) => (),
+    }
+    if let Some(counters) = solution.counters() {
+        println!(
+            "Counters: {} allocations, {} hash inserts, {} hash lookups",
+            counters.allocations, counters.hash_inserts, counters.hash_lookups
+        );
+    }
+    for warning in solution.warnings() {
+        eprintln!("{}", warning);
+    }
+}
+
+/// Solves the selected part and submits its answer to adventofcode.com.
+#[cfg(feature = "http")]
+fn submit(args: &ProgramArgs) -> ExitStatus {
+    let colors = args.colors();
+    if is_stub(args.day(), args.part()) {
+        let err = AocError::new(format!(
+            "day {} part {} has no puzzle, so there is no answer to submit",
+            args.day(),
+            args.part()
+        ));
+        eprintln!("{}", colors.error(err));
+        return ExitStatus::BadArguments;
+    }
+    let solution = match solve(args) {
+        Err(err) => {
+            let status = ExitStatus::of_error(&err);
+            eprintln!("{}", colors.error(err));
+            return status;
+        }
+        Ok(solution) => solution,
+    };
+    let answer = solution.solution().to_string();
+    println!(
+        "Day {}, Part {}: submitting {}",
+        args.day(),
+        args.part(),
+        colors.answer(&answer)
+    );
+    match fetch::submit(args, &answer) {
+        Err(err) => {
+            eprintln!("{}", colors.error(err));
+            ExitStatus::SolverError
+        }
+        Ok(verdict) => {
+            println!("{}", verdict);
+            match verdict {
+                Verdict::Correct => ExitStatus::Success,
+                Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong => ExitStatus::Mismatch,
+                _ => ExitStatus::SolverError,
+            }
+        }
+    }
+}
+
+/// Exits with the given status, or with a mismatch if the answers did not
+/// all match.
+fn verified(result: AocResult<bool>, colors: &Colors) -> ExitStatus {
+    match result {
+        Err(err) => {
+            eprintln!("{}", colors.error(err));
+            ExitStatus::SolverError
+        }
+        Ok(true) => ExitStatus::Success,
+        Ok(false) => ExitStatus::Mismatch,
+    }
+}
 
-fn main() {
+fn run() -> ExitStatus {
     let mut args = env::args();
     let program_name = match args.next() {
-        None => return eprintln!("args is empty"),
+        None => {
+            eprintln!("args is empty");
+            return ExitStatus::BadArguments;
+        }
         Some(name) => name,
     };
     let args = match ProgramArgs::parse_from_args(args) {
         Err(err) => {
             eprintln!("{}", err);
-            return eprintln!("{}", ProgramArgs::usage(&program_name));
+            eprintln!("{}", ProgramArgs::usage(&program_name));
+            return ExitStatus::BadArguments;
         }
         Ok(args) => args,
     };
-    let solution = match solve(&args) {
+    if args.list() {
+        print_days(&args);
+        return ExitStatus::Success;
+    }
+    let colors = args.colors();
+    #[cfg(feature = "http")]
+    if args.submit() {
+        return submit(&args);
+    }
+    #[cfg(feature = "tui")]
+    if args.tui() {
+        return match tui::run(&args) {
+            Err(err) => {
+                eprintln!("{}", colors.error(err));
+                ExitStatus::SolverError
+            }
+            Ok(()) => ExitStatus::Success,
+        };
+    }
+    if args.new_day() {
+        return match scaffold::new_day(&args) {
+            Err(err) => {
+                eprintln!("{}", colors.error(err));
+                ExitStatus::SolverError
+            }
+            Ok(()) => ExitStatus::Success,
+        };
+    }
+    if args.summary() {
+        return match summary::print(&args) {
+            Err(err) => {
+                eprintln!("{}", colors.error(err));
+                ExitStatus::SolverError
+            }
+            Ok(()) => ExitStatus::Success,
+        };
+    }
+    if args.list_strategies() || args.list_params() {
+        for day in args.days() {
+            let info = match info(&args.with_day(*day)) {
+                Err(err) => {
+                    eprintln!("{}", colors.error(err));
+                    return ExitStatus::BadArguments;
+                }
+                Ok(info) => info,
+            };
+            if args.list_strategies() {
+                print_strategies(*day, info);
+            }
+            if args.list_params() {
+                print_params(*day, info);
+            }
+        }
+        return ExitStatus::Success;
+    }
+    if args.parse_only() {
+        let mut status = ExitStatus::Success;
+        for day in args.days() {
+            match parse(&args.with_day(*day)) {
+                Err(err) => {
+                    status = status.or(ExitStatus::of_error(&err));
+                    eprintln!("Day {}: {}", day, colors.error(err));
+                }
+                Ok((time, warnings)) => {
+                    println!("Day {}: input parsed ({} us)", day, time.as_micros());
+                    for warning in warnings {
+                        eprintln!("{}", warning);
+                    }
+                }
+            };
+        }
+        return status;
+    }
+    if args.examples() {
+        return verified(examples::run(&args), &colors);
+    }
+    if args.check() {
+        return verified(check::run(&args), &colors);
+    }
+    if args.is_batch() {
+        let results = batch::run(&args);
+        if let Err(err) = report::write(&results, &args) {
+            eprintln!("failed to write Markdown report: {}", colors.error(err));
+        }
+        batch::print_summary(&results, &args);
+        return if batch::any_failed(&results) {
+            ExitStatus::SolverError
+        } else {
+            ExitStatus::Success
+        };
+    }
+    let solutions = match solve_parts(&args) {
         Err(err) => {
-            return eprintln!("{}", err);
+            let status = ExitStatus::of_error(&err);
+            eprintln!("{}", colors.error(err));
+            return status;
         }
-        Ok(solution) => solution,
+        Ok(solutions) => solutions,
     };
-    println!("Day {}, Part {}", args.day(), args.part());
-    println!(
-        "Solution: {} ({} us)",
-        solution.solution(),
-        solution.time().as_micros()
-    );
+    let both_parts = solutions.len() > 1;
+    let mut status = ExitStatus::Success;
+    for (part, solution) in solutions {
+        match solution {
+            Err(err) => {
+                status = status.or(ExitStatus::of_error(&err));
+                if both_parts {
+                    eprintln!("Day {}, Part {}: {}", args.day(), part, colors.error(err))
+                } else {
+                    eprintln!("{}", colors.error(err))
+                }
+            }
+            Ok(solution) => print_solution(&args.with_part(part), &solution),
+        }
+    }
+    status
+}
+
+fn main() -> ExitCode {
+    run().into()
 }
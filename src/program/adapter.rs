@@ -0,0 +1,227 @@
+use crate::common::{AocError, AocResult, IntoAocResult};
+
+/// Transforms the input text before the solver sees it.
+pub trait InputAdapter {
+    /// Name used to select the adapter with `--adapter`.
+    fn name(&self) -> &'static str;
+
+    fn adapt(&self, input: String) -> AocResult<String>;
+}
+
+/// Passes the input through unchanged.
+struct Identity;
+
+impl InputAdapter for Identity {
+    fn name(&self) -> &'static str {
+        "identity"
+    }
+
+    fn adapt(&self, input: String) -> AocResult<String> {
+        Ok(input)
+    }
+}
+
+/// Removes `//` comments, dropping lines that only hold a comment.
+///
+/// Puzzle inputs use `#` as data, so it cannot start a comment.
+struct StripComments;
+
+impl InputAdapter for StripComments {
+    fn name(&self) -> &'static str {
+        "strip-comments"
+    }
+
+    fn adapt(&self, input: String) -> AocResult<String> {
+        let mut output = String::with_capacity(input.len());
+        for line in input.lines() {
+            match line.split_once("//") {
+                None => output += line,
+                Some((code, _)) if code.trim().is_empty() => continue,
+                Some((code, _)) => output += code.trim_end(),
+            }
+            output.push('\n');
+        }
+        Ok(output)
+    }
+}
+
+/// Puts every comma-separated value on its own line, so a list given as
+/// `1,2,3` reads like one given a value per line.
+///
+/// A value in double quotes may hold commas, and `""` inside it stands for
+/// one quote.
+struct CsvToLines;
+
+impl CsvToLines {
+    /// Splits a line into its values, removing the quotes around them.
+    fn values(line: &str) -> AocResult<Vec<String>> {
+        let mut values = Vec::new();
+        let mut value = String::new();
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    value.push('"');
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => values.push(std::mem::take(&mut value)),
+                _ => value.push(ch),
+            }
+        }
+        if quoted {
+            return Err(AocError::new("quoted value is not closed"));
+        }
+        values.push(value);
+        Ok(values)
+    }
+}
+
+impl InputAdapter for CsvToLines {
+    fn name(&self) -> &'static str {
+        "csv-to-lines"
+    }
+
+    fn adapt(&self, input: String) -> AocResult<String> {
+        let mut output = String::with_capacity(input.len());
+        for (i, line) in input.lines().enumerate() {
+            for value in Self::values(line).map_err(|err| err.at_line(i + 1))? {
+                let value = value.trim();
+                if !value.is_empty() {
+                    output += value;
+                    output.push('\n');
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Decodes standard base64, ignoring whitespace, into UTF-8 text.
+struct Base64Decode;
+
+impl Base64Decode {
+    fn sextet(byte: u8) -> AocResult<u32> {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => {
+                return Err(AocError::new(format!(
+                    "invalid base64 character '{}'",
+                    byte as char
+                )))
+            }
+        };
+        Ok(value as u32)
+    }
+}
+
+impl InputAdapter for Base64Decode {
+    fn name(&self) -> &'static str {
+        "base64-decode"
+    }
+
+    fn adapt(&self, input: String) -> AocResult<String> {
+        let encoded = input
+            .bytes()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect::<Vec<_>>();
+        let unpadded = encoded
+            .strip_suffix(b"==")
+            .or_else(|| encoded.strip_suffix(b"="))
+            .unwrap_or(&encoded);
+        if encoded.len() % 4 != 0 || unpadded.len() % 4 == 1 {
+            return Err(AocError::new("base64 input has an invalid length"));
+        }
+
+        let mut decoded = Vec::with_capacity(unpadded.len() / 4 * 3);
+        for chunk in unpadded.chunks(4) {
+            // Each character holds 6 bits, so a chunk of n characters holds
+            // n - 1 whole bytes.
+            let mut bits = 0;
+            for (i, byte) in chunk.iter().enumerate() {
+                bits |= Self::sextet(*byte)? << (18 - 6 * i);
+            }
+            decoded.extend(&bits.to_be_bytes()[1..chunk.len()]);
+        }
+        String::from_utf8(decoded).into_aoc_result_msg("base64 input is not UTF-8 text")
+    }
+}
+
+const ADAPTERS: [&dyn InputAdapter; 4] = [&Identity, &StripComments, &CsvToLines, &Base64Decode];
+
+/// Finds the adapter with the given name.
+pub fn adapter(name: &str) -> AocResult<&'static dyn InputAdapter> {
+    ADAPTERS
+        .iter()
+        .find(|adapter| adapter.name() == name)
+        .copied()
+        .ok_or_else(|| {
+            AocError::new(
+                "adapter must be identity, strip-comments, csv-to-lines, or base64-decode",
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapt(name: &str, input: &str) -> AocResult<String> {
+        adapter(name).unwrap().adapt(input.to_owned())
+    }
+
+    #[test]
+    fn strip_comments_drops_comment_lines_and_trailing_comments() {
+        let input = "// header\n1,2 // first\n#.#\n   // indented\n3\n";
+        assert_eq!(adapt("strip-comments", input).unwrap(), "1,2\n#.#\n3\n");
+    }
+
+    #[test]
+    fn csv_to_lines_splits_values_outside_quotes() {
+        let input = "1, 2,3\n\"a,b\",\"say \"\"hi\"\"\",,4\n";
+        assert_eq!(
+            adapt("csv-to-lines", input).unwrap(),
+            "1\n2\n3\na,b\nsay \"hi\"\n4\n"
+        );
+    }
+
+    #[test]
+    fn csv_to_lines_rejects_unclosed_quotes() {
+        let err = adapt("csv-to-lines", "1,2\n3,\"4\n").unwrap_err();
+        assert_eq!(err.message(), "line 2: quoted value is not closed");
+    }
+
+    #[test]
+    fn base64_decode_handles_padding_and_whitespace() {
+        assert_eq!(adapt("base64-decode", "").unwrap(), "");
+        assert_eq!(adapt("base64-decode", "MTIz").unwrap(), "123");
+        assert_eq!(adapt("base64-decode", "MTI=").unwrap(), "12");
+        assert_eq!(adapt("base64-decode", "MQ==").unwrap(), "1");
+        assert_eq!(adapt("base64-decode", "MSwy\nLDM=\n").unwrap(), "1,2,3");
+    }
+
+    #[test]
+    fn base64_decode_rejects_invalid_input() {
+        let message = |input| adapt("base64-decode", input).unwrap_err().message();
+        assert_eq!(message("MTI"), "base64 input has an invalid length");
+        assert_eq!(message("M==="), "invalid base64 character '='");
+        assert_eq!(message("MT!z"), "invalid base64 character '!'");
+    }
+
+    #[test]
+    fn base64_decode_rejects_text_that_is_not_utf8() {
+        // 0xff 0xfe is not valid UTF-8.
+        let err = adapt("base64-decode", "//4=").unwrap_err();
+        assert!(err.message().starts_with("base64 input is not UTF-8 text"));
+    }
+
+    #[test]
+    fn unknown_adapter_is_an_error() {
+        assert!(adapter("rot13").is_err());
+    }
+}
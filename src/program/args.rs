@@ -1,8 +1,15 @@
+use crate::common::math::OverflowPolicy;
 use crate::common::{AocError, AocResult};
-use std::env::Args;
+use crate::days::implemented_days;
+use crate::program::adapter::{self, InputAdapter};
+use crate::program::{ColorChoice, Colors, SortOrder, ValueFormat};
+use std::collections::HashMap;
+use std::env::{self, Args};
 use std::fmt::{Display, Formatter, Result as DisplayResult};
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub enum SolutionPart {
     A,
     B,
@@ -28,65 +35,838 @@ impl Display for SolutionPart {
     }
 }
 
+/// Filename that reads the input from standard input.
+const STDIN_FILENAME: &str = "-";
+
+/// Reads an environment variable, treating an empty one as unset.
+fn environment_variable(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Environment variable that sets the directory input files are read from.
+const INPUT_DIR_VARIABLE: &str = "AOC_INPUT_DIR";
+
+/// Environment variable that holds the adventofcode.com session cookie.
+#[cfg(feature = "http")]
+const SESSION_VARIABLE: &str = "AOC_SESSION";
+
+/// Environment variable that sets the year inputs are downloaded for.
+#[cfg(feature = "http")]
+const YEAR_VARIABLE: &str = "AOC_YEAR";
+
+#[derive(Clone)]
 pub struct ProgramArgs {
     day: u8,
+    days: Vec<u8>,
     part: SolutionPart,
+    all: bool,
+    both_parts: bool,
     filename: Option<String>,
+    input_dir: String,
+    #[cfg(feature = "http")]
+    session: Option<String>,
+    #[cfg(feature = "http")]
+    year: u16,
+    csv: Option<String>,
+    markdown: Option<String>,
+    adapters: Vec<&'static dyn InputAdapter>,
+    strategy: Option<String>,
+    params: HashMap<String, String>,
+    threads: usize,
+    bench: usize,
+    bench_cold: bool,
+    warmup: usize,
+    budget: Option<Duration>,
+    timeout: Option<Duration>,
+    progress: Option<Duration>,
+    verbosity: u8,
+    quiet: bool,
+    value_format: ValueFormat,
+    overflow: OverflowPolicy,
+    sort: SortOrder,
+    color: ColorChoice,
+    strict: bool,
+    explain_failure: bool,
+    parse_only: bool,
+    examples: bool,
+    check: bool,
+    list_strategies: bool,
+    list_params: bool,
+    list: bool,
+    summary: bool,
+    submit: bool,
+    new_day: bool,
+    tui: bool,
 }
 
 impl ProgramArgs {
-    pub fn new(day: u8, part: SolutionPart, filename: Option<String>) -> Self {
+    /// Creates arguments for the given day and part with default options.
+    pub fn new(day: u8, part: SolutionPart) -> Self {
         ProgramArgs {
             day,
+            days: vec![day],
             part,
-            filename,
+            all: false,
+            both_parts: false,
+            filename: None,
+            input_dir: String::from("input"),
+            #[cfg(feature = "http")]
+            session: None,
+            #[cfg(feature = "http")]
+            year: 2021,
+            csv: None,
+            markdown: None,
+            adapters: Vec::new(),
+            strategy: None,
+            params: HashMap::new(),
+            threads: 1,
+            bench: 1,
+            bench_cold: false,
+            warmup: 0,
+            budget: None,
+            timeout: None,
+            progress: None,
+            verbosity: 0,
+            quiet: false,
+            value_format: ValueFormat::Plain,
+            overflow: OverflowPolicy::BigInt,
+            sort: SortOrder::Day,
+            color: ColorChoice::Auto,
+            strict: false,
+            explain_failure: false,
+            parse_only: false,
+            examples: false,
+            check: false,
+            list_strategies: false,
+            list_params: false,
+            list: false,
+            summary: false,
+            submit: false,
+            new_day: false,
+            tui: false,
         }
     }
 
     pub fn day(&self) -> u8 {
-        return self.day;
+        self.day
+    }
+
+    /// All days selected by the day argument, which is more than one for a
+    /// batch run.
+    pub fn days(&self) -> &[u8] {
+        &self.days
+    }
+
+    pub fn is_batch(&self) -> bool {
+        self.all || self.days.len() > 1
+    }
+
+    /// Copies the arguments for running a single day.
+    pub fn with_day(&self, day: u8) -> Self {
+        ProgramArgs {
+            day,
+            days: vec![day],
+            ..self.clone()
+        }
     }
 
     pub fn part(&self) -> SolutionPart {
-        return self.part;
+        self.part
+    }
+
+    /// All parts selected, which is both parts when the part is `AB` or
+    /// omitted.
+    pub fn parts(&self) -> Vec<SolutionPart> {
+        if self.both_parts {
+            vec![SolutionPart::A, SolutionPart::B]
+        } else {
+            vec![self.part]
+        }
+    }
+
+    /// Copies the arguments for running a single part.
+    pub fn with_part(&self, part: SolutionPart) -> Self {
+        ProgramArgs {
+            part,
+            both_parts: false,
+            ..self.clone()
+        }
     }
 
     pub fn filename(&self) -> &Option<String> {
-        return &self.filename;
+        &self.filename
+    }
+
+    /// Directory that input files are read from, `input` unless
+    /// `AOC_INPUT_DIR` is set.
+    pub fn input_dir(&self) -> &str {
+        &self.input_dir
+    }
+
+    /// Session cookie for downloading inputs, from `AOC_SESSION`.
+    #[cfg(feature = "http")]
+    pub fn session(&self) -> Option<&str> {
+        self.session.as_deref()
+    }
+
+    /// Year that inputs are downloaded for, 2021 unless `AOC_YEAR` is set.
+    #[cfg(feature = "http")]
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// File that the time and answer of every run are appended to, if any.
+    pub fn csv(&self) -> Option<&str> {
+        self.csv.as_deref()
+    }
+
+    /// File that a Markdown table of the answers and times of a batch run is
+    /// written to, if any.
+    pub fn markdown(&self) -> Option<&str> {
+        self.markdown.as_deref()
+    }
+
+    /// Copies the arguments for reading the input from the given file.
+    pub fn with_filename(&self, filename: String) -> Self {
+        ProgramArgs {
+            filename: Some(filename),
+            ..self.clone()
+        }
+    }
+
+    /// Copies the arguments with the given params added.
+    pub fn with_params(&self, params: &[(String, String)]) -> Self {
+        let mut args = self.clone();
+        args.params.extend(params.iter().cloned());
+        args
+    }
+
+    /// Whether the input is read from standard input, which is selected by
+    /// giving `-` as the filename.
+    pub fn stdin(&self) -> bool {
+        self.filename.as_deref() == Some(STDIN_FILENAME)
+    }
+
+    /// The URL to download the input from, if one was given as the input
+    /// file.
+    pub fn url(&self) -> Option<&str> {
+        self.filename
+            .as_deref()
+            .filter(|filename| filename.starts_with("http://") || filename.starts_with("https://"))
+    }
+
+    /// Adapters applied to the input text, in order, before solving.
+    pub fn adapters(&self) -> &[&'static dyn InputAdapter] {
+        &self.adapters
+    }
+
+    pub fn strategy(&self) -> &Option<String> {
+        &self.strategy
+    }
+
+    pub fn params(&self) -> &HashMap<String, String> {
+        &self.params
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn bench(&self) -> usize {
+        self.bench
+    }
+
+    /// Whether benchmarks also re-read the input from disk on every run.
+    pub fn bench_cold(&self) -> bool {
+        self.bench_cold
+    }
+
+    /// Number of runs before the measured ones whose times are thrown away.
+    pub fn warmup(&self) -> usize {
+        self.warmup
+    }
+
+    /// Wall-clock time a batch run may take before it skips the remaining days.
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    /// Wall-clock time a single solver run may take before it is abandoned.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Time between reports of how far long-running solvers have gotten, or
+    /// `None` if progress is not reported.
+    pub fn progress(&self) -> Option<Duration> {
+        self.progress
+    }
+
+    pub fn verbose(&self) -> bool {
+        self.verbosity > 0
+    }
+
+    /// How many times `-v` was given, which sets how much solvers log.
+    pub fn verbosity(&self) -> u8 {
+        self.verbosity
+    }
+
+    /// Whether to print only the answer, for piping it into other programs.
+    pub fn quiet(&self) -> bool {
+        self.quiet
+    }
+
+    pub fn value_format(&self) -> ValueFormat {
+        self.value_format
+    }
+
+    pub fn overflow(&self) -> OverflowPolicy {
+        self.overflow
+    }
+
+    /// Colors for the program output.
+    pub fn colors(&self) -> Colors {
+        Colors::new(self.color)
+    }
+
+    /// Order of the rows in the summary of a batch run.
+    pub fn sort(&self) -> SortOrder {
+        self.sort
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Whether to show the input around the line an error happened on.
+    pub fn explain_failure(&self) -> bool {
+        self.explain_failure
+    }
+
+    pub fn parse_only(&self) -> bool {
+        self.parse_only
+    }
+
+    /// Whether to check the example inputs against their expected answers
+    /// instead of solving the puzzle input.
+    pub fn examples(&self) -> bool {
+        self.examples
     }
 
-    fn get_next_string_optional(args: &mut Args) -> Option<String> {
-        args.next()
+    /// Whether to check the answers for the puzzle inputs against the
+    /// recorded correct answers.
+    pub fn check(&self) -> bool {
+        self.check
     }
 
-    fn get_next_string(args: &mut Args, name: &str) -> AocResult<String> {
-        match Self::get_next_string_optional(args) {
-            None => Err(AocError::new(format!("missing {}", name))),
-            Some(parsed) => Ok(parsed),
+    pub fn list_strategies(&self) -> bool {
+        self.list_strategies
+    }
+
+    pub fn list_params(&self) -> bool {
+        self.list_params
+    }
+
+    /// Whether to list every day and its input instead of solving anything.
+    pub fn list(&self) -> bool {
+        self.list
+    }
+
+    /// Whether to summarize the stars, gaps, and timings of every day instead
+    /// of solving anything.
+    pub fn summary(&self) -> bool {
+        self.summary
+    }
+
+    /// Whether to submit the answer to adventofcode.com after solving.
+    #[cfg(feature = "http")]
+    pub fn submit(&self) -> bool {
+        self.submit
+    }
+
+    /// Whether to browse and re-run every day in the terminal dashboard
+    /// instead of solving anything up front.
+    #[cfg(feature = "tui")]
+    pub fn tui(&self) -> bool {
+        self.tui
+    }
+
+    /// Whether to generate the skeleton of a new day instead of solving
+    /// anything.
+    pub fn new_day(&self) -> bool {
+        self.new_day
+    }
+
+    fn parse_day(input: &str) -> AocResult<u8> {
+        match input.parse::<u8>() {
+            Err(_) => Err(AocError::new("day must be an integer")),
+            Ok(day) if day == 0 || day > 31 => Err(AocError::new("day must be between 1 and 31")),
+            Ok(day) => Ok(day),
         }
     }
 
-    fn get_next_integer(args: &mut Args, name: &str) -> AocResult<u8> {
-        match Self::get_next_string(args, name)?.parse::<u8>() {
-            Err(_) => Err(AocError::new(format!("{} must be an integer", name))),
-            Ok(parsed) => Ok(parsed),
+    /// Parses a comma-separated list of days and day ranges, such as
+    /// `1-5,12,20-25`.
+    fn parse_days(input: &str) -> AocResult<Vec<u8>> {
+        let mut days = Vec::new();
+        for entry in input.split(',') {
+            let (first, last) = entry.split_once('-').unwrap_or((entry, entry));
+            let (first, last) = (Self::parse_day(first)?, Self::parse_day(last)?);
+            if first > last {
+                return Err(AocError::new(format!("day range {} is backwards", entry)));
+            }
+            days.extend(first..=last);
         }
+        Ok(days)
     }
 
-    pub fn parse_from_args(mut args: Args) -> AocResult<Self> {
-        let day = Self::get_next_integer(&mut args, "day")?;
-        if day <= 0 || day > 31 {
-            return Err(AocError::new("day must be between 1 and 31"));
+    /// Parses a part, returning `None` for `AB`, which selects both parts.
+    fn parse_part(input: &str) -> AocResult<Option<SolutionPart>> {
+        match input {
+            "AB" => Ok(None),
+            _ => SolutionPart::from_string(input)
+                .map(Some)
+                .map_err(|_| AocError::new("part must be A, B, or AB")),
         }
+    }
 
-        let part = SolutionPart::from_string(&Self::get_next_string(&mut args, "part")?)?;
+    fn parse_positive_integer(input: &str, name: &str) -> AocResult<usize> {
+        input
+            .parse::<usize>()
+            .ok()
+            .filter(|parsed| *parsed > 0)
+            .ok_or_else(|| AocError::new(format!("{} must be a positive integer", name)))
+    }
 
-        let filename = Self::get_next_string_optional(&mut args);
+    fn set_days(&mut self, days: Vec<u8>) -> AocResult<()> {
+        if self.all || !self.days.is_empty() {
+            return Err(AocError::new("days may only be given once"));
+        }
+        self.day = days[0];
+        self.days = days;
+        Ok(())
+    }
 
-        Ok(ProgramArgs::new(day, part, filename))
+    fn set_part(&mut self, part: Option<SolutionPart>, part_given: &mut bool) -> AocResult<()> {
+        if *part_given {
+            return Err(AocError::new("part may only be given once"));
+        }
+        *part_given = true;
+        self.part = part.unwrap_or(SolutionPart::A);
+        self.both_parts = part.is_none();
+        Ok(())
+    }
+
+    fn set_filename(&mut self, filename: String) -> AocResult<()> {
+        if self.filename.is_some() {
+            return Err(AocError::new("only one input file may be given"));
+        }
+        self.filename = Some(filename);
+        Ok(())
     }
 
+    /// Applies a named option, with its value if it takes one.
+    fn apply(&mut self, name: &str, value: String, part_given: &mut bool) -> AocResult<()> {
+        match name {
+            "day" => self.set_days(Self::parse_days(&value)?)?,
+            "part" => self.set_part(Self::parse_part(&value)?, part_given)?,
+            "input" => self.set_filename(value)?,
+            "all" => {
+                if !self.days.is_empty() {
+                    return Err(AocError::new("--all cannot be combined with days"));
+                }
+                // Every implemented day and both of its parts.
+                self.days = implemented_days().collect();
+                self.day = self.days[0];
+                self.all = true;
+                self.both_parts = true;
+                *part_given = true;
+            }
+            "stdin" => self.set_filename(STDIN_FILENAME.to_owned())?,
+            "adapter" => self.adapters.push(adapter::adapter(&value)?),
+            "strategy" => self.strategy = Some(value),
+            "param" => {
+                let (name, value) = value
+                    .split_once('=')
+                    .ok_or_else(|| AocError::new("param must be of the form name=value"))?;
+                self.params.insert(name.to_owned(), value.to_owned());
+            }
+            "threads" => self.threads = Self::parse_positive_integer(&value, "threads")?,
+            "bench" => self.bench = Self::parse_positive_integer(&value, "bench")?,
+            "bench-cold" => self.bench_cold = true,
+            "warmup" => {
+                self.warmup = value
+                    .parse::<usize>()
+                    .map_err(|_| AocError::new("warmup must be a non-negative integer"))?
+            }
+            "csv" => self.csv = Some(value),
+            "markdown" => self.markdown = Some(value),
+            "budget" => {
+                let budget = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|budget| *budget > 0.0)
+                    .and_then(|budget| Duration::try_from_secs_f64(budget).ok())
+                    .ok_or_else(|| AocError::new("budget must be a positive number of seconds"))?;
+                self.budget = Some(budget);
+            }
+            "timeout" => {
+                let timeout = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|timeout| *timeout > 0.0)
+                    .and_then(|timeout| Duration::try_from_secs_f64(timeout).ok())
+                    .ok_or_else(|| AocError::new("timeout must be a positive number of seconds"))?;
+                self.timeout = Some(timeout);
+            }
+            "progress" => {
+                let interval = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|interval| *interval > 0.0)
+                    .and_then(|interval| Duration::try_from_secs_f64(interval).ok())
+                    .ok_or_else(|| {
+                        AocError::new("progress must be a positive number of seconds")
+                    })?;
+                self.progress = Some(interval);
+            }
+            "verbose" => self.verbosity = self.verbosity.saturating_add(1),
+            "quiet" => self.quiet = true,
+            "format-value" => self.value_format = ValueFormat::from_str(&value)?,
+            "overflow" => self.overflow = OverflowPolicy::from_str(&value)?,
+            "sort" => self.sort = SortOrder::from_str(&value)?,
+            "color" => self.color = ColorChoice::from_str(&value)?,
+            "strict" => self.strict = true,
+            "explain-failure" => self.explain_failure = true,
+            "parse-only" => self.parse_only = true,
+            "examples" => self.examples = true,
+            "check" => self.check = true,
+            "list-strategies" => self.list_strategies = true,
+            "list-params" => self.list_params = true,
+            _ => return Err(AocError::new(format!("unknown option --{}", name))),
+        }
+        Ok(())
+    }
+
+    /// Reads the options that are set through environment variables.
+    fn read_environment(&mut self) -> AocResult<()> {
+        if let Some(input_dir) = environment_variable(INPUT_DIR_VARIABLE) {
+            self.input_dir = input_dir;
+        }
+        // Only downloads need the session and year.
+        #[cfg(feature = "http")]
+        self.read_download_environment()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "http")]
+    fn read_download_environment(&mut self) -> AocResult<()> {
+        self.session =
+            environment_variable(SESSION_VARIABLE).map(|session| session.trim().to_owned());
+        if let Some(year) = environment_variable(YEAR_VARIABLE) {
+            self.year = year
+                .trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|year| *year >= 2015)
+                .ok_or_else(|| {
+                    AocError::new(format!("{} must be a year from 2015 on", YEAR_VARIABLE))
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Parses the program arguments.
+    ///
+    /// Options are named, with a long form such as `--day 5` or `--day=5`,
+    /// and for some a short form such as `-d 5`. The day, part, and input file
+    /// can also be given by position, in that order, as in `5 A input.txt`.
+    pub fn parse_from_args(args: Args) -> AocResult<Self> {
+        let mut program_args = ProgramArgs::new(1, SolutionPart::A);
+        program_args.read_environment()?;
+        program_args.days.clear();
+        // Both parts run if the part is AB or left out.
+        program_args.both_parts = true;
+        let mut part_given = false;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (spec, inline_value) = if let Some(long) = arg.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    None => (long, None),
+                    Some((name, value)) => (name, Some(value.to_owned())),
+                };
+                let spec = OPTIONS
+                    .iter()
+                    .find(|spec| spec.long == name)
+                    .ok_or_else(|| AocError::new(format!("unknown option {}", arg)))?;
+                (spec, value)
+            } else if arg.len() == 2 && arg.starts_with('-') && arg != STDIN_FILENAME {
+                let short = arg.chars().nth(1);
+                let spec = OPTIONS
+                    .iter()
+                    .find(|spec| spec.short.is_some() && spec.short == short)
+                    .ok_or_else(|| AocError::new(format!("unknown option {}", arg)))?;
+                (spec, None)
+            } else if arg.len() > 2 && arg.starts_with('-') {
+                // Short flags can be grouped, such as -vv.
+                for short in arg.chars().skip(1) {
+                    let spec = OPTIONS
+                        .iter()
+                        .find(|spec| spec.short == Some(short) && spec.value.is_none())
+                        .ok_or_else(|| AocError::new(format!("unknown option {}", arg)))?;
+                    program_args.apply(spec.long, String::new(), &mut part_given)?;
+                }
+                continue;
+            } else {
+                // Positional arguments fill in the day, part, and input file,
+                // unless the first one is a subcommand. The submit and new-day
+                // subcommands take a day of their own.
+                if program_args.list || program_args.summary || program_args.tui {
+                    return Err(AocError::new(format!("unexpected argument {}", arg)));
+                } else if program_args.days.is_empty() && !program_args.all && arg == "list" {
+                    program_args.list = true;
+                } else if program_args.days.is_empty() && !program_args.all && arg == "summary" {
+                    program_args.summary = true;
+                } else if program_args.days.is_empty() && !program_args.all && arg == "tui" {
+                    program_args.tui = true;
+                } else if program_args.days.is_empty()
+                    && !program_args.all
+                    && !program_args.submit
+                    && arg == "submit"
+                {
+                    program_args.submit = true;
+                } else if program_args.days.is_empty()
+                    && !program_args.all
+                    && !program_args.new_day
+                    && arg == "new-day"
+                {
+                    program_args.new_day = true;
+                } else if program_args.days.is_empty() && !program_args.all {
+                    program_args.set_days(Self::parse_days(&arg)?)?;
+                } else if !part_given && matches!(arg.as_str(), "A" | "B" | "AB") {
+                    program_args.set_part(Self::parse_part(&arg)?, &mut part_given)?;
+                } else {
+                    program_args.set_filename(arg)?;
+                }
+                continue;
+            };
+
+            let value = match (spec.value, inline_value) {
+                (None, None) => String::new(),
+                (None, Some(_)) => {
+                    return Err(AocError::new(format!(
+                        "--{} does not take a value",
+                        spec.long
+                    )))
+                }
+                (Some(_), Some(value)) => value,
+                (Some(_), None) => args
+                    .next()
+                    .ok_or_else(|| AocError::new(format!("missing {}", spec.long)))?,
+            };
+            program_args.apply(spec.long, value, &mut part_given)?;
+        }
+
+        if program_args.tui && !cfg!(feature = "tui") {
+            return Err(AocError::new(
+                "tui requires the tui feature, which this build does not have",
+            ));
+        }
+
+        if program_args.list || program_args.summary || program_args.tui {
+            return Ok(program_args);
+        }
+
+        if program_args.days.is_empty() {
+            return Err(AocError::new("missing day"));
+        }
+
+        if program_args.is_batch() && program_args.filename.is_some() {
+            return Err(AocError::new(
+                "an input file cannot be given for more than one day",
+            ));
+        }
+
+        if program_args.markdown.is_some() && !program_args.is_batch() {
+            return Err(AocError::new(
+                "--markdown reports on a batch run, so it needs more than one day",
+            ));
+        }
+
+        if program_args.quiet && program_args.is_batch() {
+            return Err(AocError::new(
+                "--quiet prints the answers for a single day, so it cannot be used for more than one day",
+            ));
+        }
+
+        if program_args.quiet && program_args.verbose() {
+            return Err(AocError::new(
+                "--quiet and --verbose cannot be used together",
+            ));
+        }
+
+        if program_args.submit {
+            program_args.check_submit()?;
+        }
+
+        if program_args.new_day
+            && (program_args.is_batch()
+                || program_args.filename.is_some()
+                || !program_args.params.is_empty())
+        {
+            return Err(AocError::new(
+                "new-day generates a single day, so it cannot be given more days, an input file, or params",
+            ));
+        }
+
+        if program_args.check && program_args.filename.is_some() {
+            return Err(AocError::new(
+                "--check compares against the puzzle input, so an input file cannot be given",
+            ));
+        }
+
+        if program_args.threads > 1 && !cfg!(feature = "parallel") {
+            return Err(AocError::new(
+                "--threads requires the parallel feature, which this build does not have",
+            ));
+        }
+
+        if program_args.stdin() && program_args.bench_cold {
+            return Err(AocError::new(
+                "--bench-cold cannot be used with input from stdin",
+            ));
+        }
+
+        if program_args.url().is_some() && program_args.bench_cold {
+            return Err(AocError::new(
+                "--bench-cold cannot be used with input from a URL",
+            ));
+        }
+
+        Ok(program_args)
+    }
+
+    /// Checks that the arguments select a single answer to the puzzle input,
+    /// which is all that can be submitted.
+    fn check_submit(&self) -> AocResult<()> {
+        if !cfg!(feature = "http") {
+            return Err(AocError::new(
+                "submit requires the http feature, which this build does not have",
+            ));
+        }
+        if self.is_batch() || self.parts().len() > 1 {
+            return Err(AocError::new(
+                "submit sends a single answer, so it needs one day and part A or B",
+            ));
+        }
+        if self.filename.is_some() || !self.params.is_empty() {
+            return Err(AocError::new(
+                "submit sends the answer for the puzzle input, so an input file or params cannot be given",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builds the usage string from the table of options.
     pub fn usage(program_name: &str) -> String {
-        format!("{} [1-31] [A|B]", program_name)
+        let mut usage = format!(
+            "{0} list\n{0} summary [--csv path]\n{0} tui [-v|--verbose]...\n{0} submit day A|B\n{0} new-day day\n{0} [days] [A|B|AB] [filename|url|-]",
+            program_name
+        );
+        for spec in &OPTIONS {
+            let mut option = format!("--{}", spec.long);
+            if let Some(short) = spec.short {
+                option = format!("-{}|{}", short, option);
+            }
+            if let Some(value) = spec.value {
+                option = format!("{} {}", option, value);
+            }
+            let repeat = if spec.repeatable { "..." } else { "" };
+            usage += &format!(" [{}]{}", option, repeat);
+        }
+        usage
     }
 }
+
+/// A named program option.
+struct OptionSpec {
+    long: &'static str,
+    short: Option<char>,
+    /// Placeholder for the option's value in the usage string, or `None` for
+    /// options that take no value.
+    value: Option<&'static str>,
+    /// Whether the option may be given more than once.
+    repeatable: bool,
+}
+
+impl OptionSpec {
+    const fn flag(long: &'static str) -> Self {
+        OptionSpec {
+            long,
+            short: None,
+            value: None,
+            repeatable: false,
+        }
+    }
+
+    const fn value(long: &'static str, value: &'static str) -> Self {
+        OptionSpec {
+            long,
+            short: None,
+            value: Some(value),
+            repeatable: false,
+        }
+    }
+
+    const fn short(self, short: char) -> Self {
+        OptionSpec {
+            short: Some(short),
+            ..self
+        }
+    }
+
+    const fn repeatable(self) -> Self {
+        OptionSpec {
+            repeatable: true,
+            ..self
+        }
+    }
+}
+
+const OPTIONS: [OptionSpec; 30] = [
+    OptionSpec::value("day", "1-31[,first-last]...").short('d'),
+    OptionSpec::value("part", "A|B|AB").short('p'),
+    OptionSpec::value("input", "filename|url|-").short('i'),
+    OptionSpec::flag("all"),
+    OptionSpec::flag("stdin"),
+    OptionSpec::value(
+        "adapter",
+        "identity|strip-comments|csv-to-lines|base64-decode",
+    )
+    .repeatable(),
+    OptionSpec::value("strategy", "name").short('s'),
+    OptionSpec::value("param", "name=value").repeatable(),
+    OptionSpec::value("threads", "n").short('t'),
+    OptionSpec::value("bench", "runs").short('b'),
+    OptionSpec::flag("bench-cold"),
+    OptionSpec::value("warmup", "runs"),
+    OptionSpec::value("budget", "seconds"),
+    OptionSpec::value("timeout", "seconds"),
+    OptionSpec::value("progress", "seconds"),
+    OptionSpec::value("csv", "path"),
+    OptionSpec::value("markdown", "path"),
+    OptionSpec::flag("verbose").short('v').repeatable(),
+    OptionSpec::flag("quiet").short('q'),
+    OptionSpec::value("format-value", "plain|pretty|hex|bin"),
+    OptionSpec::value("overflow", "check|saturate|wrap|bigint"),
+    OptionSpec::value("sort", "day|time"),
+    OptionSpec::value("color", "auto|always|never"),
+    OptionSpec::flag("strict"),
+    OptionSpec::flag("explain-failure"),
+    OptionSpec::flag("parse-only"),
+    OptionSpec::flag("examples"),
+    OptionSpec::flag("check"),
+    OptionSpec::flag("list-strategies"),
+    OptionSpec::flag("list-params"),
+];
@@ -0,0 +1,263 @@
+use crate::common::AocError;
+use crate::days::{input_path, solve, Solution};
+#[cfg(feature = "http")]
+use crate::program::fetch;
+use crate::program::{csv, history, ProgramArgs, SolutionPart};
+use std::cmp::Reverse;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Order of the rows in the batch summary.
+#[derive(Clone, Copy)]
+pub enum SortOrder {
+    /// By day and part, in the order they ran.
+    Day,
+    /// Slowest first, with days that did not run last.
+    Time,
+}
+
+impl FromStr for SortOrder {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "day" => Ok(Self::Day),
+            "time" => Ok(Self::Time),
+            _ => Err(AocError::new("sort order must be day or time")),
+        }
+    }
+}
+
+/// How solving one day in a batch run turned out.
+pub enum BatchOutcome {
+    Solved(Solution),
+    Failed(AocError),
+    /// The day was not started because the time budget ran out.
+    Skipped,
+    /// The day was not started because its input file does not exist.
+    MissingInput,
+}
+
+/// The outcome of solving one day in a batch run.
+pub struct BatchResult {
+    day: u8,
+    part: SolutionPart,
+    outcome: BatchOutcome,
+    /// How the answer changed from the last input revision, if it did.
+    change: Option<String>,
+}
+
+/// Whether a missing input can be downloaded.
+#[cfg(feature = "http")]
+fn can_download(args: &ProgramArgs) -> bool {
+    fetch::session(args).is_some()
+}
+
+#[cfg(not(feature = "http"))]
+fn can_download(_: &ProgramArgs) -> bool {
+    false
+}
+
+/// Solves every selected day and part in order, continuing past days that
+/// fail.
+///
+/// Days without an input file are skipped, unless a session is set to
+/// download it. Once the time budget is used up,
+/// the remaining days are skipped too. A day that has already started is
+/// allowed to finish.
+pub fn run(args: &ProgramArgs) -> Vec<BatchResult> {
+    let start = Instant::now();
+    let mut results = Vec::new();
+    for day in args.days() {
+        for part in args.parts() {
+            let args = args.with_day(*day).with_part(part);
+            let out_of_time = args
+                .budget()
+                .is_some_and(|budget| start.elapsed() >= budget);
+            let mut change = None;
+            let has_input = Path::new(&input_path(&args)).exists() || can_download(&args);
+            let outcome = if !has_input {
+                BatchOutcome::MissingInput
+            } else if out_of_time {
+                BatchOutcome::Skipped
+            } else {
+                match solve(&args) {
+                    Ok(solution) => {
+                        if let Err(err) = csv::append(&args, &solution) {
+                            eprintln!(
+                                "failed to write timings to CSV: {}",
+                                args.colors().error(err)
+                            );
+                        }
+                        match history::record(&args, &solution) {
+                            Err(err) => eprintln!(
+                                "failed to record answer history: {}",
+                                args.colors().error(err)
+                            ),
+                            Ok(recorded) => change = recorded,
+                        }
+                        BatchOutcome::Solved(solution)
+                    }
+                    Err(err) => BatchOutcome::Failed(err),
+                }
+            };
+            results.push(BatchResult {
+                day: *day,
+                part,
+                outcome,
+                change,
+            });
+        }
+    }
+    results
+}
+
+impl BatchResult {
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn part(&self) -> SolutionPart {
+        self.part
+    }
+
+    pub fn outcome(&self) -> &BatchOutcome {
+        &self.outcome
+    }
+
+    fn time(&self) -> Option<u128> {
+        match &self.outcome {
+            BatchOutcome::Solved(solution) => Some(solution.time().as_micros()),
+            _ => None,
+        }
+    }
+}
+
+/// Whether any day in the batch run failed. Days that were skipped did not
+/// fail.
+pub fn any_failed(results: &[BatchResult]) -> bool {
+    results
+        .iter()
+        .any(|result| matches!(result.outcome, BatchOutcome::Failed(_)))
+}
+
+/// Prints a table of the answer, time, and time spent parsing for each result,
+/// followed by the total time. Each time is also shown as a share of the
+/// total, and the rows
+/// can be sorted by time to find the slowest days. Verbose output also shows
+/// the hash of each input.
+pub fn print_summary(results: &[BatchResult], args: &ProgramArgs) {
+    let total = results.iter().filter_map(BatchResult::time).sum::<u128>();
+    let none = || String::from("-");
+    let rows = results
+        .iter()
+        .map(|result| match &result.outcome {
+            BatchOutcome::Solved(solution) => {
+                let time = solution.time().as_micros();
+                let share = if total == 0 {
+                    0.0
+                } else {
+                    time as f64 * 100.0 / total as f64
+                };
+                let parse_time = solution
+                    .parse_time()
+                    .map_or_else(none, |parse_time| parse_time.as_micros().to_string());
+                (
+                    args.value_format().format(solution.solution()),
+                    time.to_string(),
+                    parse_time,
+                    format!("{:.1}%", share),
+                    solution.input_hash().to_string(),
+                )
+            }
+            BatchOutcome::Failed(err) => (err.to_string(), none(), none(), none(), none()),
+            BatchOutcome::Skipped => (
+                String::from("skipped (out of time)"),
+                none(),
+                none(),
+                none(),
+                none(),
+            ),
+            BatchOutcome::MissingInput => (
+                String::from("skipped (no input)"),
+                none(),
+                none(),
+                none(),
+                none(),
+            ),
+        })
+        .collect::<Vec<_>>();
+    let width = rows
+        .iter()
+        .map(|(value, _, _, _, _)| value.len())
+        .chain(["Solution".len()])
+        .max()
+        .unwrap_or(0);
+
+    let mut header = format!(
+        "Day  Part  {:<width$}  Time (us)  Parse (us)  Share",
+        "Solution",
+        width = width
+    );
+    if args.verbose() {
+        header += "  Input";
+    }
+    println!("{}", header);
+    let mut order = (0..results.len()).collect::<Vec<_>>();
+    if let SortOrder::Time = args.sort() {
+        // Sorting is stable, so equal times keep their day order.
+        order.sort_by_key(|i| Reverse(results[*i].time()));
+    }
+    let colors = args.colors();
+    for i in order {
+        let (result, (value, time, parse_time, share, input_hash)) = (&results[i], &rows[i]);
+        // Cells are padded before they are colored, since the escape codes
+        // would otherwise count towards the width.
+        let value = format!("{:<width$}", value, width = width);
+        let value = match result.outcome {
+            BatchOutcome::Solved(_) => colors.answer(value),
+            BatchOutcome::Failed(_) => colors.error_in_output(value),
+            BatchOutcome::Skipped | BatchOutcome::MissingInput => value,
+        };
+        let mut row = format!(
+            "{:<3}  {:<4}  {}  {}  {:>10}  {:>5}",
+            result.day,
+            result.part.to_string(),
+            value,
+            colors.time(format!("{:>9}", time)),
+            parse_time,
+            share
+        );
+        if args.verbose() {
+            row += "  ";
+            row += input_hash;
+        }
+        println!("{}", row);
+    }
+
+    let count = |matches: fn(&BatchOutcome) -> bool| {
+        results
+            .iter()
+            .filter(|result| matches(&result.outcome))
+            .count()
+    };
+    println!(
+        "Total: {} us across {} solved, {} failed, {} skipped",
+        total,
+        count(|outcome| matches!(outcome, BatchOutcome::Solved(_))),
+        count(|outcome| matches!(outcome, BatchOutcome::Failed(_))),
+        count(|outcome| matches!(outcome, BatchOutcome::Skipped | BatchOutcome::MissingInput)),
+    );
+
+    for result in results {
+        if let Some(change) = &result.change {
+            println!("Day {} Part {}: {}", result.day, result.part, change);
+        }
+        if let BatchOutcome::Solved(solution) = &result.outcome {
+            for warning in solution.warnings() {
+                eprintln!("Day {}: {}", result.day, warning);
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::common::{AocError, AocResult, IntoAocResult};
+use crate::days::solve_parts;
+use crate::program::{ProgramArgs, SolutionPart};
+use std::fs;
+use std::io::ErrorKind;
+
+/// Reads the recorded answers for a day from `answers/<day>.txt`, which has
+/// lines of the form `<part> <answer>`, such as `A 1233`.
+///
+/// A day without an answers file has no recorded answers.
+pub fn read_answers(day: u8) -> AocResult<Vec<(SolutionPart, String)>> {
+    let path = format!("answers/{}.txt", day);
+    let answers = match fs::read_to_string(&path) {
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        result => result.into_aoc_result()?,
+    };
+    answers
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            let error =
+                |message: &str| AocError::new(format!("{} line {}: {}", path, i + 1, message));
+            let (part, answer) = line
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| error("expected a part and an answer"))?;
+            let part = SolutionPart::from_string(part).map_err(|err| error(&err.message()))?;
+            Ok((part, answer.trim().to_owned()))
+        })
+        .collect()
+}
+
+/// Solves the selected days and parts on their puzzle inputs, printing
+/// whether each one gives its recorded answer.
+///
+/// Returns whether none of them failed.
+pub fn run(args: &ProgramArgs) -> AocResult<bool> {
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    for day in args.days() {
+        let answers = read_answers(*day)?;
+        let expected = |part: SolutionPart| {
+            answers
+                .iter()
+                .find(|(answer_part, _)| *answer_part == part)
+                .map(|(_, answer)| answer)
+        };
+        let args = args.with_day(*day);
+        let solutions = match solve_parts(&args) {
+            Ok(solutions) => solutions,
+            Err(err) => {
+                let parts = args.parts();
+                failed += parts.len();
+                for part in parts {
+                    println!("FAIL Day {}, Part {}: {}", day, part, err.message());
+                }
+                continue;
+            }
+        };
+        for (part, solution) in solutions {
+            let name = format!("Day {}, Part {}", day, part);
+            let expected = match expected(part) {
+                None => {
+                    skipped += 1;
+                    println!("SKIP {}: no recorded answer", name);
+                    continue;
+                }
+                Some(expected) => expected,
+            };
+            match solution.map(|solution| solution.solution().to_string()) {
+                Ok(actual) if actual == *expected => {
+                    passed += 1;
+                    println!("PASS {}", name);
+                }
+                Ok(actual) => {
+                    failed += 1;
+                    println!("FAIL {}: expected {}, got {}", name, expected, actual);
+                }
+                Err(err) => {
+                    failed += 1;
+                    println!("FAIL {}: {}", name, err.message());
+                }
+            }
+        }
+    }
+    println!("{} passed, {} failed, {} skipped", passed, failed, skipped);
+    Ok(failed == 0)
+}
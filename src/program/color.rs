@@ -0,0 +1,94 @@
+use crate::common::AocError;
+use std::env;
+use std::fmt::Display;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+
+/// When to color the program output.
+#[derive(Clone, Copy)]
+pub enum ColorChoice {
+    /// Colors output written to a terminal, unless `NO_COLOR` is set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(AocError::new("color must be auto, always, or never")),
+        }
+    }
+}
+
+/// ANSI escape code for each color used.
+const GREEN: u8 = 32;
+const YELLOW: u8 = 33;
+const RED: u8 = 31;
+
+/// Colors answers, timings, and errors, depending on the choice made with
+/// `--color`.
+///
+/// Standard output and standard error are checked separately, so piping one
+/// of them to a file leaves the other colored.
+#[derive(Clone, Copy)]
+pub struct Colors {
+    stdout: bool,
+    stderr: bool,
+}
+
+impl Colors {
+    pub fn new(choice: ColorChoice) -> Self {
+        match choice {
+            ColorChoice::Always => Colors {
+                stdout: true,
+                stderr: true,
+            },
+            ColorChoice::Never => Colors {
+                stdout: false,
+                stderr: false,
+            },
+            ColorChoice::Auto => {
+                let enabled = env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+                Colors {
+                    stdout: enabled && io::stdout().is_terminal(),
+                    stderr: enabled && io::stderr().is_terminal(),
+                }
+            }
+        }
+    }
+
+    fn paint<T: Display>(enabled: bool, color: u8, text: T) -> String {
+        if enabled {
+            format!("\x1b[{}m{}\x1b[0m", color, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Colors an answer printed to standard output.
+    pub fn answer<T: Display>(&self, text: T) -> String {
+        Self::paint(self.stdout, GREEN, text)
+    }
+
+    /// Colors a timing printed to standard output.
+    pub fn time<T: Display>(&self, text: T) -> String {
+        Self::paint(self.stdout, YELLOW, text)
+    }
+
+    /// Colors an error printed to standard error.
+    pub fn error<T: Display>(&self, text: T) -> String {
+        Self::paint(self.stderr, RED, text)
+    }
+
+    /// Colors an error printed to standard output, such as in a summary
+    /// table.
+    pub fn error_in_output<T: Display>(&self, text: T) -> String {
+        Self::paint(self.stdout, RED, text)
+    }
+}
@@ -0,0 +1,63 @@
+use crate::common::{AocResult, IntoAocResult};
+use crate::days::Solution;
+use crate::program::ProgramArgs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HEADER: &str = "timestamp,day,part,run,time_us,parse_us,answer,input";
+
+/// Quotes a field if it holds a character that would break the row.
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Appends a row for every run of the solution to the file given with
+/// `--csv`, writing a header first if the file is new.
+///
+/// Every row from one invocation has the same Unix timestamp, so runs from
+/// different invocations can be told apart when tracking times.
+pub fn append(args: &ProgramArgs, solution: &Solution) -> AocResult<()> {
+    let path = match args.csv() {
+        None => return Ok(()),
+        Some(path) => path,
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .into_aoc_result()?;
+    if file.metadata().into_aoc_result()?.len() == 0 {
+        writeln!(file, "{}", HEADER).into_aoc_result()?;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .into_aoc_result()?
+        .as_secs();
+    let answer = field(&solution.solution().to_string());
+    let mut rows = String::new();
+    for (run, time) in solution.times().iter().enumerate() {
+        let parse_time = solution
+            .parse_times()
+            .get(run)
+            .map(|parse_time| parse_time.as_micros().to_string())
+            .unwrap_or_default();
+        rows += &format!(
+            "{},{},{},{},{},{},{},{}\n",
+            timestamp,
+            args.day(),
+            args.part(),
+            run + 1,
+            time.as_micros(),
+            parse_time,
+            answer,
+            solution.input_hash()
+        );
+    }
+    file.write_all(rows.as_bytes()).into_aoc_result()
+}
@@ -0,0 +1,121 @@
+use crate::common::{AocError, AocResult, IntoAocResult};
+use crate::days::solve;
+use crate::program::{ProgramArgs, SolutionPart};
+use std::fs;
+
+/// Manifest of the expected answers for the example inputs, relative to the
+/// input directory.
+const MANIFEST: &str = "test/answers.txt";
+
+/// Expected answer written for inputs that must be rejected with an error,
+/// such as regressions for inputs that once crashed a solver.
+const ERROR: &str = "error";
+
+/// An example input with the answer it is expected to give.
+struct Example {
+    day: u8,
+    part: SolutionPart,
+    filename: String,
+    expected: String,
+    /// Params to solve the example with.
+    params: Vec<(String, String)>,
+}
+
+impl Example {
+    /// Parses a manifest line of the form `<file> <part> <answer>
+    /// [name=value]...`, where the file name starts with the day, such as
+    /// `14.1.txt A 1588` or `1.1.txt B 5 window=5`.
+    fn parse(line: &str) -> AocResult<Self> {
+        let mut fields = line.split_whitespace();
+        let mut next = |name: &str| {
+            fields
+                .next()
+                .ok_or_else(|| AocError::new(format!("missing {}", name)))
+        };
+        let file = next("file")?;
+        let part = SolutionPart::from_string(next("part")?)?;
+        let expected = next("answer")?.to_owned();
+        let params = fields
+            .map(|param| {
+                param
+                    .split_once('=')
+                    .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                    .ok_or_else(|| AocError::new("param must be of the form name=value"))
+            })
+            .collect::<AocResult<Vec<_>>>()?;
+        let day = file
+            .split('.')
+            .next()
+            .and_then(|day| day.parse::<u8>().ok())
+            .into_aoc_result_msg("file name must start with the day")?;
+        Ok(Example {
+            day,
+            part,
+            filename: format!("test/{}", file),
+            expected,
+            params,
+        })
+    }
+}
+
+fn read_manifest(args: &ProgramArgs) -> AocResult<Vec<Example>> {
+    let manifest =
+        fs::read_to_string(format!("{}/{}", args.input_dir(), MANIFEST)).into_aoc_result()?;
+    manifest
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            Example::parse(line).map_err(|err| {
+                AocError::new(format!("{} line {}: {}", MANIFEST, i + 1, err.message()))
+            })
+        })
+        .collect()
+}
+
+/// Solves every example for the selected days and parts, printing whether
+/// each one gives its expected answer.
+///
+/// Returns whether none of them failed.
+pub fn run(args: &ProgramArgs) -> AocResult<bool> {
+    let examples = read_manifest(args)?
+        .into_iter()
+        .filter(|example| args.days().contains(&example.day))
+        .filter(|example| args.parts().contains(&example.part))
+        .collect::<Vec<_>>();
+
+    let mut failed = 0;
+    for example in &examples {
+        let args = args
+            .with_day(example.day)
+            .with_part(example.part)
+            .with_filename(example.filename.clone())
+            .with_params(&example.params);
+        let actual = solve(&args).map(|solution| solution.solution().to_string());
+        let mut name = format!(
+            "Day {}, Part {} ({}",
+            example.day, example.part, example.filename
+        );
+        for (param, value) in &example.params {
+            name += &format!(", {}={}", param, value);
+        }
+        name += ")";
+        match actual {
+            Ok(actual) if actual == example.expected => println!("PASS {}", name),
+            Err(_) if example.expected == ERROR => println!("PASS {}", name),
+            Ok(actual) => {
+                failed += 1;
+                println!(
+                    "FAIL {}: expected {}, got {}",
+                    name, example.expected, actual
+                );
+            }
+            Err(err) => {
+                failed += 1;
+                println!("FAIL {}: {}", name, err.message());
+            }
+        }
+    }
+    println!("{} passed, {} failed", examples.len() - failed, failed);
+    Ok(failed == 0)
+}
@@ -0,0 +1,41 @@
+use crate::common::AocError;
+use std::process::ExitCode;
+
+/// How the program exited, so that scripts can tell failures apart.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success = 0,
+    /// A solver failed, such as when it found no solution.
+    SolverError = 1,
+    /// The program arguments are invalid.
+    BadArguments = 2,
+    /// The input file does not exist and could not be downloaded.
+    MissingInput = 3,
+    /// An answer checked with `--check` or `--examples` was wrong.
+    Mismatch = 4,
+}
+
+impl ExitStatus {
+    /// The status for a run that failed with the given error.
+    pub fn of_error(err: &AocError) -> Self {
+        if err.is_missing_input() {
+            Self::MissingInput
+        } else {
+            Self::SolverError
+        }
+    }
+
+    /// Keeps the first failure when more than one thing ran.
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Self::Success => other,
+            _ => self,
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
@@ -0,0 +1,195 @@
+use crate::common::{AocError, AocResult, IntoAocResult};
+use crate::program::{ProgramArgs, SolutionPart};
+use std::fmt::{Display, Formatter, Result as DisplayResult};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// File that holds the session cookie if the environment variable is not set.
+const SESSION_FILE: &str = ".session";
+
+/// Finds the adventofcode.com session cookie, first in the `AOC_SESSION`
+/// environment variable and then in the `.session` file.
+pub fn session(args: &ProgramArgs) -> Option<String> {
+    args.session().map(str::to_owned).or_else(|| {
+        fs::read_to_string(SESSION_FILE)
+            .ok()
+            .map(|session| session.trim().to_owned())
+            .filter(|session| !session.is_empty())
+    })
+}
+
+/// Downloads the puzzle input for the selected day and year and saves it to
+/// `path`, so later runs read it locally.
+pub fn fetch_input(args: &ProgramArgs, path: &str) -> AocResult<String> {
+    let session = session(args).ok_or_else(|| {
+        AocError::new(format!(
+            "{} does not exist, and no session is set to download it (set AOC_SESSION or write it to {})",
+            path, SESSION_FILE
+        ))
+    })?;
+
+    let url = format!(
+        "https://adventofcode.com/{}/day/{}/input",
+        args.year(),
+        args.day()
+    );
+    let input = download(&url, Some(&session), &[])?;
+    // The input directory may be set to one that does not exist yet.
+    if let Some(directory) = Path::new(path).parent() {
+        fs::create_dir_all(directory).into_aoc_result()?;
+    }
+    fs::write(path, &input).into_aoc_result()?;
+    Ok(input)
+}
+
+/// Downloads an input from any URL given with `--input`, such as a private
+/// gist. The input is not saved, and no session cookie is sent.
+pub fn fetch_url(url: &str) -> AocResult<String> {
+    download(url, None, &[])
+}
+
+/// Downloads the text at the URL with `curl`, sending the session cookie if
+/// there is one. Any form fields, given as `name=value`, are POSTed.
+///
+/// The cookie is given to `curl` on standard input so that it does not show
+/// up in the process list. Redirects are only followed without a cookie, so
+/// that it is never sent to another host.
+fn download(url: &str, session: Option<&str>, form: &[String]) -> AocResult<String> {
+    let mut curl = Command::new("curl");
+    curl.args(["--silent", "--show-error", "--fail"]).args([
+        "--user-agent",
+        "github.com/jackson-nestelroad/advent-of-code-2021",
+    ]);
+    match session {
+        Some(_) => curl.args(["--header", "@-"]),
+        None => curl.arg("--location"),
+    };
+    for field in form {
+        curl.args(["--data-urlencode", field]);
+    }
+    let mut curl = curl
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .into_aoc_result_msg("failed to run curl to download the input")?;
+    let mut stdin = curl.stdin.take().into_aoc_result()?;
+    if let Some(session) = session {
+        stdin
+            .write_all(format!("Cookie: session={}\n", session).as_bytes())
+            .into_aoc_result()?;
+    }
+    drop(stdin);
+    let output = curl.wait_with_output().into_aoc_result()?;
+    if !output.status.success() {
+        return Err(AocError::new(format!(
+            "failed to download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8(output.stdout).into_aoc_result_msg("input is not UTF-8 text")
+}
+
+/// What adventofcode.com said about a submitted answer.
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// The answer is wrong, and the site did not say in which direction.
+    Wrong,
+    /// An answer was submitted too recently, with the time left to wait if
+    /// the site gave it.
+    RateLimited(Option<String>),
+    /// The part is already solved, or not unlocked yet.
+    WrongLevel,
+    /// The response did not match any known message.
+    Unknown(String),
+}
+
+impl Verdict {
+    /// Reads the verdict from the page returned for a submitted answer.
+    fn parse(page: &str) -> Self {
+        // The message is in the page's only article.
+        let message = page
+            .split_once("<article>")
+            .and_then(|(_, rest)| rest.split_once("</article>"))
+            .map_or(page, |(article, _)| article);
+        if message.contains("That's the right answer") {
+            Self::Correct
+        } else if message.contains("too high") {
+            Self::TooHigh
+        } else if message.contains("too low") {
+            Self::TooLow
+        } else if message.contains("That's not the right answer") {
+            Self::Wrong
+        } else if message.contains("You gave an answer too recently") {
+            let wait = message
+                .split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .map(|(wait, _)| wait.to_owned());
+            Self::RateLimited(wait)
+        } else if message.contains("You don't seem to be solving the right level") {
+            Self::WrongLevel
+        } else {
+            Self::Unknown(strip_tags(message))
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter) -> DisplayResult {
+        match self {
+            Self::Correct => write!(f, "correct"),
+            Self::TooHigh => write!(f, "wrong, too high"),
+            Self::TooLow => write!(f, "wrong, too low"),
+            Self::Wrong => write!(f, "wrong"),
+            Self::RateLimited(None) => write!(f, "rate limited"),
+            Self::RateLimited(Some(wait)) => write!(f, "rate limited, {} left to wait", wait),
+            Self::WrongLevel => write!(f, "already solved or not unlocked yet"),
+            Self::Unknown(message) => write!(f, "unknown response: {}", message),
+        }
+    }
+}
+
+/// Removes the HTML tags from a message and collapses its whitespace.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(ch),
+            _ => (),
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Submits the answer for the selected day and part to adventofcode.com,
+/// returning what the site said about it.
+pub fn submit(args: &ProgramArgs, answer: &str) -> AocResult<Verdict> {
+    let session = session(args).ok_or_else(|| {
+        AocError::new(format!(
+            "no session is set to submit the answer (set AOC_SESSION or write it to {})",
+            SESSION_FILE
+        ))
+    })?;
+    let url = format!(
+        "https://adventofcode.com/{}/day/{}/answer",
+        args.year(),
+        args.day()
+    );
+    let level = match args.part() {
+        SolutionPart::A => 1,
+        SolutionPart::B => 2,
+    };
+    let form = [format!("level={}", level), format!("answer={}", answer)];
+    let page = download(&url, Some(&session), &form)?;
+    Ok(Verdict::parse(&page))
+}
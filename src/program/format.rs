@@ -0,0 +1,62 @@
+use crate::common::{AocError, SolutionValue};
+use std::str::FromStr;
+
+/// How solution values are rendered in the program output.
+#[derive(Clone, Copy)]
+pub enum ValueFormat {
+    /// Plain decimal, e.g. `1234567`.
+    Plain,
+    /// Decimal with digits grouped by underscores, e.g. `1_234_567`.
+    Pretty,
+    /// Hexadecimal, e.g. `0x12d687`.
+    Hex,
+    /// Binary, e.g. `0b100101101011010000111`.
+    Bin,
+}
+
+/// Groups the digits of a decimal number by underscores.
+fn group_digits(digits: &str) -> String {
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push('_');
+        }
+        result.push(digit);
+    }
+    result
+}
+
+impl ValueFormat {
+    /// Formats the value. Text values are shown as they are, except that
+    /// decimal digits are still grouped in the pretty format.
+    pub fn format(&self, value: &SolutionValue) -> String {
+        match (self, value) {
+            (Self::Pretty, SolutionValue::Text(text))
+                if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                group_digits(text)
+            }
+            (_, SolutionValue::Text(text)) => text.clone(),
+            (Self::Plain, SolutionValue::Number(value)) => value.to_string(),
+            (Self::Pretty, SolutionValue::Number(value)) => group_digits(&value.to_string()),
+            (Self::Hex, SolutionValue::Number(value)) => format!("{:#x}", value),
+            (Self::Bin, SolutionValue::Number(value)) => format!("{:#b}", value),
+        }
+    }
+}
+
+impl FromStr for ValueFormat {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "plain" => Ok(Self::Plain),
+            "pretty" => Ok(Self::Pretty),
+            "hex" => Ok(Self::Hex),
+            "bin" => Ok(Self::Bin),
+            _ => Err(AocError::new(
+                "value format must be plain, pretty, hex, or bin",
+            )),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::common::{AocResult, IntoAocResult};
+use crate::days::Solution;
+use crate::program::ProgramArgs;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Directory that holds the answer history of each day and part.
+const HISTORY_DIRECTORY: &str = "output/history";
+
+/// Records the answer for the selected day's input, returning a message if
+/// the input has changed since the last recorded run and the answer changed
+/// with it.
+///
+/// The history keeps one line per input revision, so answers to earlier
+/// revisions are never overwritten. Only runs on the default input without
+/// any params are recorded, since params can change the answer.
+pub fn record(args: &ProgramArgs, solution: &Solution) -> AocResult<Option<String>> {
+    if args.filename().is_some() || !args.params().is_empty() {
+        return Ok(None);
+    }
+
+    let name = format!("{}.{}.txt", args.day(), args.part());
+    let path = PathBuf::from(HISTORY_DIRECTORY).join(name);
+    // A day without a history yet has nothing to compare against.
+    let history = fs::read_to_string(&path).unwrap_or_default();
+    let revision = solution.input_hash().to_string();
+    let answer = solution.solution().to_string();
+    let last = history.lines().rev().find_map(|line| line.split_once(' '));
+    if last.is_some_and(|(last_revision, _)| last_revision == revision) {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(HISTORY_DIRECTORY).into_aoc_result()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .into_aoc_result()?;
+    writeln!(file, "{} {}", revision, answer).into_aoc_result()?;
+
+    Ok(match last {
+        Some((_, last_answer)) if last_answer != answer => Some(format!(
+            "answer changed from {} to {} for input revision {}",
+            last_answer, answer, revision
+        )),
+        _ => None,
+    })
+}
@@ -1,3 +1,23 @@
+mod adapter;
 mod args;
+pub mod batch;
+pub mod check;
+mod color;
+pub mod csv;
+pub mod examples;
+mod exit;
+#[cfg(feature = "http")]
+pub mod fetch;
+mod format;
+pub mod history;
+pub mod report;
+pub mod scaffold;
+pub mod summary;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 pub use args::{ProgramArgs, SolutionPart};
+pub use batch::SortOrder;
+pub use color::{ColorChoice, Colors};
+pub use exit::ExitStatus;
+pub use format::ValueFormat;
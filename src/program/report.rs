@@ -0,0 +1,69 @@
+use crate::common::{AocResult, IntoAocResult};
+use crate::days::is_stub;
+use crate::program::batch::{BatchOutcome, BatchResult};
+use crate::program::{ProgramArgs, SolutionPart};
+use std::fs;
+use std::time::Duration;
+
+/// Formats a time for reading rather than for comparing, in microseconds
+/// below a millisecond and in milliseconds above.
+fn format_time(time: Duration) -> String {
+    if time < Duration::from_millis(1) {
+        format!("{} us", time.as_micros())
+    } else {
+        format!("{:.1} ms", time.as_secs_f64() * 1000.0)
+    }
+}
+
+/// The answer and time cells for one part of a day. Stubs are left empty,
+/// since their answer means nothing.
+fn cells(result: Option<&BatchResult>, args: &ProgramArgs) -> (String, String) {
+    let none = || String::from("-");
+    if result.is_some_and(|result| is_stub(result.day(), result.part())) {
+        return (none(), none());
+    }
+    match result.map(BatchResult::outcome) {
+        Some(BatchOutcome::Solved(solution)) => (
+            format!("`{}`", args.value_format().format(solution.solution())),
+            format_time(solution.time()),
+        ),
+        Some(BatchOutcome::Failed(_)) => (String::from("failed"), none()),
+        Some(BatchOutcome::Skipped | BatchOutcome::MissingInput) | None => (none(), none()),
+    }
+}
+
+/// Renders a Markdown table with a row for each day, holding the answer and
+/// time of both parts, followed by the total time.
+pub fn markdown(results: &[BatchResult], args: &ProgramArgs) -> String {
+    let mut days = results.iter().map(BatchResult::day).collect::<Vec<_>>();
+    days.dedup();
+
+    let mut report = String::from("| Day | Part A | Time | Part B | Time |\n");
+    report += "| --: | -----: | ---: | -----: | ---: |\n";
+    let mut total = Duration::ZERO;
+    for day in days {
+        let part = |part: SolutionPart| {
+            results
+                .iter()
+                .find(|result| result.day() == day && result.part() == part)
+        };
+        let (a, a_time) = cells(part(SolutionPart::A), args);
+        let (b, b_time) = cells(part(SolutionPart::B), args);
+        report += &format!("| {} | {} | {} | {} | {} |\n", day, a, a_time, b, b_time);
+    }
+    for result in results {
+        if let BatchOutcome::Solved(solution) = result.outcome() {
+            total += solution.time();
+        }
+    }
+    report += &format!("\nTotal time: {}\n", format_time(total));
+    report
+}
+
+/// Writes the Markdown report to the file given with `--markdown`, if any.
+pub fn write(results: &[BatchResult], args: &ProgramArgs) -> AocResult<()> {
+    match args.markdown() {
+        None => Ok(()),
+        Some(path) => fs::write(path, markdown(results, args)).into_aoc_result(),
+    }
+}
@@ -0,0 +1,203 @@
+use crate::common::{AocError, AocResult, IntoAocResult};
+use crate::program::ProgramArgs;
+use std::fs;
+use std::path::Path;
+
+/// Directory that holds the module of each day.
+const DAYS_DIRECTORY: &str = "src/days";
+
+/// Number of days that the solver tables have room for.
+const DAYS: u8 = 25;
+
+/// Skeleton of a new day, where `NN` is replaced by the day.
+const TEMPLATE: &str = r#"use crate::common::{
+    parse_lines, AocError, AocResult, DayInfo, SolutionValue, Solver, SolverContext,
+};
+use std::str::FromStr;
+
+pub struct Entry;
+
+impl FromStr for Entry {
+    type Err = AocError;
+
+    fn from_str(_: &str) -> Result<Self, Self::Err> {
+        Ok(Entry)
+    }
+}
+
+pub const INFO: DayInfo = DayInfo::EMPTY;
+
+pub struct DayNN;
+
+impl Solver for DayNN {
+    type Parsed = Vec<Entry>;
+
+    fn parse(input: &str, _: &SolverContext) -> AocResult<Vec<Entry>> {
+        parse_lines(input, Entry::from_str)
+    }
+
+    fn part_a(_: &Vec<Entry>, _: &SolverContext) -> AocResult<SolutionValue> {
+        Err(AocError::new("day NN part A is not implemented"))
+    }
+
+    fn part_b(_: &Vec<Entry>, _: &SolverContext) -> AocResult<SolutionValue> {
+        Err(AocError::new("day NN part B is not implemented"))
+    }
+}
+"#;
+
+/// Replaces the row for the given day in the table that starts at the line
+/// `header`, where each row of the table is on a line of its own.
+fn replace_row(source: &str, header: &str, day: u8, row: &str) -> AocResult<String> {
+    let mut lines = source.lines().collect::<Vec<_>>();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with(header))
+        .ok_or_else(|| AocError::new(format!("could not find {}", header)))?;
+    let index = start + day as usize;
+    if lines.get(index).is_none_or(|line| line.starts_with(']')) {
+        return Err(AocError::new(format!(
+            "{} has no row for day {}",
+            header, day
+        )));
+    }
+    lines[index] = row;
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Replaces a field of the given day's entry in the table that starts at the
+/// line `header`, where each entry starts with a `Day {` line and has each
+/// field on a line of its own.
+fn replace_field(
+    source: &str,
+    header: &str,
+    day: u8,
+    field: &str,
+    value: &str,
+) -> AocResult<String> {
+    let mut lines = source.lines().collect::<Vec<_>>();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with(header))
+        .ok_or_else(|| AocError::new(format!("could not find {}", header)))?;
+    let missing = || AocError::new(format!("{} has no {} for day {}", header, field, day));
+    let entry = lines[start + 1..]
+        .iter()
+        .take_while(|line| !line.starts_with(']'))
+        .enumerate()
+        .filter(|(_, line)| line.trim() == "Day {")
+        .nth(day as usize - 1)
+        .map(|(index, _)| start + 1 + index)
+        .ok_or_else(missing)?;
+    let index = lines[entry + 1..]
+        .iter()
+        .take_while(|line| !line.starts_with("    }"))
+        .position(|line| line.trim_start().starts_with(&format!("{}:", field)))
+        .map(|index| entry + 1 + index)
+        .ok_or_else(missing)?;
+    let indent = &lines[index][..lines[index].len() - lines[index].trim_start().len()];
+    let line = format!("{}{}: {},", indent, field, value);
+    lines[index] = &line;
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Declares the module of the given day, keeping the declarations in order.
+fn declare_module(source: &str, module: &str) -> String {
+    let declaration = format!("mod {};", module);
+    let mut lines = source.lines().collect::<Vec<_>>();
+    if lines.contains(&declaration.as_str()) {
+        return source.to_owned();
+    }
+    let index = lines
+        .iter()
+        .position(|line| line.starts_with("mod day") && *line > declaration.as_str())
+        .or_else(|| {
+            lines
+                .iter()
+                .rposition(|line| line.starts_with("mod "))
+                .map(|index| index + 1)
+        })
+        .unwrap_or(0);
+    lines.insert(index, &declaration);
+    lines.join("\n") + "\n"
+}
+
+/// Writes a file only if it does not exist yet, returning whether it was
+/// written.
+fn create(path: &str, contents: &str) -> AocResult<bool> {
+    if Path::new(path).exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent).into_aoc_result()?;
+    }
+    fs::write(path, contents).into_aoc_result()?;
+    Ok(true)
+}
+
+/// Generates the module of the selected day, registers it in the module list
+/// and the solver tables, and creates empty puzzle and example inputs.
+///
+/// The module must not exist yet, so a finished day is never overwritten.
+/// Existing inputs are left as they are.
+pub fn new_day(args: &ProgramArgs) -> AocResult<()> {
+    let day = args.day();
+    if day == 0 || day > DAYS {
+        return Err(AocError::new(format!(
+            "the solver tables only have room for days 1-{}",
+            DAYS
+        )));
+    }
+    let module = format!("day{:02}", day);
+    let module_path = format!("{}/{}.rs", DAYS_DIRECTORY, module);
+    if Path::new(&module_path).exists() {
+        return Err(AocError::new(format!("{} already exists", module_path)));
+    }
+
+    let mod_path = format!("{}/mod.rs", DAYS_DIRECTORY);
+    let all_path = format!("{}/all.rs", DAYS_DIRECTORY);
+    let mod_source = fs::read_to_string(&mod_path).into_aoc_result()?;
+    let all_source = fs::read_to_string(&all_path).into_aoc_result()?;
+    let all_source = replace_field(
+        &all_source,
+        "const DAYS:",
+        day,
+        "solvers",
+        &format!("solvers!({}::Day{})", module, day),
+    )?;
+    let all_source = replace_field(
+        &all_source,
+        "const DAYS:",
+        day,
+        "summary",
+        "\"Not solved yet.\"",
+    )?;
+    let all_source = replace_row(
+        &all_source,
+        "const INFO:",
+        day,
+        &format!("    &{}::INFO,", module),
+    )?;
+
+    let template = TEMPLATE.replace("NN", &day.to_string());
+    fs::write(&module_path, template).into_aoc_result()?;
+    fs::write(&mod_path, declare_module(&mod_source, &module)).into_aoc_result()?;
+    fs::write(&all_path, all_source).into_aoc_result()?;
+    println!("Created {}", module_path);
+    println!("Registered {} in {} and {}", module, mod_path, all_path);
+
+    let input_dir = args.input_dir();
+    for path in [
+        format!("{}/{}.txt", input_dir, day),
+        format!("{}/test/{}.1.txt", input_dir, day),
+    ] {
+        if create(&path, "")? {
+            println!("Created {}", path);
+        }
+    }
+    println!(
+        "Paste the example into {}/test/{}.1.txt and add its answers to {}/test/answers.txt",
+        input_dir, day, input_dir
+    );
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use crate::common::{AocError, AocResult, IntoAocResult};
+use crate::days::{implemented_days, is_stub};
+use crate::program::check::read_answers;
+use crate::program::{ProgramArgs, SolutionPart};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+
+/// Number of days in the event.
+const DAYS: u8 = 25;
+
+/// Number of the slowest parts that are listed.
+const SLOWEST: usize = 5;
+
+/// How far along one part is.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Completion {
+    /// There is no solver for the part.
+    Unimplemented,
+    /// The part has no puzzle, so its star comes with all the others.
+    Stub,
+    /// The part is solved, but there is no recorded answer to check it
+    /// against.
+    Unverified,
+    /// The part is solved and has a recorded answer.
+    Verified,
+}
+
+/// Reads how far along every part of every day is.
+fn read_completion() -> AocResult<Vec<(u8, SolutionPart, Completion)>> {
+    let mut completion = Vec::new();
+    for day in 1..=DAYS {
+        let answers = read_answers(day)?;
+        for part in [SolutionPart::A, SolutionPart::B] {
+            let state = if !implemented_days().contains(&day) {
+                Completion::Unimplemented
+            } else if is_stub(day, part) {
+                Completion::Stub
+            } else if answers.iter().any(|(answer_part, _)| *answer_part == part) {
+                Completion::Verified
+            } else {
+                Completion::Unverified
+            };
+            completion.push((day, part, state));
+        }
+    }
+    Ok(completion)
+}
+
+/// Reads the time of the fastest run of each part from the latest
+/// invocation recorded in the timings file written by `--csv`, in
+/// microseconds.
+fn read_timings(path: &str) -> AocResult<HashMap<(u8, SolutionPart), u128>> {
+    let timings = fs::read_to_string(path).into_aoc_result()?;
+    // The latest timestamp and fastest time of that invocation for each part.
+    let mut latest: HashMap<(u8, SolutionPart), (u64, u128)> = HashMap::new();
+    for (i, line) in timings.lines().enumerate().skip(1) {
+        let error = || AocError::new(format!("{} line {}: invalid timing row", path, i + 1));
+        let mut fields = line.splitn(6, ',');
+        let mut field = || fields.next().ok_or_else(error);
+        let timestamp = field()?;
+        // Answers that span lines are quoted, and their later lines are not
+        // rows of their own.
+        let timestamp = match timestamp.parse::<u64>() {
+            Err(_) => continue,
+            Ok(timestamp) => timestamp,
+        };
+        let day = field()?.parse::<u8>().map_err(|_| error())?;
+        let part = SolutionPart::from_string(field()?).map_err(|_| error())?;
+        let _run = field()?;
+        let time = field()?.parse::<u128>().map_err(|_| error())?;
+        let entry = latest.entry((day, part)).or_insert((timestamp, time));
+        if timestamp > entry.0 {
+            *entry = (timestamp, time);
+        } else if timestamp == entry.0 {
+            entry.1 = entry.1.min(time);
+        }
+    }
+    Ok(latest
+        .into_iter()
+        .map(|(key, (_, time))| (key, time))
+        .collect())
+}
+
+/// Formats the parts in the given state, or `none`.
+fn parts_in(completion: &[(u8, SolutionPart, Completion)], state: Completion) -> String {
+    let parts = completion
+        .iter()
+        .filter(|(_, _, part_state)| *part_state == state)
+        .map(|(day, part, _)| format!("{}{}", day, part))
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        String::from("none")
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Prints the stars earned, which parts are unimplemented or unverified, and
+/// the total time and slowest parts from the timings file given with
+/// `--csv`.
+///
+/// A part earns its star once it has a verified answer. A stub part, which
+/// has no puzzle, earns its star once every other part has one.
+pub fn print(args: &ProgramArgs) -> AocResult<()> {
+    let completion = read_completion()?;
+    let verified = completion
+        .iter()
+        .filter(|(_, _, state)| *state == Completion::Verified)
+        .count();
+    let stubs = completion
+        .iter()
+        .filter(|(_, _, state)| *state == Completion::Stub)
+        .count();
+    let stars = if verified + stubs == completion.len() {
+        completion.len()
+    } else {
+        verified
+    };
+    println!("Stars: {} of {}", stars, completion.len());
+    println!(
+        "Unimplemented: {}",
+        parts_in(&completion, Completion::Unimplemented)
+    );
+    println!(
+        "Unverified: {}",
+        parts_in(&completion, Completion::Unverified)
+    );
+
+    let path = match args.csv() {
+        None => {
+            println!("Timings: none (record them with --csv path)");
+            return Ok(());
+        }
+        Some(path) => path,
+    };
+    let timings = read_timings(path)?;
+    let solved = completion
+        .iter()
+        .filter(|(_, _, state)| *state != Completion::Unimplemented)
+        .map(|(day, part, _)| (*day, *part))
+        .collect::<Vec<_>>();
+    let untimed = solved
+        .iter()
+        .filter(|key| !timings.contains_key(key))
+        .map(|(day, part)| format!("{}{}", day, part))
+        .collect::<Vec<_>>();
+    let mut timed = solved
+        .iter()
+        .filter_map(|key| timings.get(key).map(|time| (*key, *time)))
+        .collect::<Vec<_>>();
+    println!(
+        "Total time: {} us across {} parts",
+        timed.iter().map(|(_, time)| time).sum::<u128>(),
+        timed.len()
+    );
+    if !untimed.is_empty() {
+        println!("Not timed: {}", untimed.join(", "));
+    }
+    // Sorting is stable, so equal times keep their day order.
+    timed.sort_by_key(|(_, time)| Reverse(*time));
+    println!("Slowest:");
+    for ((day, part), time) in timed.iter().take(SLOWEST) {
+        println!("  Day {}, Part {}: {} us", day, part, time);
+    }
+    Ok(())
+}
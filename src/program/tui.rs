@@ -0,0 +1,276 @@
+use crate::common::{log, AocError, AocResult, IntoAocResult};
+use crate::days::{implemented_days, puzzle, solve};
+use crate::program::{ProgramArgs, SolutionPart};
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+
+/// Width of the answer columns, past which answers are cut short.
+const ANSWER_WIDTH: usize = 20;
+
+/// Lines taken up by everything but the rows of days and the output pane:
+/// the title, the column headings, and the heading of the pane.
+const HEADING_LINES: usize = 3;
+
+/// A key that does something in the dashboard.
+enum Key {
+    Up,
+    Down,
+    Run,
+    RunAll,
+    Quit,
+}
+
+impl Key {
+    /// Reads the next key that does something, skipping any others.
+    fn read(stdin: &mut impl Read) -> AocResult<Self> {
+        let mut buffer = [0; 8];
+        loop {
+            let read = stdin.read(&mut buffer).into_aoc_result()?;
+            let key = match &buffer[..read] {
+                // End of input, such as when stdin is not a terminal.
+                [] => Some(Self::Quit),
+                b"\x1b[A" | b"k" => Some(Self::Up),
+                b"\x1b[B" | b"j" => Some(Self::Down),
+                b"\n" | b"\r" | b"r" => Some(Self::Run),
+                b"a" => Some(Self::RunAll),
+                b"q" | b"\x1b" => Some(Self::Quit),
+                _ => None,
+            };
+            if let Some(key) = key {
+                return Ok(key);
+            }
+        }
+    }
+}
+
+/// Puts the terminal into a mode that reads keys as they are pressed, on an
+/// alternate screen, until dropped.
+struct RawTerminal {
+    /// Settings to restore, as printed by `stty -g`.
+    saved: String,
+}
+
+/// Runs `stty` on the terminal, returning what it prints.
+fn stty(args: &[&str]) -> AocResult<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .into_aoc_result_msg("failed to run stty")?;
+    if !output.status.success() {
+        return Err(AocError::new(
+            "stty failed, so standard input may not be a terminal",
+        ));
+    }
+    String::from_utf8(output.stdout).into_aoc_result()
+}
+
+impl RawTerminal {
+    fn enter() -> AocResult<Self> {
+        let saved = stty(&["-g"])?.trim().to_owned();
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush().into_aoc_result()?;
+        Ok(RawTerminal { saved })
+    }
+
+    /// Lines on the screen, or 24 if the terminal does not say.
+    fn lines() -> usize {
+        stty(&["size"])
+            .ok()
+            .and_then(|size| size.split_whitespace().next()?.parse().ok())
+            .filter(|lines| *lines > 0)
+            .unwrap_or(24)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// How the latest run of one part turned out, as shown in the table.
+struct PartCell {
+    answer: String,
+    time: String,
+}
+
+/// Everything known about one day since the dashboard started.
+#[derive(Default)]
+struct DayRow {
+    /// Cells for part A and part B, or `None` if the day has not run.
+    parts: Option<[PartCell; 2]>,
+    /// Logged messages, answers, warnings, and errors from the latest run.
+    output: Vec<String>,
+}
+
+/// Cuts an answer down to its first line and the width of its column.
+fn shorten(answer: &str) -> String {
+    let first_line = answer.lines().next().unwrap_or_default();
+    if answer.lines().nth(1).is_none() && first_line.chars().count() <= ANSWER_WIDTH {
+        return first_line.to_owned();
+    }
+    let mut short = first_line
+        .chars()
+        .take(ANSWER_WIDTH - 3)
+        .collect::<String>();
+    short += "...";
+    short
+}
+
+struct Dashboard<'a> {
+    args: &'a ProgramArgs,
+    days: Vec<u8>,
+    rows: Vec<DayRow>,
+    selected: usize,
+}
+
+impl<'a> Dashboard<'a> {
+    fn new(args: &'a ProgramArgs) -> Self {
+        let days = implemented_days().collect::<Vec<_>>();
+        let rows = days.iter().map(|_| DayRow::default()).collect();
+        Dashboard {
+            args,
+            days,
+            rows,
+            selected: 0,
+        }
+    }
+
+    /// Solves one part of a day, adding what it logs, its answer, and its
+    /// warnings or error to the output.
+    fn run_part(&self, day: u8, part: SolutionPart, output: &mut Vec<String>) -> PartCell {
+        log::start_capture();
+        let result = solve(&self.args.with_day(day).with_part(part));
+        output.append(&mut log::take_capture());
+        match result {
+            Err(err) => {
+                output.push(format!("Part {}: {}", part, err));
+                PartCell {
+                    answer: String::from("error"),
+                    time: String::new(),
+                }
+            }
+            Ok(solution) => {
+                let answer = self.args.value_format().format(solution.solution());
+                output.push(format!("Part {}: {}", part, answer));
+                output.extend(solution.warnings().iter().map(ToString::to_string));
+                PartCell {
+                    answer: shorten(&answer),
+                    time: format!("{} us", solution.time().as_micros()),
+                }
+            }
+        }
+    }
+
+    /// Solves both parts of the day in the given row.
+    fn run(&mut self, index: usize) {
+        let day = self.days[index];
+        let mut output = Vec::new();
+        let parts =
+            [SolutionPart::A, SolutionPart::B].map(|part| self.run_part(day, part, &mut output));
+        self.rows[index] = DayRow {
+            parts: Some(parts),
+            output,
+        };
+    }
+
+    /// Draws the table of days, with the output of the selected day below it.
+    fn draw(&self, status: &str) -> AocResult<()> {
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        screen += "Advent of Code 2021 (up/down or j/k select, enter runs, a runs all, q quits)\n";
+        screen += &format!(
+            "  Day  {:<width$} {:>10}  {:<width$} {:>10}\n",
+            "Part A",
+            "Time",
+            "Part B",
+            "Time",
+            width = ANSWER_WIDTH
+        );
+        for (i, (day, row)) in self.days.iter().zip(&self.rows).enumerate() {
+            let (a, b) = match &row.parts {
+                None => (("-", ""), ("-", "")),
+                Some([a, b]) => (
+                    (a.answer.as_str(), a.time.as_str()),
+                    (b.answer.as_str(), b.time.as_str()),
+                ),
+            };
+            let line = format!(
+                "{} {:>3}  {:<width$} {:>10}  {:<width$} {:>10}",
+                if i == self.selected { '>' } else { ' ' },
+                day,
+                a.0,
+                a.1,
+                b.0,
+                b.1,
+                width = ANSWER_WIDTH
+            );
+            if i == self.selected {
+                screen += &format!("\x1b[7m{}\x1b[0m\n", line);
+            } else {
+                screen += &line;
+                screen += "\n";
+            }
+        }
+
+        let day = self.days[self.selected];
+        screen += &format!(
+            "--- Day {}: {} output {}---\n",
+            day,
+            puzzle(day).title,
+            status
+        );
+        let output = &self.rows[self.selected].output;
+        // The last line is left empty so that the screen does not scroll.
+        let room = RawTerminal::lines()
+            .saturating_sub(HEADING_LINES + self.days.len() + 1)
+            .max(1);
+        let lines = output.iter().flat_map(|message| message.lines());
+        let skip = lines.clone().count().saturating_sub(room);
+        for line in lines.skip(skip) {
+            screen += line;
+            screen += "\n";
+        }
+
+        let mut stdout = io::stdout();
+        stdout.write_all(screen.as_bytes()).into_aoc_result()?;
+        stdout.flush().into_aoc_result()
+    }
+}
+
+/// Shows every day in an interactive dashboard that runs days on request,
+/// until quit.
+///
+/// Each run solves both parts with the given options and shows their
+/// answers and times in the table, and everything logged with `-v` or `-vv`
+/// in the pane below it.
+pub fn run(args: &ProgramArgs) -> AocResult<()> {
+    let _terminal = RawTerminal::enter()?;
+    let mut dashboard = Dashboard::new(args);
+    let mut stdin = io::stdin();
+    dashboard.draw("")?;
+    loop {
+        match Key::read(&mut stdin)? {
+            Key::Up => dashboard.selected = dashboard.selected.saturating_sub(1),
+            Key::Down => {
+                dashboard.selected = (dashboard.selected + 1).min(dashboard.days.len() - 1)
+            }
+            Key::Run => {
+                dashboard.draw("(running) ")?;
+                dashboard.run(dashboard.selected);
+            }
+            Key::RunAll => {
+                for i in 0..dashboard.days.len() {
+                    dashboard.selected = i;
+                    dashboard.draw("(running) ")?;
+                    dashboard.run(i);
+                }
+            }
+            Key::Quit => return Ok(()),
+        }
+        dashboard.draw("")?;
+    }
+}